@@ -0,0 +1,52 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use mp_sol_restaking::{
+    MainVaultState, SecondaryVaultState, UnstakeTicket, UserTicketIndex, VaultRegistry,
+    VaultStrategyRelationEntry,
+};
+
+/// all the account types owned by the program
+pub enum MpSolRestakingAccount {
+    MainVaultState(MainVaultState),
+    SecondaryVaultState(SecondaryVaultState),
+    UnstakeTicket(UnstakeTicket),
+    VaultStrategyRelationEntry(VaultStrategyRelationEntry),
+    VaultRegistry(VaultRegistry),
+    UserTicketIndex(UserTicketIndex),
+}
+
+/// decode any program account, dispatching on the account discriminator
+pub fn decode_account(data: &[u8]) -> anchor_lang::Result<MpSolRestakingAccount> {
+    if data.len() < 8 {
+        return Err(anchor_lang::error::ErrorCode::AccountDiscriminatorNotFound.into());
+    }
+    let mut data = data;
+    Ok(match data[..8].try_into().unwrap() {
+        MainVaultState::DISCRIMINATOR => {
+            MpSolRestakingAccount::MainVaultState(MainVaultState::try_deserialize(&mut data)?)
+        }
+        SecondaryVaultState::DISCRIMINATOR => MpSolRestakingAccount::SecondaryVaultState(
+            SecondaryVaultState::try_deserialize(&mut data)?,
+        ),
+        UnstakeTicket::DISCRIMINATOR => {
+            MpSolRestakingAccount::UnstakeTicket(UnstakeTicket::try_deserialize(&mut data)?)
+        }
+        VaultStrategyRelationEntry::DISCRIMINATOR => {
+            MpSolRestakingAccount::VaultStrategyRelationEntry(
+                VaultStrategyRelationEntry::try_deserialize(&mut data)?,
+            )
+        }
+        VaultRegistry::DISCRIMINATOR => {
+            MpSolRestakingAccount::VaultRegistry(VaultRegistry::try_deserialize(&mut data)?)
+        }
+        UserTicketIndex::DISCRIMINATOR => {
+            MpSolRestakingAccount::UserTicketIndex(UserTicketIndex::try_deserialize(&mut data)?)
+        }
+        _ => return Err(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch.into()),
+    })
+}
+
+/// decode a single account type, checking the discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> anchor_lang::Result<T> {
+    let mut data = data;
+    T::try_deserialize(&mut data)
+}
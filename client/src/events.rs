@@ -0,0 +1,68 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use mp_sol_restaking::events::*;
+
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+macro_rules! program_events {
+    ($($event:ident),* $(,)?) => {
+        /// all the events emitted by the program
+        pub enum MpSolRestakingEvent {
+            $($event($event),)*
+        }
+
+        /// decode an event from its serialized data (discriminator + borsh)
+        pub fn decode_event(data: &[u8]) -> Option<MpSolRestakingEvent> {
+            if data.len() < 8 {
+                return None;
+            }
+            let (discriminator, mut event_data) = data.split_at(8);
+            $(
+                if discriminator == $event::DISCRIMINATOR {
+                    return $event::deserialize(&mut event_data)
+                        .ok()
+                        .map(MpSolRestakingEvent::$event);
+                }
+            )*
+            None
+        }
+    };
+}
+
+program_events!(
+    StakeEvent,
+    UnstakeEvent,
+    UpdateVaultTokenSolPriceEvent,
+    VaultPriceCircuitBreakerEvent,
+    VaultPriceAcknowledgedEvent,
+    UpdateAttachedStratLstAmountEvent,
+    TicketClaimEvent,
+    TransferLstToStratEvent,
+    GetLstFromStratEvent,
+    StrategyLossEvent,
+    CheckInvariantsEvent,
+    PauseEvent,
+    UnpauseEvent,
+    PauseAuthorityChangedEvent,
+    HandoverProposedEvent,
+    HandoverAcceptedEvent,
+    HandoverCancelledEvent,
+    ConfigChangeQueuedEvent,
+    ConfigChangeAppliedEvent,
+    ConfigChangeCancelledEvent,
+    UnstakeInstantEvent,
+    TicketTransferEvent,
+    TicketSplitEvent,
+    TicketTokenizedEvent,
+    TicketCancelEvent,
+);
+
+/// decode all the program events from transaction log messages
+/// Note: logs from other programs emitting "Program data:" are ignored if they don't decode
+pub fn parse_events_from_logs(logs: &[String]) -> Vec<MpSolRestakingEvent> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA_LOG_PREFIX))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .filter_map(|data| decode_event(&data))
+        .collect()
+}
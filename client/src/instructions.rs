@@ -0,0 +1,1189 @@
+//! one builder per mp-sol-restaking instruction
+//! PDAs are derived, signers & user accounts are parameters
+use crate::pda::*;
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::AccountMeta,
+    solana_program::instruction::Instruction, system_program, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token, metadata::mpl_token_metadata, token};
+use mp_sol_restaking::{
+    accounts, instruction, ConfigParam, ConfigureSecondaryVaultValues, PriceSourceKind,
+};
+
+fn build_ix(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut account_metas = accounts.to_account_metas(None);
+    account_metas.extend(remaining_accounts);
+    Instruction {
+        program_id: mp_sol_restaking::ID,
+        accounts: account_metas,
+        data: data.data(),
+    }
+}
+
+/// `stake`, `unstake_instant`, `ticket_claim` & `update_vault_token_sol_price` read the LST state from remaining_accounts[0]
+/// None for wSOL, Marinade state for mSOL, SPL/Sanctum stake-pool state for other LSTs
+fn lst_state_remaining_account(lst_state: Option<Pubkey>) -> Vec<AccountMeta> {
+    lst_state
+        .map(|lst_state| vec![AccountMeta::new_readonly(lst_state, false)])
+        .unwrap_or_default()
+}
+
+// ------------------
+// admin
+// ------------------
+pub fn initialize(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    operator_auth: Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::Initialize {
+            admin: *admin,
+            main_state: *main_state,
+            mpsol_mint_pda_authority: find_mpsol_mint_authority(main_state).0,
+            mpsol_token_mint: *mpsol_mint,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Initialize { operator_auth },
+        vec![],
+    )
+}
+
+pub fn init_metadata(admin: &Pubkey, main_state: &Pubkey, mpsol_mint: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::InitMetadata {
+            admin: *admin,
+            main_state: *main_state,
+            mpsol_mint_pda_authority: find_mpsol_mint_authority(main_state).0,
+            mpsol_mint: *mpsol_mint,
+            metadata: find_mpsol_metadata(mpsol_mint).0,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+        },
+        instruction::InitMetadata {},
+        vec![],
+    )
+}
+
+/// Note: the vault lst account (ATA) must be created before, in the same or a previous tx
+/// `price_source_state` is None for wSOL, Marinade state for mSOL, the stake-pool state otherwise
+/// `lst_token_program`: Token or Token-2022, owner of `lst_mint`
+pub fn create_secondary_vault(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    price_source_kind: PriceSourceKind,
+    price_source_state: Option<Pubkey>,
+) -> Instruction {
+    build_ix(
+        accounts::CreateSecondaryVault {
+            admin: *admin,
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            vault_registry: find_vault_registry(main_state).0,
+            associated_token_program: associated_token::ID,
+            lst_token_program: *lst_token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CreateSecondaryVault {
+            price_source_kind,
+            price_source_state: price_source_state.unwrap_or_default(),
+        },
+        lst_state_remaining_account(price_source_state),
+    )
+}
+
+pub fn create_vault_registry(admin: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::CreateVaultRegistry {
+            admin: *admin,
+            main_state: *main_state,
+            vault_registry: find_vault_registry(main_state).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateVaultRegistry {},
+        vec![],
+    )
+}
+
+pub fn register_secondary_vault(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::RegisterSecondaryVault {
+            admin: *admin,
+            main_state: *main_state,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vault_registry: find_vault_registry(main_state).0,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSecondaryVault {},
+        vec![],
+    )
+}
+
+pub fn configure_treasury_account(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    treasury_mpsol_account: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::ConfigureTreasuryAccount {
+            admin: *admin,
+            main_state: *main_state,
+            treasury_mpsol_account: *treasury_mpsol_account,
+        },
+        instruction::ConfigureTreasuryAccount {},
+        vec![],
+    )
+}
+
+fn admin_and_main_state(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+) -> accounts::AdminAndMainStateAccounts {
+    accounts::AdminAndMainStateAccounts {
+        admin: *admin,
+        main_state: *main_state,
+    }
+}
+
+pub fn clear_treasury_account(admin: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ClearTreasuryAccount {},
+        vec![],
+    )
+}
+
+pub fn configure_unstake_waiting_hours(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    hours: u16,
+) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ConfigureUnstakeWaitingHours { hours },
+        vec![],
+    )
+}
+
+pub fn configure_withdrawal_fee(admin: &Pubkey, main_state: &Pubkey, bp: u16) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ConfigureWithdrawalFee { bp },
+        vec![],
+    )
+}
+
+pub fn configure_performance_fee(admin: &Pubkey, main_state: &Pubkey, bp: u16) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ConfigurePerformanceFee { bp },
+        vec![],
+    )
+}
+
+pub fn configure_instant_unstake_fee(admin: &Pubkey, main_state: &Pubkey, bp: u16) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ConfigureInstantUnstakeFee { bp },
+        vec![],
+    )
+}
+
+pub fn cancel_pending_config(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    param: ConfigParam,
+) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::CancelPendingConfig { param },
+        vec![],
+    )
+}
+
+pub fn propose_admin(admin: &Pubkey, main_state: &Pubkey, new_admin: Pubkey) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ProposeAdmin { new_admin },
+        vec![],
+    )
+}
+
+/// signed by main_state.pending_admin
+pub fn accept_admin(new_admin: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::AcceptAdmin {
+            new_admin: *new_admin,
+            main_state: *main_state,
+        },
+        instruction::AcceptAdmin {},
+        vec![],
+    )
+}
+
+pub fn cancel_admin_proposal(admin: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::CancelAdminProposal {},
+        vec![],
+    )
+}
+
+pub fn propose_operator_auth(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    new_operator_auth: Pubkey,
+) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ProposeOperatorAuth { new_operator_auth },
+        vec![],
+    )
+}
+
+/// signed by main_state.pending_operator_auth
+pub fn accept_operator_auth(new_operator_auth: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::AcceptOperatorAuth {
+            new_operator_auth: *new_operator_auth,
+            main_state: *main_state,
+        },
+        instruction::AcceptOperatorAuth {},
+        vec![],
+    )
+}
+
+pub fn cancel_operator_auth_proposal(admin: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::CancelOperatorAuthProposal {},
+        vec![],
+    )
+}
+
+pub fn configure_pause_authority(admin: &Pubkey, main_state: &Pubkey, auth: Pubkey) -> Instruction {
+    build_ix(
+        admin_and_main_state(admin, main_state),
+        instruction::ConfigurePauseAuthority { auth },
+        vec![],
+    )
+}
+
+pub fn realloc_main_state(admin: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::ReallocMainState {
+            admin: *admin,
+            main_state: *main_state,
+            system_program: system_program::ID,
+        },
+        instruction::ReallocMainState {},
+        vec![],
+    )
+}
+
+pub fn realloc_secondary_vault(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::ReallocSecondaryVault {
+            admin: *admin,
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            system_program: system_program::ID,
+        },
+        instruction::ReallocSecondaryVault {},
+        vec![],
+    )
+}
+
+/// `authority`: main_state.pause_authority or main_state.admin
+pub fn pause(authority: &Pubkey, main_state: &Pubkey, pause_ticket_claims: bool) -> Instruction {
+    build_ix(
+        accounts::PauseAuthorityAndMainState {
+            authority: *authority,
+            main_state: *main_state,
+        },
+        instruction::Pause {
+            pause_ticket_claims,
+        },
+        vec![],
+    )
+}
+
+/// `authority`: main_state.pause_authority or main_state.admin
+pub fn unpause(authority: &Pubkey, main_state: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::PauseAuthorityAndMainState {
+            authority: *authority,
+            main_state: *main_state,
+        },
+        instruction::Unpause {},
+        vec![],
+    )
+}
+
+pub fn configure_secondary_vault(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    values: ConfigureSecondaryVaultValues,
+) -> Instruction {
+    build_ix(
+        accounts::ConfigureSecondaryVault {
+            admin: *admin,
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            secondary_state: find_secondary_vault_state(main_state, lst_mint).0,
+        },
+        instruction::ConfigureSecondaryVault { values },
+        vec![],
+    )
+}
+
+/// sets the price source of a vault created before price sources were stored
+pub fn configure_vault_price_source(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    price_source_kind: PriceSourceKind,
+    price_source_state: Option<Pubkey>,
+) -> Instruction {
+    build_ix(
+        accounts::ConfigureSecondaryVault {
+            admin: *admin,
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            secondary_state: find_secondary_vault_state(main_state, lst_mint).0,
+        },
+        instruction::ConfigureVaultPriceSource {
+            price_source_kind,
+            price_source_state: price_source_state.unwrap_or_default(),
+        },
+        lst_state_remaining_account(price_source_state),
+    )
+}
+
+/// accepts the price that tripped the vault price circuit breaker
+pub fn acknowledge_vault_price(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::AcknowledgeVaultPrice {
+            admin: *admin,
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            secondary_state: find_secondary_vault_state(main_state, lst_mint).0,
+        },
+        instruction::AcknowledgeVaultPrice {},
+        vec![],
+    )
+}
+
+pub fn attach_common_strategy_state(
+    admin: &Pubkey,
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    strategy_program: &Pubkey,
+    strategy_state: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::AttachCommonStrategyState {
+            admin: *admin,
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            common_strategy_state: *strategy_state,
+            vault_strategy_relation_entry: find_vault_strategy_relation_entry(strategy_state).0,
+            strategy_program_code: *strategy_program,
+            system_program: system_program::ID,
+        },
+        instruction::AttachCommonStrategyState {},
+        vec![],
+    )
+}
+
+// ------------------
+// cranks
+// ------------------
+
+/// `treasury_mpsol_account`: the one set in main_state, if None any writable account can be used
+#[allow(clippy::too_many_arguments)]
+pub fn update_attached_strat_lst_amount(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    strategy_program: &Pubkey,
+    strategy_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    treasury_mpsol_account: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::UpdateAttachedStratLstAmount {
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vault_strategy_relation_entry: find_vault_strategy_relation_entry(strategy_state).0,
+            common_strategy_state: *strategy_state,
+            strategy_authority: find_strategy_authority(strategy_program, strategy_state).0,
+            strategy_deposit_account: strategy_deposit_account_with_program_id(
+                strategy_program,
+                strategy_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            vault_strat_withdraw_auth: find_vault_strat_withdraw_auth(strategy_state).0,
+            lst_withdraw_account: vault_strat_withdraw_account_with_program_id(
+                strategy_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            mpsol_mint: *mpsol_mint,
+            mpsol_mint_authority: find_mpsol_mint_authority(main_state).0,
+            treasury_mpsol_account: *treasury_mpsol_account,
+            token_program: token::ID,
+            lst_token_program: *lst_token_program,
+        },
+        instruction::UpdateAttachedStratLstAmount {},
+        vec![],
+    )
+}
+
+pub fn update_vault_token_sol_price(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_state: Option<Pubkey>,
+) -> Instruction {
+    build_ix(
+        accounts::UpdateVaultTokenSolPrice {
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            secondary_state: find_secondary_vault_state(main_state, lst_mint).0,
+        },
+        instruction::UpdateVaultTokenSolPrice {},
+        lst_state_remaining_account(lst_state),
+    )
+}
+
+pub fn update_vault_ticket_target(
+    main_state: &Pubkey,
+    operator_auth: &Pubkey,
+    lst_mint: &Pubkey,
+    new_ticket_target_sol_amount: u64,
+) -> Instruction {
+    build_ix(
+        accounts::UpdateVaultTicketTarget {
+            main_state: *main_state,
+            operator_auth: *operator_auth,
+            lst_mint: *lst_mint,
+            secondary_state: find_secondary_vault_state(main_state, lst_mint).0,
+        },
+        instruction::UpdateVaultTicketTarget {
+            new_ticket_target_sol_amount,
+        },
+        vec![],
+    )
+}
+
+pub fn apply_pending_config(main_state: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::ApplyPendingConfig {
+            main_state: *main_state,
+        },
+        instruction::ApplyPendingConfig {},
+        vec![],
+    )
+}
+
+/// extends a ticket created with the v1.0 layout, permissionless
+pub fn realloc_unstake_ticket(payer: &Pubkey, ticket_account: &Pubkey) -> Instruction {
+    build_ix(
+        accounts::ReallocUnstakeTicket {
+            payer: *payer,
+            ticket_account: *ticket_account,
+            system_program: system_program::ID,
+        },
+        instruction::ReallocUnstakeTicket {},
+        vec![],
+    )
+}
+
+/// extends a vault-strategy relation entry created with the v1.0 layout, permissionless
+pub fn realloc_vault_strategy_relation_entry(
+    payer: &Pubkey,
+    vault_strategy_relation_entry: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::ReallocVaultStrategyRelationEntry {
+            payer: *payer,
+            vault_strategy_relation_entry: *vault_strategy_relation_entry,
+            system_program: system_program::ID,
+        },
+        instruction::ReallocVaultStrategyRelationEntry {},
+        vec![],
+    )
+}
+
+/// `lst_mints`: (lst_mint, lst_token_program) of all the vaults, in vault-registry order.
+/// past ~14 vaults the accounts only fit in a tx using an address lookup table
+/// `caller` must be main_state.operator_auth or a pause authority if `pause_deposits_on_violation`
+pub fn check_invariants(
+    caller: &Pubkey,
+    main_state: &Pubkey,
+    lst_mints: &[(Pubkey, Pubkey)],
+    pause_deposits_on_violation: bool,
+) -> Instruction {
+    let remaining_accounts = lst_mints
+        .iter()
+        .flat_map(|(lst_mint, lst_token_program)| {
+            let vault_state = find_secondary_vault_state(main_state, lst_mint).0;
+            [
+                if pause_deposits_on_violation {
+                    AccountMeta::new(vault_state, false)
+                } else {
+                    AccountMeta::new_readonly(vault_state, false)
+                },
+                AccountMeta::new_readonly(
+                    vault_lst_account_with_program_id(main_state, lst_mint, lst_token_program),
+                    false,
+                ),
+            ]
+        })
+        .collect();
+    build_ix(
+        accounts::CheckInvariants {
+            caller: *caller,
+            main_state: *main_state,
+            vault_registry: find_vault_registry(main_state).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+        },
+        instruction::CheckInvariants {
+            pause_deposits_on_violation,
+        },
+        remaining_accounts,
+    )
+}
+
+pub fn get_lst_from_strat(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    strategy_state: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::GetLstFromStrat {
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            vault_strategy_relation_entry: find_vault_strategy_relation_entry(strategy_state).0,
+            common_strategy_state: *strategy_state,
+            vault_strat_withdraw_auth: find_vault_strat_withdraw_auth(strategy_state).0,
+            lst_withdraw_account: vault_strat_withdraw_account_with_program_id(
+                strategy_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            lst_token_program: *lst_token_program,
+        },
+        instruction::GetLstFromStrat {},
+        vec![],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_lst_to_strat(
+    main_state: &Pubkey,
+    operator_auth: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    strategy_program: &Pubkey,
+    strategy_state: &Pubkey,
+    lst_amount: u64,
+) -> Instruction {
+    build_ix(
+        accounts::TransferLstToStrat {
+            main_state: *main_state,
+            operator_auth: *operator_auth,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            vault_strategy_relation_entry: find_vault_strategy_relation_entry(strategy_state).0,
+            strategy_program_code: *strategy_program,
+            common_strategy_state: *strategy_state,
+            strategy_authority: find_strategy_authority(strategy_program, strategy_state).0,
+            strategy_deposit_account: strategy_deposit_account_with_program_id(
+                strategy_program,
+                strategy_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            lst_token_program: *lst_token_program,
+        },
+        instruction::TransferLstToStrat { lst_amount },
+        vec![],
+    )
+}
+
+pub fn set_next_withdraw_amount(
+    main_state: &Pubkey,
+    operator_auth: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    strategy_state: &Pubkey,
+    lst_amount: u64,
+) -> Instruction {
+    build_ix(
+        accounts::SetNextWithdrawAmount {
+            main_state: *main_state,
+            operator_auth: *operator_auth,
+            lst_mint: *lst_mint,
+            vault_strategy_relation_entry: find_vault_strategy_relation_entry(strategy_state).0,
+            common_strategy_state: *strategy_state,
+            vault_strat_withdraw_auth: find_vault_strat_withdraw_auth(strategy_state).0,
+            lst_withdraw_account: vault_strat_withdraw_account_with_program_id(
+                strategy_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            lst_token_program: *lst_token_program,
+        },
+        instruction::SetNextWithdrawAmount { lst_amount },
+        vec![],
+    )
+}
+
+// ------------------
+// users
+// ------------------
+#[allow(clippy::too_many_arguments)]
+pub fn stake(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    mpsol_mint: &Pubkey,
+    depositor: &Pubkey,
+    depositor_lst_account: &Pubkey,
+    depositor_mpsol_account: &Pubkey,
+    lst_state: Option<Pubkey>,
+    lst_amount: u64,
+    ref_code: u32,
+) -> Instruction {
+    build_ix(
+        accounts::Stake {
+            main_state: *main_state,
+            lst_mint: *lst_mint,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            depositor: *depositor,
+            depositor_lst_account: *depositor_lst_account,
+            mpsol_mint: *mpsol_mint,
+            mpsol_mint_authority: find_mpsol_mint_authority(main_state).0,
+            depositor_mpsol_account: *depositor_mpsol_account,
+            token_program: token::ID,
+            lst_token_program: *lst_token_program,
+        },
+        instruction::Stake {
+            lst_amount,
+            ref_code,
+        },
+        lst_state_remaining_account(lst_state),
+    )
+}
+
+/// stake native SOL, the lamports are wrapped into the wSOL vault
+pub fn stake_sol(
+    main_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    depositor: &Pubkey,
+    depositor_mpsol_account: &Pubkey,
+    lamports: u64,
+    ref_code: u32,
+) -> Instruction {
+    let lst_mint = mp_sol_restaking::WSOL_MINT;
+    build_ix(
+        accounts::StakeSol {
+            main_state: *main_state,
+            lst_mint,
+            vault_state: find_secondary_vault_state(main_state, &lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account(main_state, &lst_mint),
+            depositor: *depositor,
+            mpsol_mint: *mpsol_mint,
+            mpsol_mint_authority: find_mpsol_mint_authority(main_state).0,
+            depositor_mpsol_account: *depositor_mpsol_account,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::StakeSol { lamports, ref_code },
+        vec![],
+    )
+}
+
+/// the optional accounts of `unstake`/`unstake_pda_ticket` to get the ticket as an NFT
+struct TicketNftAccounts {
+    ticket_nft_authority: Pubkey,
+    ticket_nft_mint: Pubkey,
+    unstaker_nft_account: Pubkey,
+    metadata: Pubkey,
+    master_edition: Pubkey,
+}
+
+impl TicketNftAccounts {
+    fn new(main_state: &Pubkey, unstaker: &Pubkey, ticket_account: &Pubkey) -> Self {
+        let ticket_nft_mint = find_ticket_nft_mint(ticket_account).0;
+        let (metadata, master_edition) = find_ticket_nft_metadata(&ticket_nft_mint);
+        Self {
+            ticket_nft_authority: find_ticket_nft_authority(main_state).0,
+            ticket_nft_mint,
+            unstaker_nft_account: associated_token::get_associated_token_address(
+                unstaker,
+                &ticket_nft_mint,
+            ),
+            metadata,
+            master_edition,
+        }
+    }
+}
+
+/// `new_ticket_account` is a new keypair, must sign the tx
+/// `treasury_mpsol_account`: the one set in main_state, if None any writable account can be used
+/// `tokenized`: the ticket is minted as an NFT to the unstaker, see `tokenize_ticket`
+#[allow(clippy::too_many_arguments)]
+pub fn unstake(
+    main_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    unstaker: &Pubkey,
+    unstaker_mpsol_account: &Pubkey,
+    treasury_mpsol_account: &Pubkey,
+    new_ticket_account: &Pubkey,
+    mpsol_amount: u64,
+    tokenized: bool,
+) -> Instruction {
+    let nft = tokenized.then(|| TicketNftAccounts::new(main_state, unstaker, new_ticket_account));
+    build_ix(
+        accounts::Unstake {
+            main_state: *main_state,
+            unstaker: *unstaker,
+            unstaker_mpsol_account: *unstaker_mpsol_account,
+            mpsol_mint: *mpsol_mint,
+            treasury_mpsol_account: *treasury_mpsol_account,
+            new_ticket_account: *new_ticket_account,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            ticket_nft_authority: nft.as_ref().map(|nft| nft.ticket_nft_authority),
+            ticket_nft_mint: nft.as_ref().map(|nft| nft.ticket_nft_mint),
+            unstaker_nft_account: nft.as_ref().map(|nft| nft.unstaker_nft_account),
+            metadata: nft.as_ref().map(|nft| nft.metadata),
+            master_edition: nft.as_ref().map(|nft| nft.master_edition),
+            rent: tokenized.then_some(anchor_lang::solana_program::sysvar::rent::ID),
+            associated_token_program: tokenized.then_some(associated_token::ID),
+            token_metadata_program: tokenized.then_some(mpl_token_metadata::ID),
+        },
+        instruction::Unstake { mpsol_amount },
+        vec![],
+    )
+}
+
+/// `treasury_mpsol_account`: the one set in main_state, if None any writable account can be used
+/// `unstaker_lst_account`: token account of `lst_mint` owned by `unstaker`
+#[allow(clippy::too_many_arguments)]
+pub fn unstake_instant(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    mpsol_mint: &Pubkey,
+    unstaker: &Pubkey,
+    unstaker_mpsol_account: &Pubkey,
+    unstaker_lst_account: &Pubkey,
+    treasury_mpsol_account: &Pubkey,
+    lst_state: Option<Pubkey>,
+    mpsol_amount: u64,
+    min_lst_amount: u64,
+) -> Instruction {
+    build_ix(
+        accounts::UnstakeInstant {
+            main_state: *main_state,
+            unstaker: *unstaker,
+            unstaker_mpsol_account: *unstaker_mpsol_account,
+            mpsol_mint: *mpsol_mint,
+            treasury_mpsol_account: *treasury_mpsol_account,
+            lst_mint: *lst_mint,
+            unstaker_lst_account: *unstaker_lst_account,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            token_program: token::ID,
+            lst_token_program: *lst_token_program,
+        },
+        instruction::UnstakeInstant {
+            mpsol_amount,
+            min_lst_amount,
+        },
+        lst_state_remaining_account(lst_state),
+    )
+}
+
+/// anyone can pay for the index of `beneficiary`
+pub fn create_user_ticket_index(
+    payer: &Pubkey,
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::CreateUserTicketIndex {
+            payer: *payer,
+            beneficiary: *beneficiary,
+            main_state: *main_state,
+            user_ticket_index: find_user_ticket_index(main_state, beneficiary).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateUserTicketIndex {},
+        vec![],
+    )
+}
+
+/// `next_ticket_seq`: from the unstaker `UserTicketIndex`, the ticket is created at that seq
+/// `treasury_mpsol_account`: the one set in main_state, if None any writable account can be used
+/// `tokenized`: the ticket is minted as an NFT to the unstaker, see `tokenize_ticket`
+#[allow(clippy::too_many_arguments)]
+pub fn unstake_pda_ticket(
+    main_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    unstaker: &Pubkey,
+    unstaker_mpsol_account: &Pubkey,
+    treasury_mpsol_account: &Pubkey,
+    next_ticket_seq: u64,
+    mpsol_amount: u64,
+    tokenized: bool,
+) -> Instruction {
+    let new_ticket_account = find_pda_ticket(main_state, unstaker, next_ticket_seq).0;
+    let nft = tokenized.then(|| TicketNftAccounts::new(main_state, unstaker, &new_ticket_account));
+    build_ix(
+        accounts::UnstakePdaTicket {
+            main_state: *main_state,
+            unstaker: *unstaker,
+            unstaker_mpsol_account: *unstaker_mpsol_account,
+            mpsol_mint: *mpsol_mint,
+            treasury_mpsol_account: *treasury_mpsol_account,
+            user_ticket_index: find_user_ticket_index(main_state, unstaker).0,
+            new_ticket_account,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            ticket_nft_authority: nft.as_ref().map(|nft| nft.ticket_nft_authority),
+            ticket_nft_mint: nft.as_ref().map(|nft| nft.ticket_nft_mint),
+            unstaker_nft_account: nft.as_ref().map(|nft| nft.unstaker_nft_account),
+            metadata: nft.as_ref().map(|nft| nft.metadata),
+            master_edition: nft.as_ref().map(|nft| nft.master_edition),
+            rent: tokenized.then_some(anchor_lang::solana_program::sysvar::rent::ID),
+            associated_token_program: tokenized.then_some(associated_token::ID),
+            token_metadata_program: tokenized.then_some(mpl_token_metadata::ID),
+        },
+        instruction::UnstakePdaTicket { mpsol_amount },
+        vec![],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn ticket_claim(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+    beneficiary_lst_account: &Pubkey,
+    lst_state: Option<Pubkey>,
+    withdraw_sol_value_amount: u64,
+    min_lst_amount: u64,
+) -> Instruction {
+    build_ix(
+        accounts::TicketClaim {
+            main_state: *main_state,
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            lst_mint: *lst_mint,
+            beneficiary_lst_account: *beneficiary_lst_account,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            lst_token_program: *lst_token_program,
+            system_program: system_program::ID,
+        },
+        instruction::TicketClaim {
+            withdraw_sol_value_amount,
+            min_lst_amount,
+        },
+        lst_state_remaining_account(lst_state),
+    )
+}
+
+/// one entry per vault to claim from:
+/// (lst_mint, lst_token_program, beneficiary_lst_account, lst_state, sol_value_amount, min_lst_amount)
+/// lst_state: see `lst_state_remaining_account`, None for wSOL
+/// min_lst_amount: net of the Token-2022 transfer fee
+pub fn ticket_claim_multi(
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+    claims: &[(Pubkey, Pubkey, Pubkey, Option<Pubkey>, u64, u64)],
+) -> Instruction {
+    let mut remaining_accounts =
+        Vec::with_capacity(claims.len() * mp_sol_restaking::TICKET_CLAIM_MULTI_ACCOUNTS_PER_VAULT);
+    for (lst_mint, lst_token_program, beneficiary_lst_account, lst_state, _, _) in claims {
+        let vault_state = find_secondary_vault_state(main_state, lst_mint).0;
+        remaining_accounts.extend([
+            AccountMeta::new(vault_state, false),
+            AccountMeta::new_readonly(*lst_mint, false),
+            AccountMeta::new(
+                vault_lst_account_with_program_id(main_state, lst_mint, lst_token_program),
+                false,
+            ),
+            AccountMeta::new(*beneficiary_lst_account, false),
+            // wSOL price is not read from an account, use the vault state as placeholder
+            AccountMeta::new_readonly(lst_state.unwrap_or(vault_state), false),
+            AccountMeta::new_readonly(*lst_token_program, false),
+        ]);
+    }
+    build_ix(
+        accounts::TicketClaimMulti {
+            main_state: *main_state,
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            system_program: system_program::ID,
+        },
+        instruction::TicketClaimMulti {
+            sol_value_amounts: claims.iter().map(|claim| claim.4).collect(),
+            min_lst_amounts: claims.iter().map(|claim| claim.5).collect(),
+        },
+        remaining_accounts,
+    )
+}
+
+/// converts an existing ticket into an NFT ticket, for new tickets see `unstake` `tokenized`
+pub fn tokenize_ticket(
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+) -> Instruction {
+    let ticket_nft_mint = find_ticket_nft_mint(ticket_account).0;
+    let (metadata, master_edition) = find_ticket_nft_metadata(&ticket_nft_mint);
+    build_ix(
+        accounts::TokenizeTicket {
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            ticket_nft_authority: find_ticket_nft_authority(main_state).0,
+            ticket_nft_mint,
+            beneficiary_nft_account: associated_token::get_associated_token_address(
+                beneficiary,
+                &ticket_nft_mint,
+            ),
+            metadata,
+            master_edition,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+        },
+        instruction::TokenizeTicket {},
+        vec![],
+    )
+}
+
+/// claim an NFT ticket, `holder` must hold the ticket NFT in its ATA
+/// on full claim the NFT is burned
+#[allow(clippy::too_many_arguments)]
+pub fn ticket_claim_nft(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+    holder: &Pubkey,
+    ticket_account: &Pubkey,
+    holder_lst_account: &Pubkey,
+    lst_state: Option<Pubkey>,
+    withdraw_sol_value_amount: u64,
+    min_lst_amount: u64,
+) -> Instruction {
+    let ticket_nft_mint = find_ticket_nft_mint(ticket_account).0;
+    let (metadata, master_edition) = find_ticket_nft_metadata(&ticket_nft_mint);
+    build_ix(
+        accounts::TicketClaimNft {
+            main_state: *main_state,
+            holder: *holder,
+            ticket_account: *ticket_account,
+            ticket_nft_mint,
+            holder_nft_account: associated_token::get_associated_token_address(
+                holder,
+                &ticket_nft_mint,
+            ),
+            metadata,
+            master_edition,
+            lst_mint: *lst_mint,
+            holder_lst_account: *holder_lst_account,
+            vault_state: find_secondary_vault_state(main_state, lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account_with_program_id(
+                main_state,
+                lst_mint,
+                lst_token_program,
+            ),
+            token_program: token::ID,
+            lst_token_program: *lst_token_program,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+        },
+        instruction::TicketClaimNft {
+            withdraw_sol_value_amount,
+            min_lst_amount,
+        },
+        lst_state_remaining_account(lst_state),
+    )
+}
+
+/// claim from the wSOL vault, the beneficiary receives native SOL
+pub fn ticket_claim_sol(
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+    withdraw_sol_value_amount: u64,
+) -> Instruction {
+    let lst_mint = mp_sol_restaking::WSOL_MINT;
+    build_ix(
+        accounts::TicketClaimSol {
+            main_state: *main_state,
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            lst_mint,
+            vault_state: find_secondary_vault_state(main_state, &lst_mint).0,
+            vaults_ata_pda_auth: find_vaults_ata_auth(main_state).0,
+            vault_lst_account: vault_lst_account(main_state, &lst_mint),
+            unwrap_wsol_account: find_unwrap_wsol_account(main_state, beneficiary).0,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::TicketClaimSol {
+            withdraw_sol_value_amount,
+        },
+        vec![],
+    )
+}
+
+/// `new_beneficiary_next_ticket_seq`: from the new beneficiary `UserTicketIndex`,
+/// the ticket is re-created at that seq
+pub fn transfer_ticket(
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+    new_beneficiary: Pubkey,
+    new_beneficiary_next_ticket_seq: u64,
+) -> Instruction {
+    build_ix(
+        accounts::TransferTicket {
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            new_beneficiary_ticket_index: find_user_ticket_index(main_state, &new_beneficiary).0,
+            new_ticket_account: find_pda_ticket(
+                main_state,
+                &new_beneficiary,
+                new_beneficiary_next_ticket_seq,
+            )
+            .0,
+            system_program: system_program::ID,
+        },
+        instruction::TransferTicket { new_beneficiary },
+        vec![],
+    )
+}
+
+/// `next_ticket_seq`: from the beneficiary `UserTicketIndex`, the new ticket is created at that seq
+pub fn split_ticket(
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+    next_ticket_seq: u64,
+    sol_value_amount: u64,
+) -> Instruction {
+    build_ix(
+        accounts::SplitTicket {
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            user_ticket_index: find_user_ticket_index(main_state, beneficiary).0,
+            new_ticket_account: find_pda_ticket(main_state, beneficiary, next_ticket_seq).0,
+            system_program: system_program::ID,
+        },
+        instruction::SplitTicket { sol_value_amount },
+        vec![],
+    )
+}
+
+/// re-stake the ticket sol-value, `beneficiary_mpsol_account` receives the mpSOL
+pub fn cancel_ticket(
+    main_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    beneficiary: &Pubkey,
+    ticket_account: &Pubkey,
+    beneficiary_mpsol_account: &Pubkey,
+) -> Instruction {
+    build_ix(
+        accounts::CancelTicket {
+            main_state: *main_state,
+            beneficiary: *beneficiary,
+            ticket_account: *ticket_account,
+            mpsol_mint: *mpsol_mint,
+            mpsol_mint_authority: find_mpsol_mint_authority(main_state).0,
+            beneficiary_mpsol_account: *beneficiary_mpsol_account,
+            token_program: token::ID,
+        },
+        instruction::CancelTicket {},
+        vec![],
+    )
+}
@@ -0,0 +1,11 @@
+//! Rust client for the mp-sol-restaking program
+//! - `pda`: every PDA used by the program
+//! - `instructions`: one builder per program instruction, with the correct account metas
+//! - `accounts`: decode program accounts
+//! - `events`: decode program events from transaction logs
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+
+pub use mp_sol_restaking::ID as PROGRAM_ID;
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::{metadata::mpl_token_metadata, token};
+
+// strategy-auth, strategy deposit account, vault-strat-entry & vault-strat-withdraw-auth PDAs
+pub use strategy_interface::pda::*;
+
+/// secondary-vault state, PDA(mp-sol-restaking, [main_state, lst_mint])
+pub fn find_secondary_vault_state(main_state: &Pubkey, lst_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[&main_state.to_bytes(), &lst_mint.to_bytes()],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// vaults ATA auth PDA, owner of the secondary-vaults LST accounts
+pub fn find_vaults_ata_auth(main_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::VAULTS_ATA_AUTH_SEED,
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// secondary-vault LST account, where the lst tokens are stored while not in strategies
+pub fn vault_lst_account(main_state: &Pubkey, lst_mint: &Pubkey) -> Pubkey {
+    vault_lst_account_with_program_id(main_state, lst_mint, &token::ID)
+}
+
+/// secondary-vault LST account for a lst mint of `lst_token_program` (Token or Token-2022)
+pub fn vault_lst_account_with_program_id(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+) -> Pubkey {
+    let (authority, _) = find_vaults_ata_auth(main_state);
+    get_associated_token_address_with_program_id(&authority, lst_mint, lst_token_program)
+}
+
+/// mpSOL mint authority PDA
+pub fn find_mpsol_mint_authority(main_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::MAIN_VAULT_MINT_AUTH_SEED,
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// vault registry, list of the secondary vaults of a main state
+pub fn find_vault_registry(main_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::VAULT_REGISTRY_SEED,
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// Metaplex metadata account for the mpSOL mint
+pub fn find_mpsol_metadata(mpsol_mint: &Pubkey) -> (Pubkey, u8) {
+    mpl_token_metadata::accounts::Metadata::find_pda(mpsol_mint)
+}
+
+/// temporary wSOL account used by `ticket_claim_sol` to unwrap to native SOL
+pub fn find_unwrap_wsol_account(main_state: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::UNWRAP_WSOL_SEED,
+            &beneficiary.to_bytes(),
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// mint, freeze & metadata update authority of the ticket NFTs
+pub fn find_ticket_nft_authority(main_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::TICKET_NFT_AUTH_SEED,
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// NFT mint of a tokenized ticket
+pub fn find_ticket_nft_mint(ticket_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            mp_sol_restaking::TICKET_NFT_MINT_SEED,
+            &ticket_account.to_bytes(),
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// Metaplex metadata & master edition accounts of a ticket NFT
+pub fn find_ticket_nft_metadata(ticket_nft_mint: &Pubkey) -> (Pubkey, Pubkey) {
+    (
+        mpl_token_metadata::accounts::Metadata::find_pda(ticket_nft_mint).0,
+        mpl_token_metadata::accounts::MasterEdition::find_pda(ticket_nft_mint).0,
+    )
+}
+
+/// per-user ticket index, counter of the PDA tickets
+pub fn find_user_ticket_index(main_state: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::USER_TICKET_INDEX_SEED,
+            &beneficiary.to_bytes(),
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// PDA unstake-ticket number `seq` of a user
+pub fn find_pda_ticket(main_state: &Pubkey, beneficiary: &Pubkey, seq: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            &main_state.to_bytes(),
+            mp_sol_restaking::TICKET_SEED,
+            &beneficiary.to_bytes(),
+            &seq.to_le_bytes(),
+        ],
+        &mp_sol_restaking::ID,
+    )
+}
+
+/// all the PDA ticket addresses of a user, `next_ticket_seq` from the user ticket index
+/// closed tickets are included, they don't exist anymore (fetch with getMultipleAccounts)
+pub fn user_pda_tickets(
+    main_state: &Pubkey,
+    beneficiary: &Pubkey,
+    next_ticket_seq: u64,
+) -> Vec<Pubkey> {
+    (0..next_ticket_seq)
+        .map(|seq| find_pda_ticket(main_state, beneficiary, seq).0)
+        .collect()
+}
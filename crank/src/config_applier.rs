@@ -0,0 +1,120 @@
+use crate::rpc::{fetch_main_state, send_instructions_with_logs};
+use anchor_lang::prelude::Pubkey;
+use mp_sol_restaking::{ConfigParam, MainVaultState, PendingConfigValue};
+use mp_sol_restaking_client::events::{parse_events_from_logs, MpSolRestakingEvent};
+use mp_sol_restaking_client::instructions;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::Keypair;
+
+fn pending_changes(main_state: &MainVaultState) -> Vec<(ConfigParam, u16, PendingConfigValue)> {
+    [
+        (
+            ConfigParam::WithdrawFeeBp,
+            main_state.withdraw_fee_bp,
+            main_state.pending_withdraw_fee_bp,
+        ),
+        (
+            ConfigParam::PerformanceFeeBp,
+            main_state.performance_fee_bp,
+            main_state.pending_performance_fee_bp,
+        ),
+        (
+            ConfigParam::UnstakeTicketWaitingHours,
+            main_state.unstake_ticket_waiting_hours,
+            main_state.pending_unstake_ticket_waiting_hours,
+        ),
+        (
+            ConfigParam::InstantUnstakeFeeBp,
+            main_state.instant_unstake_fee_bp,
+            main_state.pending_instant_unstake_fee_bp,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(param, current, pending)| pending.map(|pending| (param, current, pending)))
+    .collect()
+}
+
+/// applies the queued config changes that reached their effective timestamp (permissionless)
+/// With `dry_run` the tx is only simulated.
+pub fn apply_config(
+    client: &RpcClient,
+    payer: &Keypair,
+    main_state: &Pubkey,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let state = fetch_main_state(client, main_state)?;
+    let now_timestamp = client.get_block_time(client.get_slot()?)? as u64;
+    let pending = pending_changes(&state);
+    if pending.is_empty() {
+        println!("no pending config changes");
+        return Ok(());
+    }
+    let mut effective = 0;
+    for (param, current, change) in &pending {
+        let is_effective = change.effective_timestamp <= now_timestamp;
+        println!(
+            "{param:?}: {current} -> {}, effective at {} ({})",
+            change.value,
+            change.effective_timestamp,
+            if is_effective { "ready" } else { "waiting" }
+        );
+        if is_effective {
+            effective += 1;
+        }
+    }
+    if effective == 0 {
+        return Ok(());
+    }
+
+    let instruction = instructions::apply_pending_config(main_state);
+    let (signature, logs) = send_instructions_with_logs(client, payer, &[instruction], dry_run)?;
+    println!(
+        "{}: {}",
+        if dry_run { "SIMULATED" } else { "OK" },
+        signature.unwrap_or_default()
+    );
+    for event in parse_events_from_logs(&logs) {
+        if let MpSolRestakingEvent::ConfigChangeAppliedEvent(event) = event {
+            println!(
+                "  applied {:?}: {} -> {}",
+                event.param, event.old_value, event.new_value
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mp_sol_restaking::test_utils::zeroed_account;
+
+    #[test]
+    fn pending_changes_lists_only_the_queued_params() {
+        let mut main_state: MainVaultState = zeroed_account();
+        assert!(pending_changes(&main_state).is_empty());
+
+        main_state.withdraw_fee_bp = 10;
+        main_state.pending_withdraw_fee_bp = Some(PendingConfigValue {
+            value: 50,
+            effective_timestamp: 100,
+        });
+        main_state.pending_instant_unstake_fee_bp = Some(PendingConfigValue {
+            value: 1,
+            effective_timestamp: 200,
+        });
+        let changes: Vec<_> = pending_changes(&main_state)
+            .into_iter()
+            .map(|(param, current, pending)| {
+                (param, current, pending.value, pending.effective_timestamp)
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (ConfigParam::WithdrawFeeBp, 10, 50, 100),
+                (ConfigParam::InstantUnstakeFeeBp, 0, 1, 200),
+            ]
+        );
+    }
+}
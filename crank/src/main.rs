@@ -0,0 +1,103 @@
+//! mpSOL restaking off-chain cranks
+//!
+//! fulfill-tickets: the "ticket-fulfiller crank"
+//!   reads outstanding unstake tickets, sets per-vault `tickets_target_sol_amount`,
+//!   asks strategies to free lst (`set_next_withdraw_amount`) and sweeps it back (`get_lst_from_strat`)
+//!
+//! refresh-prices: calls the permissionless `update_vault_token_sol_price` for every secondary vault,
+//!   resolving the Marinade state or SPL/Sanctum stake-pool of each lst_mint,
+//!   and reports price changes from `UpdateVaultTokenSolPriceEvent`
+//!   and circuit-breaker alerts from `VaultPriceCircuitBreakerEvent`
+//!
+//! apply-config: calls the permissionless `apply_pending_config` when a queued (timelocked)
+//!   fee or waiting-hours change reached its effective timestamp
+//!
+//! use --dry-run to print the plan (or simulate the txs) without sending transactions
+//! use --url http://127.0.0.1:8899 (default) to run against a local test-validator
+mod config_applier;
+mod price_refresh;
+mod rpc;
+mod ticket_fulfiller;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::read_keypair_file};
+
+#[derive(Parser)]
+#[command(version, about = "mpSOL restaking cranks")]
+struct Cli {
+    /// RPC url
+    #[arg(long, default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    /// keypair file, pays for the txs. Must be main_state.operator_auth for operator instructions
+    #[arg(long, default_value = "~/.config/solana/id.json")]
+    keypair: String,
+
+    /// main state address
+    #[arg(long)]
+    main_state: Pubkey,
+
+    /// print the plan, do not send transactions
+    #[arg(long)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// set vault ticket targets, request & sweep lst from strategies to cover outstanding tickets
+    FulfillTickets,
+    /// refresh the lst/SOL price of all secondary vaults
+    RefreshPrices {
+        /// vaults refreshed per transaction
+        #[arg(long, default_value_t = 5)]
+        batch_size: usize,
+    },
+    /// apply the queued config changes that reached their effective timestamp
+    ApplyConfig,
+}
+
+fn expand_tilde(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let client = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let keypair_path = expand_tilde(&cli.keypair);
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|err| anyhow::anyhow!("{err}"))
+        .with_context(|| format!("reading keypair {keypair_path}"))?;
+
+    match cli.command {
+        Command::FulfillTickets => {
+            let snapshot = rpc::fetch_snapshot(&client, &cli.main_state)?;
+            let plan = ticket_fulfiller::plan(&snapshot)?;
+            ticket_fulfiller::print_plan(&snapshot, &plan);
+            if !cli.dry_run {
+                ticket_fulfiller::execute(&client, &payer, &cli.main_state, &plan)?;
+            }
+        }
+        Command::RefreshPrices { batch_size } => {
+            price_refresh::refresh_prices(
+                &client,
+                &payer,
+                &cli.main_state,
+                batch_size,
+                cli.dry_run,
+            )?;
+        }
+        Command::ApplyConfig => {
+            config_applier::apply_config(&client, &payer, &cli.main_state, cli.dry_run)?;
+        }
+    }
+    Ok(())
+}
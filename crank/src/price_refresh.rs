@@ -0,0 +1,177 @@
+use crate::rpc::{fetch_secondary_vaults, find_program_accounts, send_instructions_with_logs};
+use anchor_lang::prelude::Pubkey;
+use mp_sol_restaking::state::external::marinade_pool_state::{
+    MARINADE_MSOL_MINT, MARINADE_STATE_ADDRESS,
+};
+use mp_sol_restaking::state::external::spl_stake_pool_state::SPL_STAKE_POOL_PROGRAM;
+use mp_sol_restaking::{
+    PriceSourceKind, SecondaryVaultState, SANCTUM_SPL_1, SANCTUM_SPL_2, WSOL_MINT,
+};
+use mp_sol_restaking_client::events::{parse_events_from_logs, MpSolRestakingEvent};
+use mp_sol_restaking_client::instructions;
+use shared_lib::TWO_POW_32;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::Keypair;
+
+/// SplStakePoolState layout: account_type (1), manager, staker, stake_deposit_authority (32 each),
+/// stake_withdraw_bump_seed (1), validator_list, reserve_stake (32 each), pool_mint
+const SPL_STAKE_POOL_ACCOUNT_TYPE_OFFSET: usize = 0;
+const SPL_STAKE_POOL_ACCOUNT_TYPE_STAKE_POOL: u8 = 1;
+const SPL_STAKE_POOL_POOL_MINT_OFFSET: usize = 1 + 32 * 3 + 1 + 32 * 2;
+
+const STAKE_POOL_PROGRAMS: [Pubkey; 3] = [SPL_STAKE_POOL_PROGRAM, SANCTUM_SPL_1, SANCTUM_SPL_2];
+
+/// the account `update_vault_token_sol_price` expects in remaining_accounts[0]
+/// the stored price source state if the vault has one, otherwise (vaults created before v1.1)
+/// None for wSOL, Marinade state for mSOL, the stake-pool whose pool_mint is lst_mint otherwise
+fn resolve_lst_state(
+    client: &RpcClient,
+    vault: &SecondaryVaultState,
+) -> anyhow::Result<Option<Pubkey>> {
+    if vault.price_source_kind != PriceSourceKind::Unset {
+        // kinds without a state account store the default pubkey
+        return Ok(
+            (vault.price_source_state != Pubkey::default()).then_some(vault.price_source_state)
+        );
+    }
+    let lst_mint = &vault.lst_mint;
+    match *lst_mint {
+        WSOL_MINT => Ok(None),
+        MARINADE_MSOL_MINT => Ok(Some(MARINADE_STATE_ADDRESS)),
+        _ => {
+            for program_id in STAKE_POOL_PROGRAMS {
+                let pools = find_program_accounts(
+                    client,
+                    &program_id,
+                    vec![
+                        (
+                            SPL_STAKE_POOL_ACCOUNT_TYPE_OFFSET,
+                            vec![SPL_STAKE_POOL_ACCOUNT_TYPE_STAKE_POOL],
+                        ),
+                        (
+                            SPL_STAKE_POOL_POOL_MINT_OFFSET,
+                            lst_mint.to_bytes().to_vec(),
+                        ),
+                    ],
+                )?;
+                match pools.as_slice() {
+                    [] => continue,
+                    [(address, _)] => return Ok(Some(*address)),
+                    _ => anyhow::bail!(
+                        "more than one stake-pool of {program_id} with pool_mint {lst_mint}"
+                    ),
+                }
+            }
+            anyhow::bail!("no Marinade, SPL or Sanctum stake-pool found for lst {lst_mint}")
+        }
+    }
+}
+
+fn format_price_p32(price_p32: u64) -> String {
+    format!("{:.9}", price_p32 as f64 / TWO_POW_32 as f64)
+}
+
+fn report_events(logs: &[String]) -> usize {
+    let mut changes = 0;
+    for event in parse_events_from_logs(logs) {
+        match event {
+            MpSolRestakingEvent::UpdateVaultTokenSolPriceEvent(event) => {
+                changes += 1;
+                println!(
+                    "  lst {} price {} -> {}, sol-value {} -> {}, backing_sol_value {}",
+                    event.lst_mint,
+                    format_price_p32(event.old_price_p32),
+                    format_price_p32(event.new_price_p32),
+                    event.old_sol_value,
+                    event.new_sol_value,
+                    event.main_vault_backing_sol_value
+                );
+            }
+            MpSolRestakingEvent::VaultPriceCircuitBreakerEvent(event) => {
+                println!(
+                    "  ALERT lst {} price {} -> {} exceeds the limits (+{}bp/-{}bp vs {}), vault frozen until acknowledge_vault_price",
+                    event.lst_mint,
+                    format_price_p32(event.stored_price_p32),
+                    format_price_p32(event.new_price_p32),
+                    event.max_price_increase_bp,
+                    event.max_price_decrease_bp,
+                    format_price_p32(event.reference_price_p32),
+                );
+            }
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// refresh the lst/SOL price of every secondary vault of `main_state`,
+/// `batch_size` vaults per tx. With `dry_run` the txs are only simulated.
+pub fn refresh_prices(
+    client: &RpcClient,
+    payer: &Keypair,
+    main_state: &Pubkey,
+    batch_size: usize,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let vaults = fetch_secondary_vaults(client, main_state)?;
+    let mut refreshable: Vec<(&SecondaryVaultState, Option<Pubkey>)> = vec![];
+    for (address, vault) in &vaults {
+        match resolve_lst_state(client, vault) {
+            Ok(lst_state) => {
+                println!(
+                    "vault {address} lst {} price {} lst state {}",
+                    vault.lst_mint,
+                    format_price_p32(vault.lst_sol_price_p32),
+                    lst_state.map_or("-".to_string(), |lst_state| lst_state.to_string())
+                );
+                refreshable.push((vault, lst_state));
+            }
+            Err(err) => println!("SKIP vault {address}: {err}"),
+        }
+    }
+
+    let mut changes = 0;
+    for batch in refreshable.chunks(batch_size.max(1)) {
+        let batch_instructions: Vec<_> = batch
+            .iter()
+            .map(|(vault, lst_state)| {
+                instructions::update_vault_token_sol_price(main_state, &vault.lst_mint, *lst_state)
+            })
+            .collect();
+        let mints: Vec<String> = batch
+            .iter()
+            .map(|(vault, _)| vault.lst_mint.to_string())
+            .collect();
+        match send_instructions_with_logs(client, payer, &batch_instructions, dry_run) {
+            Ok((signature, logs)) => {
+                println!(
+                    "{} [{}]: {}",
+                    if dry_run { "SIMULATED" } else { "OK" },
+                    mints.join(", "),
+                    signature.unwrap_or_default()
+                );
+                changes += report_events(&logs);
+            }
+            Err(err) => println!("ERR [{}]: {err}", mints.join(", ")),
+        }
+    }
+    println!(
+        "{} vaults, {} refreshed, {} price changes",
+        vaults.len(),
+        refreshable.len(),
+        changes
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_are_printed_with_9_decimals() {
+        assert_eq!(format_price_p32(TWO_POW_32), "1.000000000");
+        assert_eq!(format_price_p32(TWO_POW_32 + TWO_POW_32 / 4), "1.250000000");
+        assert_eq!(format_price_p32(0), "0.000000000");
+    }
+}
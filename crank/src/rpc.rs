@@ -0,0 +1,287 @@
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use anchor_spl::token_interface::TokenAccount;
+use anyhow::Context;
+use mp_sol_restaking::{
+    MainVaultState, SecondaryVaultState, UnstakeTicket, VaultRegistry, VaultStrategyRelationEntry,
+};
+use mp_sol_restaking_client::pda::{
+    find_secondary_vault_state, find_vault_registry, vault_strat_withdraw_account_with_program_id,
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, instruction::Instruction};
+use solana_sdk::{
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use solana_transaction_status::UiTransactionEncoding;
+
+pub struct StrategySnapshot {
+    pub address: Pubkey,
+    pub entry: VaultStrategyRelationEntry,
+    /// None if the vault-strat withdraw ATA was not created yet
+    pub lst_withdraw_account_amount: Option<u64>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Pubkey,
+}
+
+/// on-chain state needed by the cranks, read at a single point in time
+pub struct ProgramSnapshot {
+    pub main_state: MainVaultState,
+    pub now_timestamp: u64,
+    /// in vault-registry order
+    pub vaults: Vec<(Pubkey, SecondaryVaultState)>,
+    pub tickets: Vec<(Pubkey, UnstakeTicket)>,
+    /// sorted by address
+    pub strategies: Vec<StrategySnapshot>,
+}
+
+/// addresses (and data) of the accounts owned by `program_id` matching all the `(offset, bytes)` filters
+pub fn find_program_accounts(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    memcmp_filters: Vec<(usize, Vec<u8>)>,
+) -> anyhow::Result<Vec<(Pubkey, solana_sdk::account::Account)>> {
+    let filters = memcmp_filters
+        .into_iter()
+        .map(|(offset, bytes)| RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, bytes)))
+        .collect();
+    Ok(client.get_program_accounts_with_config(
+        program_id,
+        RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?)
+}
+
+fn get_program_accounts<T: AccountDeserialize + Discriminator>(
+    client: &RpcClient,
+    main_state_offset: Option<(usize, &Pubkey)>,
+) -> anyhow::Result<Vec<(Pubkey, T)>> {
+    let mut filters = vec![(0, T::DISCRIMINATOR.to_vec())];
+    if let Some((offset, main_state)) = main_state_offset {
+        filters.push((offset, main_state.to_bytes().to_vec()));
+    }
+    let accounts = find_program_accounts(client, &mp_sol_restaking::ID, filters)?;
+    let mut decoded = accounts
+        .into_iter()
+        .map(|(address, account)| {
+            T::try_deserialize(&mut account.data.as_slice())
+                .map(|state| (address, state))
+                .with_context(|| format!("decoding account {address}"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    decoded.sort_by_key(|(address, _)| *address);
+    Ok(decoded)
+}
+
+pub fn fetch_main_state(client: &RpcClient, main_state: &Pubkey) -> anyhow::Result<MainVaultState> {
+    let account = client
+        .get_account(main_state)
+        .with_context(|| format!("reading main state {main_state}"))?;
+    Ok(MainVaultState::try_deserialize(
+        &mut account.data.as_slice(),
+    )?)
+}
+
+/// all secondary vaults of a main state, in vault-registry order
+pub fn fetch_secondary_vaults(
+    client: &RpcClient,
+    main_state: &Pubkey,
+) -> anyhow::Result<Vec<(Pubkey, SecondaryVaultState)>> {
+    let registry = client
+        .get_account(&find_vault_registry(main_state).0)
+        .ok()
+        .and_then(|account| VaultRegistry::try_deserialize(&mut account.data.as_slice()).ok());
+    let Some(registry) = registry else {
+        return scan_secondary_vaults(client, main_state);
+    };
+    let mut vaults = Vec::with_capacity(registry.vaults.len());
+    for chunk in registry.vaults.chunks(100) {
+        for (address, account) in chunk.iter().zip(client.get_multiple_accounts(chunk)?) {
+            let account = account.with_context(|| format!("vault {address} not found"))?;
+            vaults.push((
+                *address,
+                SecondaryVaultState::try_deserialize(&mut account.data.as_slice())
+                    .with_context(|| format!("decoding vault {address}"))?,
+            ));
+        }
+    }
+    Ok(vaults)
+}
+
+/// for main states without vault registry
+/// secondary vaults do not store main_state, the address is PDA(main_state, lst_mint)
+fn scan_secondary_vaults(
+    client: &RpcClient,
+    main_state: &Pubkey,
+) -> anyhow::Result<Vec<(Pubkey, SecondaryVaultState)>> {
+    Ok(get_program_accounts::<SecondaryVaultState>(client, None)?
+        .into_iter()
+        .filter(|(address, vault)| {
+            find_secondary_vault_state(main_state, &vault.lst_mint).0 == *address
+        })
+        .collect())
+}
+
+/// owner program of each account, None if the account does not exist
+fn fetch_account_owners(
+    client: &RpcClient,
+    addresses: &[Pubkey],
+) -> anyhow::Result<Vec<Option<Pubkey>>> {
+    let mut owners = Vec::with_capacity(addresses.len());
+    // getMultipleAccounts is limited to 100 accounts per call
+    for chunk in addresses.chunks(100) {
+        for account in client.get_multiple_accounts(chunk)? {
+            owners.push(account.map(|account| account.owner));
+        }
+    }
+    Ok(owners)
+}
+
+fn fetch_token_amounts(
+    client: &RpcClient,
+    addresses: &[Pubkey],
+) -> anyhow::Result<Vec<Option<u64>>> {
+    let mut amounts = Vec::with_capacity(addresses.len());
+    // getMultipleAccounts is limited to 100 accounts per call
+    for chunk in addresses.chunks(100) {
+        for account in client.get_multiple_accounts(chunk)? {
+            amounts.push(account.and_then(|account| {
+                TokenAccount::try_deserialize(&mut account.data.as_slice())
+                    .ok()
+                    .map(|token_account| token_account.amount)
+            }));
+        }
+    }
+    Ok(amounts)
+}
+
+pub fn fetch_snapshot(client: &RpcClient, main_state: &Pubkey) -> anyhow::Result<ProgramSnapshot> {
+    let main_state_account = fetch_main_state(client, main_state)?;
+    let vaults = fetch_secondary_vaults(client, main_state)?;
+    // UnstakeTicket & VaultStrategyRelationEntry: main_state is the first field
+    let tickets = get_program_accounts::<UnstakeTicket>(client, Some((8, main_state)))?;
+    let entries =
+        get_program_accounts::<VaultStrategyRelationEntry>(client, Some((8, main_state)))?;
+    let lst_mints: Vec<Pubkey> = entries.iter().map(|(_, entry)| entry.lst_mint).collect();
+    let lst_token_programs = fetch_account_owners(client, &lst_mints)?
+        .into_iter()
+        .zip(&lst_mints)
+        .map(|(owner, lst_mint)| owner.with_context(|| format!("lst mint {lst_mint} not found")))
+        .collect::<anyhow::Result<Vec<Pubkey>>>()?;
+    let withdraw_accounts: Vec<Pubkey> = entries
+        .iter()
+        .zip(&lst_token_programs)
+        .map(|((_, entry), lst_token_program)| {
+            vault_strat_withdraw_account_with_program_id(
+                &entry.common_strategy_state,
+                &entry.lst_mint,
+                lst_token_program,
+            )
+        })
+        .collect();
+    let withdraw_amounts = fetch_token_amounts(client, &withdraw_accounts)?;
+    let strategies = entries
+        .into_iter()
+        .zip(withdraw_amounts)
+        .zip(lst_token_programs)
+        .map(
+            |(((address, entry), lst_withdraw_account_amount), lst_token_program)| {
+                StrategySnapshot {
+                    address,
+                    entry,
+                    lst_withdraw_account_amount,
+                    lst_token_program,
+                }
+            },
+        )
+        .collect();
+    let now_timestamp = client.get_block_time(client.get_slot()?)? as u64;
+    Ok(ProgramSnapshot {
+        main_state: main_state_account,
+        now_timestamp,
+        vaults,
+        tickets,
+        strategies,
+    })
+}
+
+fn sign_transaction(
+    client: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+) -> anyhow::Result<Transaction> {
+    let blockhash = client.get_latest_blockhash()?;
+    Ok(Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    ))
+}
+
+/// send one tx per instruction-group, so a failing action does not block the rest
+pub fn send_instructions(
+    client: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+) -> anyhow::Result<String> {
+    let tx = sign_transaction(client, payer, instructions)?;
+    let signature = client.send_and_confirm_transaction_with_spinner_and_commitment(
+        &tx,
+        CommitmentConfig::confirmed(),
+    )?;
+    Ok(signature.to_string())
+}
+
+fn get_transaction_logs(client: &RpcClient, signature: &Signature) -> anyhow::Result<Vec<String>> {
+    let tx = client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    Ok(tx
+        .transaction
+        .meta
+        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+        .unwrap_or_default())
+}
+
+/// like `send_instructions`, but returns the tx logs, to decode program events.
+/// With `simulate`, the tx is only simulated
+pub fn send_instructions_with_logs(
+    client: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    simulate: bool,
+) -> anyhow::Result<(Option<String>, Vec<String>)> {
+    let tx = sign_transaction(client, payer, instructions)?;
+    if simulate {
+        let result = client.simulate_transaction(&tx)?.value;
+        let logs = result.logs.unwrap_or_default();
+        if let Some(err) = result.err {
+            anyhow::bail!("simulation failed: {err}, logs: {logs:#?}");
+        }
+        return Ok((None, logs));
+    }
+    let signature = client.send_and_confirm_transaction_with_spinner_and_commitment(
+        &tx,
+        CommitmentConfig::confirmed(),
+    )?;
+    let logs = get_transaction_logs(client, &signature)?;
+    Ok((Some(signature.to_string()), logs))
+}
@@ -0,0 +1,514 @@
+use crate::rpc::{send_instructions, ProgramSnapshot};
+use anchor_lang::prelude::Pubkey;
+use mp_sol_restaking_client::instructions;
+use mp_sol_restaking_client::pda::find_vault_strat_withdraw_auth;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::fmt;
+
+pub enum Action {
+    /// vault-strat withdraw ATA does not exist, required by `set_next_withdraw_amount`
+    CreateLstWithdrawAccount {
+        lst_mint: Pubkey,
+        lst_token_program: Pubkey,
+        strategy_state: Pubkey,
+    },
+    /// strategy already moved lst to the withdraw ATA, move it into the vault
+    GetLstFromStrat {
+        lst_mint: Pubkey,
+        lst_token_program: Pubkey,
+        strategy_state: Pubkey,
+        lst_amount: u64,
+    },
+    SetNextWithdrawAmount {
+        lst_mint: Pubkey,
+        lst_token_program: Pubkey,
+        strategy_state: Pubkey,
+        lst_amount: u64,
+    },
+    UpdateVaultTicketTarget {
+        lst_mint: Pubkey,
+        ticket_target_sol_amount: u64,
+    },
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::CreateLstWithdrawAccount {
+                lst_mint,
+                strategy_state,
+                ..
+            } => write!(
+                f,
+                "create lst withdraw account, lst {lst_mint}, strategy {strategy_state}"
+            ),
+            Action::GetLstFromStrat {
+                lst_mint,
+                strategy_state,
+                lst_amount,
+                ..
+            } => write!(
+                f,
+                "get_lst_from_strat, lst {lst_mint}, strategy {strategy_state}, lst amount {lst_amount}"
+            ),
+            Action::SetNextWithdrawAmount {
+                lst_mint,
+                strategy_state,
+                lst_amount,
+                ..
+            } => write!(
+                f,
+                "set_next_withdraw_amount, lst {lst_mint}, strategy {strategy_state}, lst amount {lst_amount}"
+            ),
+            Action::UpdateVaultTicketTarget {
+                lst_mint,
+                ticket_target_sol_amount,
+            } => write!(
+                f,
+                "update_vault_ticket_target, lst {lst_mint}, sol-value {ticket_target_sol_amount}"
+            ),
+        }
+    }
+}
+
+/// how much sol-value each vault must keep locally to cover the outstanding tickets
+/// 1st: use lst already stored in the vaults (no strategy withdraw needed)
+/// 2nd: the shortfall is assigned to the vaults with more sol-value in strategies
+fn compute_vault_targets(snapshot: &ProgramSnapshot) -> anyhow::Result<Vec<u64>> {
+    let mut remaining = snapshot.main_state.outstanding_tickets_sol_value;
+    let mut targets = vec![0; snapshot.vaults.len()];
+
+    for (target, (_, vault)) in targets.iter_mut().zip(&snapshot.vaults) {
+        let local_sol_value = vault.lst_amount_to_sol_value(vault.locally_stored_amount)?;
+        *target = local_sol_value.min(remaining);
+        remaining -= *target;
+    }
+
+    let in_strategies_sol_values = snapshot
+        .vaults
+        .iter()
+        .map(|(_, vault)| vault.lst_amount_to_sol_value(vault.in_strategies_amount))
+        .collect::<Result<Vec<u64>, _>>()?;
+    let mut by_in_strategies: Vec<usize> = (0..snapshot.vaults.len()).collect();
+    by_in_strategies.sort_by_key(|index| std::cmp::Reverse(in_strategies_sol_values[*index]));
+    for index in by_in_strategies {
+        if remaining == 0 {
+            break;
+        }
+        let extra = in_strategies_sol_values[index].min(remaining);
+        targets[index] += extra;
+        remaining -= extra;
+    }
+    Ok(targets)
+}
+
+/// decide the actions to cover all outstanding tickets
+/// order: sweeps first, then withdraw requests, then vault targets
+pub fn plan(snapshot: &ProgramSnapshot) -> anyhow::Result<Vec<Action>> {
+    let mut sweeps = vec![];
+    let mut requests = vec![];
+    let mut target_updates = vec![];
+
+    let targets = compute_vault_targets(snapshot)?;
+    for ((_, vault), target_sol_value) in snapshot.vaults.iter().zip(targets) {
+        if vault.tickets_target_sol_amount != target_sol_value {
+            target_updates.push(Action::UpdateVaultTicketTarget {
+                lst_mint: vault.lst_mint,
+                ticket_target_sol_amount: target_sol_value,
+            });
+        }
+        if vault.lst_sol_price_p32 == 0 {
+            // price never computed, can not convert sol-value to lst
+            continue;
+        }
+
+        let mut locally_stored_amount = vault.locally_stored_amount;
+        let mut strategies: Vec<_> = snapshot
+            .strategies
+            .iter()
+            .filter(|strategy| strategy.entry.lst_mint == vault.lst_mint)
+            .map(|strategy| {
+                // simulate get_lst_from_strat effects
+                let withdraw_account_amount = strategy.lst_withdraw_account_amount.unwrap_or(0);
+                let swept = withdraw_account_amount.min(strategy.entry.next_withdraw_lst_amount);
+                if swept > 0 {
+                    sweeps.push(Action::GetLstFromStrat {
+                        lst_mint: vault.lst_mint,
+                        lst_token_program: strategy.lst_token_program,
+                        strategy_state: strategy.entry.common_strategy_state,
+                        lst_amount: swept,
+                    });
+                    locally_stored_amount += swept;
+                }
+                (
+                    strategy,
+                    strategy.entry.next_withdraw_lst_amount - swept,
+                    strategy
+                        .entry
+                        .last_read_strat_lst_amount
+                        .saturating_sub(swept),
+                    withdraw_account_amount - swept,
+                )
+            })
+            .collect();
+        // request from the strategies with more lst first
+        strategies.sort_by_key(|(_, _, strat_lst_amount, _)| std::cmp::Reverse(*strat_lst_amount));
+
+        let mut missing_lst = vault
+            .sol_value_to_lst_amount(target_sol_value)?
+            .saturating_sub(locally_stored_amount);
+        for (strategy, next_withdraw_lst_amount, strat_lst_amount, withdraw_account_leftover) in
+            strategies
+        {
+            let requested = missing_lst.min(strat_lst_amount);
+            missing_lst -= requested;
+            // set_next_withdraw_amount requires lst_amount >= lst_withdraw_account.amount
+            let lst_amount = requested.max(withdraw_account_leftover);
+            if lst_amount == next_withdraw_lst_amount {
+                continue;
+            }
+            if strategy.lst_withdraw_account_amount.is_none() {
+                requests.push(Action::CreateLstWithdrawAccount {
+                    lst_mint: vault.lst_mint,
+                    lst_token_program: strategy.lst_token_program,
+                    strategy_state: strategy.entry.common_strategy_state,
+                });
+            }
+            requests.push(Action::SetNextWithdrawAmount {
+                lst_mint: vault.lst_mint,
+                lst_token_program: strategy.lst_token_program,
+                strategy_state: strategy.entry.common_strategy_state,
+                lst_amount,
+            });
+        }
+    }
+
+    Ok(sweeps
+        .into_iter()
+        .chain(requests)
+        .chain(target_updates)
+        .collect())
+}
+
+pub fn print_plan(snapshot: &ProgramSnapshot, plan: &[Action]) {
+    let tickets_sol_value: u64 = snapshot
+        .tickets
+        .iter()
+        .map(|(_, ticket)| ticket.ticket_sol_value)
+        .sum();
+    let due_sol_value: u64 = snapshot
+        .tickets
+        .iter()
+        .filter(|(_, ticket)| ticket.ticket_due_timestamp <= snapshot.now_timestamp)
+        .map(|(_, ticket)| ticket.ticket_sol_value)
+        .sum();
+    println!(
+        "outstanding_tickets_sol_value {}, {} tickets, sum {}, due now {}",
+        snapshot.main_state.outstanding_tickets_sol_value,
+        snapshot.tickets.len(),
+        tickets_sol_value,
+        due_sol_value
+    );
+    if tickets_sol_value != snapshot.main_state.outstanding_tickets_sol_value {
+        println!("WARN: sum of tickets != main_state.outstanding_tickets_sol_value");
+    }
+    for (address, vault) in &snapshot.vaults {
+        println!(
+            "vault {address} lst {} locally_stored {} in_strategies {} tickets_target_sol_amount {}",
+            vault.lst_mint,
+            vault.locally_stored_amount,
+            vault.in_strategies_amount,
+            vault.tickets_target_sol_amount
+        );
+    }
+    for strategy in &snapshot.strategies {
+        println!(
+            "strategy entry {} lst {} strategy {} last_read {} next_withdraw {} withdraw account {:?}",
+            strategy.address,
+            strategy.entry.lst_mint,
+            strategy.entry.common_strategy_state,
+            strategy.entry.last_read_strat_lst_amount,
+            strategy.entry.next_withdraw_lst_amount,
+            strategy.lst_withdraw_account_amount
+        );
+    }
+    if plan.is_empty() {
+        println!("nothing to do");
+    }
+    for (index, action) in plan.iter().enumerate() {
+        println!("{}. {action}", index + 1);
+    }
+}
+
+pub fn execute(
+    client: &RpcClient,
+    operator: &Keypair,
+    main_state: &Pubkey,
+    plan: &[Action],
+) -> anyhow::Result<()> {
+    let operator_auth = operator.pubkey();
+    for action in plan {
+        let instruction = match action {
+            Action::CreateLstWithdrawAccount {
+                lst_mint,
+                lst_token_program,
+                strategy_state,
+            } => create_associated_token_account_idempotent(
+                &operator_auth,
+                &find_vault_strat_withdraw_auth(strategy_state).0,
+                lst_mint,
+                lst_token_program,
+            ),
+            Action::GetLstFromStrat {
+                lst_mint,
+                lst_token_program,
+                strategy_state,
+                ..
+            } => instructions::get_lst_from_strat(
+                main_state,
+                lst_mint,
+                lst_token_program,
+                strategy_state,
+            ),
+            Action::SetNextWithdrawAmount {
+                lst_mint,
+                lst_token_program,
+                strategy_state,
+                lst_amount,
+            } => instructions::set_next_withdraw_amount(
+                main_state,
+                &operator_auth,
+                lst_mint,
+                lst_token_program,
+                strategy_state,
+                *lst_amount,
+            ),
+            Action::UpdateVaultTicketTarget {
+                lst_mint,
+                ticket_target_sol_amount,
+            } => instructions::update_vault_ticket_target(
+                main_state,
+                &operator_auth,
+                lst_mint,
+                *ticket_target_sol_amount,
+            ),
+        };
+        match send_instructions(client, operator, &[instruction]) {
+            Ok(signature) => println!("OK {action}: {signature}"),
+            Err(err) => println!("ERR {action}: {err}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::StrategySnapshot;
+    use mp_sol_restaking::test_utils::zeroed_account;
+    use mp_sol_restaking::{MainVaultState, SecondaryVaultState, VaultStrategyRelationEntry};
+    use shared_lib::TWO_POW_32;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+
+    /// a 9-decimals vault with a 1:1 LST/SOL price
+    fn vault(
+        locally_stored_amount: u64,
+        in_strategies_amount: u64,
+    ) -> (Pubkey, SecondaryVaultState) {
+        let mut vault: SecondaryVaultState = zeroed_account();
+        vault.lst_mint = Pubkey::new_unique();
+        vault.lst_sol_price_p32 = TWO_POW_32;
+        vault.locally_stored_amount = locally_stored_amount;
+        vault.in_strategies_amount = in_strategies_amount;
+        vault.vault_total_lst_amount = locally_stored_amount + in_strategies_amount;
+        (Pubkey::new_unique(), vault)
+    }
+
+    fn strategy(
+        vault: &SecondaryVaultState,
+        strat_lst_amount: u64,
+        next_withdraw_lst_amount: u64,
+        lst_withdraw_account_amount: Option<u64>,
+    ) -> StrategySnapshot {
+        StrategySnapshot {
+            address: Pubkey::new_unique(),
+            entry: VaultStrategyRelationEntry {
+                main_state: Pubkey::default(),
+                lst_mint: vault.lst_mint,
+                common_strategy_state: Pubkey::new_unique(),
+                strategy_program_code: Pubkey::new_unique(),
+                next_withdraw_lst_amount,
+                tickets_target_sol_amount: 0,
+                last_read_strat_lst_amount: strat_lst_amount,
+                last_read_strat_lst_timestamp: 0,
+                unrecovered_loss_lst_amount: 0,
+            },
+            lst_withdraw_account_amount,
+            lst_token_program: anchor_spl::token::ID,
+        }
+    }
+
+    fn snapshot(
+        outstanding_tickets_sol_value: u64,
+        vaults: Vec<(Pubkey, SecondaryVaultState)>,
+        strategies: Vec<StrategySnapshot>,
+    ) -> ProgramSnapshot {
+        let mut main_state: MainVaultState = zeroed_account();
+        main_state.outstanding_tickets_sol_value = outstanding_tickets_sol_value;
+        ProgramSnapshot {
+            main_state,
+            now_timestamp: 0,
+            vaults,
+            tickets: vec![],
+            strategies,
+        }
+    }
+
+    fn snapshot_with_outstanding(
+        mut snapshot: ProgramSnapshot,
+        outstanding: u64,
+    ) -> ProgramSnapshot {
+        snapshot.main_state.outstanding_tickets_sol_value = outstanding;
+        snapshot
+    }
+
+    fn plan_lines(snapshot: &ProgramSnapshot) -> Vec<String> {
+        plan(snapshot)
+            .unwrap()
+            .iter()
+            .map(Action::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn local_liquidity_is_used_first() {
+        let snapshot = snapshot(
+            12 * ONE_SOL,
+            vec![vault(10 * ONE_SOL, 50 * ONE_SOL), vault(5 * ONE_SOL, 0)],
+            vec![],
+        );
+        assert_eq!(
+            compute_vault_targets(&snapshot).unwrap(),
+            vec![10 * ONE_SOL, 2 * ONE_SOL]
+        );
+        let lst_mints: Vec<_> = snapshot.vaults.iter().map(|(_, v)| v.lst_mint).collect();
+        assert_eq!(
+            plan_lines(&snapshot),
+            vec![
+                format!(
+                    "update_vault_ticket_target, lst {}, sol-value {}",
+                    lst_mints[0],
+                    10 * ONE_SOL
+                ),
+                format!(
+                    "update_vault_ticket_target, lst {}, sol-value {}",
+                    lst_mints[1],
+                    2 * ONE_SOL
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn shortfall_goes_to_the_vaults_with_more_in_strategies() {
+        let snapshot = snapshot(
+            30 * ONE_SOL,
+            vec![
+                vault(5 * ONE_SOL, 10 * ONE_SOL),
+                vault(5 * ONE_SOL, 40 * ONE_SOL),
+            ],
+            vec![],
+        );
+        assert_eq!(
+            compute_vault_targets(&snapshot).unwrap(),
+            vec![5 * ONE_SOL, 25 * ONE_SOL]
+        );
+
+        // more tickets than sol-value: every vault gives all it has
+        let snapshot = snapshot_with_outstanding(snapshot, 100 * ONE_SOL);
+        assert_eq!(
+            compute_vault_targets(&snapshot).unwrap(),
+            vec![15 * ONE_SOL, 45 * ONE_SOL]
+        );
+    }
+
+    #[test]
+    fn withdraws_are_requested_from_the_strategies_with_more_lst() {
+        let (vault_address, vault_state) = vault(5 * ONE_SOL, 30 * ONE_SOL);
+        let small = strategy(&vault_state, 10 * ONE_SOL, 0, Some(0));
+        let large = strategy(&vault_state, 20 * ONE_SOL, 0, None);
+        let (small_state, large_state) = (
+            small.entry.common_strategy_state,
+            large.entry.common_strategy_state,
+        );
+        let lst_mint = vault_state.lst_mint;
+        let snapshot = snapshot(
+            30 * ONE_SOL,
+            vec![(vault_address, vault_state)],
+            vec![small, large],
+        );
+        assert_eq!(
+            plan_lines(&snapshot),
+            vec![
+                format!("create lst withdraw account, lst {lst_mint}, strategy {large_state}"),
+                format!(
+                    "set_next_withdraw_amount, lst {lst_mint}, strategy {large_state}, lst amount {}",
+                    20 * ONE_SOL
+                ),
+                format!(
+                    "set_next_withdraw_amount, lst {lst_mint}, strategy {small_state}, lst amount {}",
+                    5 * ONE_SOL
+                ),
+                format!("update_vault_ticket_target, lst {lst_mint}, sol-value {}", 30 * ONE_SOL),
+            ]
+        );
+    }
+
+    #[test]
+    fn moved_lst_is_swept_before_new_requests() {
+        let (vault_address, mut vault_state) = vault(0, 10 * ONE_SOL);
+        vault_state.tickets_target_sol_amount = 4 * ONE_SOL;
+        // the strategy already moved the requested lst to the withdraw account
+        let strategy = strategy(&vault_state, 10 * ONE_SOL, 4 * ONE_SOL, Some(4 * ONE_SOL));
+        let strategy_state = strategy.entry.common_strategy_state;
+        let lst_mint = vault_state.lst_mint;
+        let snapshot = snapshot(
+            4 * ONE_SOL,
+            vec![(vault_address, vault_state)],
+            vec![strategy],
+        );
+        assert_eq!(
+            plan_lines(&snapshot),
+            vec![format!(
+                "get_lst_from_strat, lst {lst_mint}, strategy {strategy_state}, lst amount {}",
+                4 * ONE_SOL
+            )]
+        );
+    }
+
+    #[test]
+    fn vaults_without_a_price_get_no_withdraw_requests() {
+        let (vault_address, mut vault_state) = vault(0, 10 * ONE_SOL);
+        // the price was never computed, the vault has no sol-value to cover tickets
+        vault_state.lst_sol_price_p32 = 0;
+        vault_state.tickets_target_sol_amount = ONE_SOL;
+        let strategy = strategy(&vault_state, 10 * ONE_SOL, 0, Some(0));
+        let snapshot = snapshot(
+            4 * ONE_SOL,
+            vec![(vault_address, vault_state)],
+            vec![strategy],
+        );
+        let plan = plan(&snapshot).unwrap();
+        assert_eq!(plan.len(), 1);
+        assert!(matches!(
+            plan[0],
+            Action::UpdateVaultTicketTarget {
+                ticket_target_sol_amount: 0,
+                ..
+            }
+        ));
+    }
+}
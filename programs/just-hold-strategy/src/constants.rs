@@ -0,0 +1,6 @@
+use anchor_lang::prelude::*;
+
+// generic word-seed for b"authority"
+// the vault computes the strategy ATA from it
+#[constant]
+pub const AUTHORITY_SEED: &[u8] = strategy_interface::STRAT_AUTHORITY_SEED;
@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+// NOTE: Anchor adds 6000 to user error codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Nothing to move to the withdraw account")]
+    NothingToMove, // 6000 0x1770
+}
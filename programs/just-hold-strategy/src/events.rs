@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct MoveToWithdrawAccountEvent {
+    pub strategy_state: Pubkey,
+    pub lst_mint: Pubkey,
+    pub next_withdraw_lst_amount: u64,
+    pub lst_amount: u64,
+}
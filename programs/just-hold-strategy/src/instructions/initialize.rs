@@ -0,0 +1,49 @@
+use crate::{constants::*, JustHoldStrategyState};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+/// create the strategy state and the strategy ATA
+/// after this, the mpSOL admin can attach the strategy state to the secondary-vault
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(init, payer = payer, space = 8 + JustHoldStrategyState::INIT_SPACE)]
+    pub state: Account<'info, JustHoldStrategyState>,
+
+    /// Token or Token-2022 mint
+    pub lst_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA strat authority, owner of the strategy ATA
+    #[account(
+        seeds = [
+            AUTHORITY_SEED,
+            &state.key().to_bytes()
+        ],
+        bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// strategy ATA, the vault sends the LSTs here
+    #[account(init, payer = payer,
+        associated_token::mint = lst_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program,
+    )]
+    pub lst_deposit: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// Token or Token-2022, owner of the lst mint
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_initialize(ctx: Context<Initialize>) -> Result<()> {
+    ctx.accounts.state.set_inner(JustHoldStrategyState {
+        lst_mint: ctx.accounts.lst_mint.key(),
+        strat_total_lst_amount: 0,
+    });
+    Ok(())
+}
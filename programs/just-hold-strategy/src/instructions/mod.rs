@@ -0,0 +1,5 @@
+pub mod initialize;
+pub mod move_to_withdraw_account;
+
+pub use initialize::*;
+pub use move_to_withdraw_account::*;
@@ -0,0 +1,116 @@
+use crate::{constants::*, error::ErrorCode, JustHoldStrategyState};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use strategy_interface::{
+    VaultStrategyRelationEntry, MP_SOL_RESTAKING_PROGRAM_ID, VAULT_STRAT_ENTRY_SEED,
+    VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED,
+};
+
+#[derive(Accounts)]
+/// permissionless
+/// move the lst requested by the vault (`next_withdraw_lst_amount`) into `lst_withdraw_account`
+/// so the vault can take it with `get_lst_from_strat`
+pub struct MoveToWithdrawAccount<'info> {
+    #[account(has_one = lst_mint)]
+    pub state: Account<'info, JustHoldStrategyState>,
+
+    #[account()]
+    pub lst_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA strat authority, owner of the strategy ATA
+    #[account(
+        seeds = [
+            AUTHORITY_SEED,
+            &state.key().to_bytes()
+        ],
+        bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program,
+    )]
+    pub lst_deposit: InterfaceAccount<'info, TokenAccount>,
+
+    /// vault->strat relation entry, created when the mpSOL admin attached this strategy
+    /// CHECK: mp-sol-restaking account, manually deserialized
+    #[account(
+        owner = MP_SOL_RESTAKING_PROGRAM_ID,
+        seeds = [
+            VAULT_STRAT_ENTRY_SEED,
+            &state.key().to_bytes(),
+        ],
+        bump,
+        seeds::program = MP_SOL_RESTAKING_PROGRAM_ID
+    )]
+    pub vault_strategy_relation_entry: UncheckedAccount<'info>,
+
+    /// CHECK: vault PDA, owner of the temp-ATA to move lst from strat back to the vault
+    #[account(
+        seeds = [
+            VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED,
+            &state.key().to_bytes(),
+        ],
+        bump,
+        seeds::program = MP_SOL_RESTAKING_PROGRAM_ID
+    )]
+    pub vault_strat_withdraw_auth: UncheckedAccount<'info>,
+
+    /// temp-ATA to move lst from strat back to the vault
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = vault_strat_withdraw_auth,
+        associated_token::token_program = token_program,
+    )]
+    pub lst_withdraw_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token or Token-2022, owner of the lst mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handle_move_to_withdraw_account(ctx: Context<MoveToWithdrawAccount>) -> Result<()> {
+    let next_withdraw_lst_amount = VaultStrategyRelationEntry::try_from_account_data(
+        &ctx.accounts
+            .vault_strategy_relation_entry
+            .try_borrow_data()?,
+    )?
+    .next_withdraw_lst_amount;
+    // what's already in lst_withdraw_account counts towards the requested amount
+    let lst_amount = next_withdraw_lst_amount
+        .saturating_sub(ctx.accounts.lst_withdraw_account.amount)
+        .min(ctx.accounts.lst_deposit.amount);
+    require_gt!(lst_amount, 0, ErrorCode::NothingToMove);
+
+    // Note: a Token-2022 transfer fee withheld in the withdraw account is recorded as a strategy loss
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.lst_deposit.to_account_info(),
+                mint: ctx.accounts.lst_mint.to_account_info(),
+                to: ctx.accounts.lst_withdraw_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+            &[&[
+                AUTHORITY_SEED,
+                &ctx.accounts.state.key().to_bytes(),
+                &[ctx.bumps.authority],
+            ]],
+        ),
+        lst_amount,
+        ctx.accounts.lst_mint.decimals,
+    )?;
+
+    emit!(crate::events::MoveToWithdrawAccountEvent {
+        strategy_state: ctx.accounts.state.key(),
+        lst_mint: ctx.accounts.lst_mint.key(),
+        next_withdraw_lst_amount,
+        lst_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+pub mod constants;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod state;
+
+use anchor_lang::prelude::*;
+
+pub use constants::*;
+pub use events::*;
+pub use instructions::*;
+pub use state::*;
+
+declare_id!("J8PTQkN3HmokoCnmudS42oRtak4Kk25fD6myK32MiCHf");
+
+/// just-hold strategy: receives LSTs from a mpSOL secondary-vault and just holds them.
+/// It does not generate yield beyond the LST/SOL price increase, it's meant
+/// as the reference implementation of the strategy contract & template for new strategies
+#[program]
+pub mod just_hold_strategy {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        initialize::handle_initialize(ctx)
+    }
+
+    // ------------------
+    // permissionless
+    // ------------------
+    pub fn move_to_withdraw_account(ctx: Context<MoveToWithdrawAccount>) -> Result<()> {
+        move_to_withdraw_account::handle_move_to_withdraw_account(ctx)
+    }
+}
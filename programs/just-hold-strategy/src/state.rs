@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+/// strategy state, created by `initialize`
+/// the first fields must match `strategy_interface::CommonStrategyState`
+/// (the 8-bytes anchor discriminator is the first field in `CommonStrategyState`)
+#[account]
+#[derive(InitSpace)]
+pub struct JustHoldStrategyState {
+    /// the LST this strategy holds
+    pub lst_mint: Pubkey,
+
+    /// lst amount deployed outside of the strategy ATAs
+    /// the vault adds the strategy ATA (lst_deposit) and the lst_withdraw_account balances to this amount
+    /// always 0 for just-hold, all the lst is in the strategy ATA
+    pub strat_total_lst_amount: u64,
+}
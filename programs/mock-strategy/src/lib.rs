@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+declare_id!("24oeExkXrG7UGG98tG6xFyU43w3AdaMbTCGB3uJnhKvJ");
+
+// generic word-seed for b"authority"
+#[constant]
+pub const AUTHORITY_SEED: &[u8] = strategy_interface::STRAT_AUTHORITY_SEED;
+
+/// TEST ONLY strategy
+/// holds the LSTs in the strategy ATA and allows anyone to simulate a slashing event
+#[program]
+pub mod mock_strategy {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        ctx.accounts.state.set_inner(MockStrategyState {
+            lst_mint: ctx.accounts.lst_mint.key(),
+            strat_total_lst_amount: 0,
+        });
+        Ok(())
+    }
+
+    /// simulate a slashing event by moving lst out of the strategy ATA
+    pub fn slash(ctx: Context<Slash>, lst_amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.lst_deposit.to_account_info(),
+                    mint: ctx.accounts.lst_mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+                &[&[
+                    AUTHORITY_SEED,
+                    &ctx.accounts.state.key().to_bytes(),
+                    &[ctx.bumps.authority],
+                ]],
+            ),
+            lst_amount,
+            ctx.accounts.lst_mint.decimals,
+        )
+    }
+}
+
+/// first fields must match `strategy_interface::CommonStrategyState`
+/// (the anchor discriminator is the first 8 bytes)
+#[account]
+#[derive(InitSpace)]
+pub struct MockStrategyState {
+    pub lst_mint: Pubkey,
+    // lst amount outside the strategy ATA, always 0 for this strategy
+    pub strat_total_lst_amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(init, payer = payer, space = 8 + MockStrategyState::INIT_SPACE)]
+    pub state: Account<'info, MockStrategyState>,
+
+    pub lst_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA strat authority
+    #[account(
+        seeds = [
+            AUTHORITY_SEED,
+            &state.key().to_bytes()
+        ],
+        bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(init, payer = payer,
+        associated_token::mint = lst_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program,
+    )]
+    pub lst_deposit: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// Token or Token-2022, owner of the lst mint
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Slash<'info> {
+    #[account(has_one = lst_mint)]
+    pub state: Account<'info, MockStrategyState>,
+
+    pub lst_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA strat authority
+    #[account(
+        seeds = [
+            AUTHORITY_SEED,
+            &state.key().to_bytes()
+        ],
+        bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program,
+    )]
+    pub lst_deposit: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = lst_mint, token::token_program = token_program)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token or Token-2022, owner of the lst mint
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,24 +1,58 @@
 use anchor_lang::prelude::*;
 
+/// check_invariants takes 2 remaining accounts per vault in a single tx.
+/// 28 vaults + the fixed accounts (fee payer, caller, main_state, registry, vaults ATA auth,
+/// program ids) stay within the 64 account locks of a tx (using an address lookup table)
 #[constant]
-pub const MAX_WHITELISTED_VAULTS: u8 = 64;
+pub const MAX_WHITELISTED_VAULTS: u8 = 28;
 #[constant]
 pub const MAX_WHITELISTED_VAULT_STRATEGIES: u8 = 64;
 #[constant]
-pub const MAIN_VAULT_MINT_AUTH_SEED: &'static [u8] = b"main-mint";
+pub const MAIN_VAULT_MINT_AUTH_SEED: &[u8] = b"main-mint";
 #[constant]
-pub const VAULTS_ATA_AUTH_SEED: &'static [u8] = b"vaults-ata-auth";
+pub const VAULTS_ATA_AUTH_SEED: &[u8] = b"vaults-ata-auth";
 #[constant]
-pub const VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED: &'static [u8] = b"lst_withdraw_authority";
+pub const VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED: &'static [u8] =
+    strategy_interface::VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED;
+#[constant]
+pub const UNWRAP_WSOL_SEED: &[u8] = b"unwrap-wsol"; // temp wSOL account used by ticket_claim_sol
+#[constant]
+pub const TICKET_NFT_MINT_SEED: &[u8] = b"ticket-nft-mint"; // + ticket account, NFT-ticket mint
+#[constant]
+pub const TICKET_NFT_AUTH_SEED: &[u8] = b"ticket-nft-auth"; // mint, freeze & metadata update auth
+#[constant]
+pub const TICKET_SEED: &[u8] = b"ticket"; // + beneficiary + seq, PDA unstake-tickets
+#[constant]
+pub const USER_TICKET_INDEX_SEED: &[u8] = b"ticket-index"; // + beneficiary
 #[constant]
 pub const MIN_MOVEMENT_LAMPORTS: u64 = 1_000_000; // avoid low-amount/rounding attacks
 #[constant]
 pub const MAX_PERFORMANCE_FEE_BP: u16 = 2500; // max 25% performance fee
 #[constant]
+pub const INVARIANT_CHECK_TOLERANCE_LAMPORTS: u64 = 1_000_000; // accumulated sol-value rounding
+#[constant]
+pub const MIN_UNSTAKE_TICKET_WAITING_HOURS: u16 = 1;
+#[constant]
+pub const MAX_UNSTAKE_TICKET_WAITING_HOURS: u16 = 30 * 24; // max 30 days
+#[constant]
+pub const CONFIG_CHANGE_DELAY_HOURS: u16 = 72; // timelock for fee & waiting-hours changes
+#[constant]
 pub const MAX_WITHDRAW_FEE_BP: u16 = 100; // max 1% withdraw fee
 #[constant]
-pub const VAULT_STRAT_ENTRY_SEED: &'static [u8] = b"vault-strat-entry";
+pub const MAX_INSTANT_UNSTAKE_FEE_BP: u16 = 500; // max 5% instant-unstake fee
+#[constant]
+pub const MIN_LST_DECIMALS: u8 = 1; // 0 marks vaults created before decimals were stored
+#[constant]
+pub const MAX_LST_DECIMALS: u8 = 12; // sol-value conversions drop at most 3 decimals of the lst amount
+#[constant]
+pub const DEFAULT_MAX_PRICE_AGE_SECONDS: u64 = 60 * 60 * 24; // vaults with max_price_age_seconds = 0
+#[constant]
+pub const MAX_PRICE_AGE_SECONDS_LIMIT: u64 = 7 * 60 * 60 * 24; // max configurable price age, 7 days
+#[constant]
+pub const VAULT_REGISTRY_SEED: &[u8] = b"vault-registry";
+#[constant]
+pub const VAULT_STRAT_ENTRY_SEED: &'static [u8] = strategy_interface::VAULT_STRAT_ENTRY_SEED;
 
 // generic word-seed for b"authority" --- used by strategies
 #[constant]
-pub const AUTHORITY_SEED: &'static [u8] = b"authority";
+pub const AUTHORITY_SEED: &'static [u8] = strategy_interface::STRAT_AUTHORITY_SEED;
@@ -89,4 +89,136 @@ pub enum ErrorCode {
 
     #[msg("Withdraw Fee Too High")]
     WithdrawFeeTooHigh,
+
+    #[msg("vault already registered")]
+    VaultAlreadyRegistered,
+
+    #[msg("invalid vault lst account")]
+    InvalidVaultLstAccount,
+
+    #[msg("vault state must be writable")]
+    VaultStateMustBeWritable,
+
+    #[msg("program is paused")]
+    ProgramPaused,
+
+    #[msg("signer is not the pause authority or admin")]
+    InvalidPauseAuthority,
+
+    #[msg("invalid new authority")]
+    InvalidNewAuthority,
+
+    #[msg("no pending handover")]
+    NoPendingHandover,
+
+    #[msg("signer is not the pending authority")]
+    NotThePendingAuthority,
+
+    #[msg("no pending config change")]
+    NoPendingConfigChange,
+
+    #[msg("no pending config change has reached its effective timestamp")]
+    PendingConfigNotEffectiveYet,
+
+    #[msg("unstake waiting hours out of bounds")]
+    UnstakeWaitingHoursOutOfBounds,
+
+    #[msg("Instant Unstake Fee Too High")]
+    InstantUnstakeFeeTooHigh,
+
+    #[msg("instant unstake is disabled in this vault")]
+    InstantUnstakeDisabledInVault,
+
+    #[msg("not enough free liquidity in the vault for instant unstake")]
+    NotEnoughLiquidityForInstantUnstake,
+
+    #[msg("instant unstake exceeds the vault epoch cap")]
+    InstantUnstakeExceedsVaultCap,
+
+    #[msg("lst amount is less than the requested minimum")]
+    LstAmountBelowMinimum,
+
+    #[msg("invalid number of remaining accounts")]
+    InvalidRemainingAccountsCount,
+
+    #[msg("vault state is not a secondary vault of this main state")]
+    InvalidVaultState,
+
+    #[msg("the same vault is used twice in the claim")]
+    DuplicatedVaultInClaim,
+
+    #[msg("invalid beneficiary lst account")]
+    InvalidBeneficiaryLstAccount,
+
+    #[msg("invalid new ticket beneficiary")]
+    InvalidNewBeneficiary,
+
+    #[msg("the ticket is not an NFT ticket")]
+    NotAnNftTicket,
+
+    #[msg("signer does not hold the ticket NFT")]
+    NotTheTicketNftHolder,
+
+    #[msg("invalid price source kind")]
+    InvalidPriceSourceKind,
+
+    #[msg("lst state account is not the vault price source state")]
+    InvalidPriceSourceState,
+
+    #[msg("the price source does not price this lst mint")]
+    InvalidPriceSourceLstMint,
+
+    #[msg("the vault price source is already set")]
+    PriceSourceAlreadySet,
+
+    #[msg("the lst mint has a permanent delegate")]
+    LstMintHasPermanentDelegate,
+
+    #[msg("the lst mint is non-transferable")]
+    LstMintIsNonTransferable,
+
+    #[msg("the lst mint transfer hook program is not allow-listed")]
+    LstMintTransferHookNotAllowed,
+
+    #[msg("the token program is not the owner of the lst mint")]
+    InvalidLstTokenProgram,
+
+    #[msg("lst mint decimals are out of the supported range")]
+    UnsupportedLstDecimals,
+
+    #[msg("the lst price source was not updated in the current epoch")]
+    LstPriceSourceNotUpdatedThisEpoch,
+
+    #[msg("the stored lst price is outdated, the price source must be updated first")]
+    LstSolPriceIsOutdated,
+
+    #[msg("the vault price moved more than its limits, the admin must acknowledge the new price")]
+    VaultPriceFrozen,
+
+    #[msg("the vault price is not frozen")]
+    VaultPriceNotFrozen,
+
+    #[msg("price change limit exceeds 100%")]
+    InvalidPriceChangeLimit,
+
+    #[msg("max price age must be 0 (default) or up to 7 days")]
+    InvalidMaxPriceAge,
+
+    #[msg("tickets created before v1.1 can not be cancelled")]
+    TicketCanNotBeCancelled,
+
+    #[msg("the strategy loss exceeds the mpSOL backing")]
+    StrategyLossExceedsBacking,
+
+    #[msg("can not compute the lst mint transfer fee")]
+    TransferFeeCalculationFailed,
+
+    #[msg("min_lst_amounts must have one entry per sol_value_amounts entry")]
+    InvalidMinLstAmountsCount,
+
+    #[msg("amount or sol-value out of range")]
+    AmountConversionOverflow,
+
+    #[msg("the ticket NFT needs all the optional NFT accounts")]
+    MissingTicketNftAccounts,
 }
@@ -1,3 +1,4 @@
+use anchor_lang::prelude::borsh;
 use anchor_lang::prelude::*;
 #[event]
 pub struct StakeEvent {
@@ -43,6 +44,25 @@ pub struct UpdateVaultTokenSolPriceEvent {
     pub main_vault_backing_sol_value: u64,
 }
 
+#[event]
+pub struct VaultPriceCircuitBreakerEvent {
+    pub main_state: Pubkey,
+    pub lst_mint: Pubkey,
+    pub reference_price_p32: u64,
+    pub stored_price_p32: u64,
+    pub new_price_p32: u64,
+    pub max_price_increase_bp: u16,
+    pub max_price_decrease_bp: u16,
+}
+
+#[event]
+pub struct VaultPriceAcknowledgedEvent {
+    pub main_state: Pubkey,
+    pub lst_mint: Pubkey,
+    pub old_price_p32: u64,
+    pub accepted_price_p32: u64,
+}
+
 #[event]
 pub struct UpdateAttachedStratLstAmountEvent {
     pub main_state: Pubkey,
@@ -52,6 +72,8 @@ pub struct UpdateAttachedStratLstAmountEvent {
     pub new_lst_amount: u64,
     pub lst_price_p32: u64,
     pub main_vault_backing_sol_value: u64,
+    /// part of the profit recovering previous losses, not charged the performance fee
+    pub recovered_loss_lst_amount: u64,
     pub performance_fee_mpsol_amount: u64,
 }
 
@@ -67,6 +89,51 @@ pub struct TicketClaimEvent {
     pub ticket_due_timestamp: u64,
 }
 
+#[event]
+pub struct TicketTransferEvent {
+    pub main_state: Pubkey,
+    /// closed, the ticket is re-created in the new beneficiary index
+    pub ticket_account: Pubkey,
+    pub new_ticket_account: Pubkey,
+    pub old_beneficiary: Pubkey,
+    pub new_beneficiary: Pubkey,
+    pub ticket_sol_value: u64,
+    pub ticket_due_timestamp: u64,
+}
+
+#[event]
+pub struct TicketSplitEvent {
+    pub main_state: Pubkey,
+    pub ticket_account: Pubkey,
+    pub new_ticket_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub ticket_sol_value_remaining: u64,
+    pub new_ticket_sol_value: u64,
+    pub ticket_due_timestamp: u64,
+}
+
+#[event]
+pub struct TicketCancelEvent {
+    pub main_state: Pubkey,
+    pub ticket_account: Pubkey,
+    pub beneficiary: Pubkey,
+    pub ticket_sol_value: u64,
+    pub mpsol_received: u64,
+    //--- mpSOL price components after the cancel
+    pub main_vault_backing_sol_value: u64,
+    pub mpsol_supply: u64,
+}
+
+#[event]
+pub struct TicketTokenizedEvent {
+    pub main_state: Pubkey,
+    pub ticket_account: Pubkey,
+    pub old_beneficiary: Pubkey,
+    pub ticket_nft_mint: Pubkey,
+    pub ticket_sol_value: u64,
+    pub ticket_due_timestamp: u64,
+}
+
 #[event]
 pub struct TransferLstToStratEvent {
     pub main_state: Pubkey,
@@ -84,3 +151,156 @@ pub struct GetLstFromStratEvent {
     pub existent_amount: u64,
     pub lst_amount: u64,
 }
+
+#[event]
+pub struct StrategyLossEvent {
+    pub main_state: Pubkey,
+    pub lst_mint: Pubkey,
+    pub vault_strategy_relation_entry: Pubkey,
+    pub old_lst_amount: u64,
+    pub new_lst_amount: u64,
+    pub loss_lst_amount: u64,
+    pub lst_price_p32: u64,
+    pub loss_sol_value: u64,
+    /// losses to recover before the performance fee is charged again
+    pub unrecovered_loss_lst_amount: u64,
+    //--- mpSOL price after the loss
+    pub main_vault_backing_sol_value: u64,
+    pub mpsol_supply: u64,
+}
+
+#[event]
+pub struct CheckInvariantsEvent {
+    pub main_state: Pubkey,
+    pub vaults_count: u8,
+    /// sum(secondary_vault.vault_total_sol_value)
+    pub vaults_total_sol_value: u64,
+    pub backing_sol_value: u64,
+    pub outstanding_tickets_sol_value: u64,
+    /// false if any vault price is stale, main_invariant_ok is not meaningful then
+    pub main_invariant_checked: bool,
+    /// vaults_total_sol_value = backing_sol_value + outstanding_tickets_sol_value
+    pub main_invariant_ok: bool,
+    /// registry indexes of the vaults failing
+    /// vault_total_lst_amount = in_strategies_amount + locally_stored_amount
+    /// or vault_lst_account.amount >= locally_stored_amount
+    pub violating_vaults: Vec<u8>,
+    /// registry indexes of the vaults with a price older than their max age
+    pub stale_vaults: Vec<u8>,
+    pub deposits_paused: bool,
+}
+
+#[event]
+pub struct PauseEvent {
+    pub main_state: Pubkey,
+    /// pause_authority or admin
+    pub authority: Pubkey,
+    pub ticket_claims_paused: bool,
+}
+
+#[event]
+pub struct UnpauseEvent {
+    pub main_state: Pubkey,
+    /// pause_authority or admin
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct PauseAuthorityChangedEvent {
+    pub main_state: Pubkey,
+    /// Pubkey::default() => not set
+    pub old_pause_authority: Pubkey,
+    /// Pubkey::default() => cleared, only the admin can pause
+    pub new_pause_authority: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandoverRole {
+    Admin,
+    OperatorAuth,
+}
+
+#[event]
+pub struct HandoverProposedEvent {
+    pub main_state: Pubkey,
+    pub role: HandoverRole,
+    pub current: Pubkey,
+    pub proposed: Pubkey,
+}
+
+#[event]
+pub struct HandoverAcceptedEvent {
+    pub main_state: Pubkey,
+    pub role: HandoverRole,
+    pub previous: Pubkey,
+    pub new: Pubkey,
+}
+
+#[event]
+pub struct HandoverCancelledEvent {
+    pub main_state: Pubkey,
+    pub role: HandoverRole,
+    pub cancelled: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigParam {
+    WithdrawFeeBp,
+    PerformanceFeeBp,
+    UnstakeTicketWaitingHours,
+    InstantUnstakeFeeBp,
+}
+
+impl ConfigParam {
+    /// fee decreases can not harm users, they skip the timelock.
+    /// Waiting-hours changes are always queued: a decrease makes tickets due before
+    /// the liquidity planned for the outstanding tickets is back in the vaults.
+    pub fn decrease_is_immediate(self) -> bool {
+        match self {
+            ConfigParam::WithdrawFeeBp
+            | ConfigParam::PerformanceFeeBp
+            | ConfigParam::InstantUnstakeFeeBp => true,
+            ConfigParam::UnstakeTicketWaitingHours => false,
+        }
+    }
+}
+
+#[event]
+pub struct ConfigChangeQueuedEvent {
+    pub main_state: Pubkey,
+    pub param: ConfigParam,
+    pub current_value: u16,
+    pub new_value: u16,
+    pub effective_timestamp: u64,
+}
+
+#[event]
+pub struct ConfigChangeAppliedEvent {
+    pub main_state: Pubkey,
+    pub param: ConfigParam,
+    pub old_value: u16,
+    pub new_value: u16,
+}
+
+#[event]
+pub struct ConfigChangeCancelledEvent {
+    pub main_state: Pubkey,
+    pub param: ConfigParam,
+    pub cancelled_value: u16,
+}
+
+#[event]
+pub struct UnstakeInstantEvent {
+    pub main_state: Pubkey,
+    pub lst_mint: Pubkey,
+    pub unstaker: Pubkey,
+    pub mpsol_amount: u64,
+    pub fee_mpsol: u64,
+    pub fee_to_treasury_mpsol: u64,
+    pub mpsol_burned: u64,
+    pub sol_value: u64,
+    pub lst_amount_delivered: u64,
+    //--- mpSOL price components after the unstake
+    pub main_vault_backing_sol_value: u64,
+    pub mpsol_supply: u64,
+}
@@ -0,0 +1,57 @@
+use crate::error::ErrorCode;
+use crate::state::MainVaultState;
+use crate::{apply_vault_token_sol_price, SecondaryVaultState};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcknowledgeVaultPrice<'info> {
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(mut, has_one=admin)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    /// CHECK: No auto-deserialization
+    #[account()]
+    pub lst_mint: UncheckedAccount<'info>,
+
+    #[account(mut,
+        has_one = lst_mint,
+        seeds = [
+            &main_state.key().to_bytes(),
+            &lst_mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub secondary_state: Account<'info, SecondaryVaultState>,
+}
+
+/// accepts the price that tripped the vault circuit breaker (the last one read while frozen),
+/// updating backing_sol_value, and unfreezes the vault.
+/// The price keeps its read timestamp: if it is stale, a price update is required before using it
+pub fn handle_acknowledge_vault_price(ctx: Context<AcknowledgeVaultPrice>) -> Result<()> {
+    let secondary_state = &mut ctx.accounts.secondary_state;
+    require!(secondary_state.price_frozen, ErrorCode::VaultPriceNotFrozen);
+
+    let old_price_p32 = secondary_state.lst_sol_price_p32;
+    let accepted_price_p32 = secondary_state.pending_lst_sol_price_p32;
+    apply_vault_token_sol_price(
+        &mut ctx.accounts.main_state,
+        secondary_state,
+        accepted_price_p32,
+        secondary_state.pending_lst_sol_price_timestamp,
+    )?;
+    // next limits are relative to the accepted price, in the epoch it was read
+    secondary_state.price_reference_p32 = accepted_price_p32;
+    secondary_state.price_reference_epoch = secondary_state.pending_lst_sol_price_epoch;
+    secondary_state.price_frozen = false;
+    secondary_state.set_pending_price(0, 0, 0);
+
+    emit!(crate::events::VaultPriceAcknowledgedEvent {
+        main_state: ctx.accounts.main_state.key(),
+        lst_mint: secondary_state.lst_mint,
+        old_price_p32,
+        accepted_price_p32,
+    });
+    Ok(())
+}
@@ -77,6 +77,7 @@ pub fn handle_attach_common_strategy_state(ctx: Context<AttachCommonStrategyStat
             tickets_target_sol_amount: 0,
             last_read_strat_lst_amount: 0,
             last_read_strat_lst_timestamp: 0,
+            unrecovered_loss_lst_amount: 0,
         });
     Ok(())
 }
@@ -0,0 +1,112 @@
+use crate::events::{
+    HandoverAcceptedEvent, HandoverCancelledEvent, HandoverProposedEvent, HandoverRole,
+};
+use crate::{error::ErrorCode, state::MainVaultState, AdminAndMainStateAccounts};
+use anchor_lang::prelude::*;
+
+// two-step handover for admin & operator_auth:
+// 1. admin proposes the new key (stored as pending)
+// 2. the new key signs accept, and replaces the current one
+// the admin can cancel a pending handover at any time before it is accepted
+
+fn pending_mut(main_state: &mut MainVaultState, role: HandoverRole) -> &mut Pubkey {
+    match role {
+        HandoverRole::Admin => &mut main_state.pending_admin,
+        HandoverRole::OperatorAuth => &mut main_state.pending_operator_auth,
+    }
+}
+
+fn current_mut(main_state: &mut MainVaultState, role: HandoverRole) -> &mut Pubkey {
+    match role {
+        HandoverRole::Admin => &mut main_state.admin,
+        HandoverRole::OperatorAuth => &mut main_state.operator_auth,
+    }
+}
+
+pub fn handle_propose_handover(
+    ctx: Context<AdminAndMainStateAccounts>,
+    role: HandoverRole,
+    proposed: Pubkey,
+) -> Result<()> {
+    let main_state = &mut ctx.accounts.main_state;
+    require_keys_neq!(proposed, Pubkey::default(), ErrorCode::InvalidNewAuthority);
+    require_keys_neq!(
+        proposed,
+        *current_mut(main_state, role),
+        ErrorCode::InvalidNewAuthority
+    );
+    *pending_mut(main_state, role) = proposed;
+    emit!(HandoverProposedEvent {
+        main_state: main_state.key(),
+        role,
+        current: *current_mut(main_state, role),
+        proposed,
+    });
+    Ok(())
+}
+
+pub fn handle_cancel_handover(
+    ctx: Context<AdminAndMainStateAccounts>,
+    role: HandoverRole,
+) -> Result<()> {
+    let main_state = &mut ctx.accounts.main_state;
+    let cancelled = *pending_mut(main_state, role);
+    require_keys_neq!(cancelled, Pubkey::default(), ErrorCode::NoPendingHandover);
+    *pending_mut(main_state, role) = Pubkey::default();
+    emit!(HandoverCancelledEvent {
+        main_state: main_state.key(),
+        role,
+        cancelled,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account()]
+    pub new_admin: Signer<'info>,
+
+    #[account(mut,
+        constraint = main_state.pending_admin == new_admin.key() @ ErrorCode::NotThePendingAuthority
+    )]
+    pub main_state: Account<'info, MainVaultState>,
+}
+
+pub fn handle_accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    accept(
+        &mut ctx.accounts.main_state,
+        HandoverRole::Admin,
+        ctx.accounts.new_admin.key(),
+    )
+}
+
+#[derive(Accounts)]
+pub struct AcceptOperatorAuth<'info> {
+    #[account()]
+    pub new_operator_auth: Signer<'info>,
+
+    #[account(mut,
+        constraint = main_state.pending_operator_auth == new_operator_auth.key() @ ErrorCode::NotThePendingAuthority
+    )]
+    pub main_state: Account<'info, MainVaultState>,
+}
+
+pub fn handle_accept_operator_auth(ctx: Context<AcceptOperatorAuth>) -> Result<()> {
+    accept(
+        &mut ctx.accounts.main_state,
+        HandoverRole::OperatorAuth,
+        ctx.accounts.new_operator_auth.key(),
+    )
+}
+
+fn accept(main_state: &mut Account<MainVaultState>, role: HandoverRole, new: Pubkey) -> Result<()> {
+    let previous = std::mem::replace(current_mut(main_state, role), new);
+    *pending_mut(main_state, role) = Pubkey::default();
+    emit!(HandoverAcceptedEvent {
+        main_state: main_state.key(),
+        role,
+        previous,
+        new,
+    });
+    Ok(())
+}
@@ -1,4 +1,12 @@
-use crate::{error::ErrorCode, state::MainVaultState, MAX_PERFORMANCE_FEE_BP, MAX_WITHDRAW_FEE_BP};
+use crate::events::{
+    ConfigChangeAppliedEvent, ConfigChangeCancelledEvent, ConfigChangeQueuedEvent, ConfigParam,
+};
+use crate::state::{MainVaultState, PendingConfigValue};
+use crate::{
+    error::ErrorCode, CONFIG_CHANGE_DELAY_HOURS, MAX_INSTANT_UNSTAKE_FEE_BP,
+    MAX_PERFORMANCE_FEE_BP, MAX_UNSTAKE_TICKET_WAITING_HOURS, MAX_WITHDRAW_FEE_BP,
+    MIN_UNSTAKE_TICKET_WAITING_HOURS,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token::TokenAccount;
 
@@ -32,40 +40,122 @@ pub fn handle_clear_treasury_account(ctx: Context<AdminAndMainStateAccounts>) ->
     ctx.accounts.main_state.treasury_mpsol_account = None;
     Ok(())
 }
+
+// fee increases & waiting-hours changes are timelocked: the configure_* instructions queue the change,
+// `apply_pending_config` applies it after CONFIG_CHANGE_DELAY_HOURS.
+// queuing a new value for the same param replaces the previous one (and restarts the delay).
+// Fee decreases can not harm users, they are applied immediately (and drop any queued change),
+// see `ConfigParam::decrease_is_immediate`
+fn change_config(
+    main_state: &mut Account<MainVaultState>,
+    param: ConfigParam,
+    value: u16,
+) -> Result<()> {
+    let current_value = main_state.config_value(param);
+    if value <= current_value && param.decrease_is_immediate() {
+        main_state.set_config_value(param, value);
+        *main_state.pending_config_mut(param) = None;
+        emit!(ConfigChangeAppliedEvent {
+            main_state: main_state.key(),
+            param,
+            old_value: current_value,
+            new_value: value,
+        });
+        return Ok(());
+    }
+
+    let effective_timestamp =
+        Clock::get()?.unix_timestamp as u64 + CONFIG_CHANGE_DELAY_HOURS as u64 * 60 * 60;
+    *main_state.pending_config_mut(param) = Some(PendingConfigValue {
+        value,
+        effective_timestamp,
+    });
+    emit!(ConfigChangeQueuedEvent {
+        main_state: main_state.key(),
+        param,
+        current_value,
+        new_value: value,
+        effective_timestamp,
+    });
+    Ok(())
+}
+
 pub fn handle_configure_unstake_waiting_hours(
     ctx: Context<AdminAndMainStateAccounts>,
     hours: u16,
 ) -> Result<()> {
-    ctx.accounts.main_state.unstake_ticket_waiting_hours = hours;
-    Ok(())
+    require!(
+        (MIN_UNSTAKE_TICKET_WAITING_HOURS..=MAX_UNSTAKE_TICKET_WAITING_HOURS).contains(&hours),
+        ErrorCode::UnstakeWaitingHoursOutOfBounds
+    );
+    change_config(
+        &mut ctx.accounts.main_state,
+        ConfigParam::UnstakeTicketWaitingHours,
+        hours,
+    )
 }
 pub fn handle_configure_withdrawal_fee(
     ctx: Context<AdminAndMainStateAccounts>,
     bp: u16,
 ) -> Result<()> {
     require_gte!(MAX_WITHDRAW_FEE_BP, bp, ErrorCode::WithdrawFeeTooHigh);
-    ctx.accounts.main_state.withdraw_fee_bp = bp;
-    Ok(())
+    change_config(&mut ctx.accounts.main_state, ConfigParam::WithdrawFeeBp, bp)
 }
 pub fn handle_configure_performance_fee(
     ctx: Context<AdminAndMainStateAccounts>,
     bp: u16,
 ) -> Result<()> {
     require_gte!(MAX_PERFORMANCE_FEE_BP, bp, ErrorCode::PerformanceFeeTooHigh);
-    ctx.accounts.main_state.performance_fee_bp = bp;
+    change_config(
+        &mut ctx.accounts.main_state,
+        ConfigParam::PerformanceFeeBp,
+        bp,
+    )
+}
+pub fn handle_cancel_pending_config(
+    ctx: Context<AdminAndMainStateAccounts>,
+    param: ConfigParam,
+) -> Result<()> {
+    let main_state = &mut ctx.accounts.main_state;
+    let pending = main_state
+        .pending_config_mut(param)
+        .take()
+        .ok_or(ErrorCode::NoPendingConfigChange)?;
+    emit!(ConfigChangeCancelledEvent {
+        main_state: main_state.key(),
+        param,
+        cancelled_value: pending.value,
+    });
     Ok(())
 }
-pub fn handle_configure_operator_auth(
+/// sets the emergency pause authority, Pubkey::default() clears it (only the admin can pause).
+/// Note: main states created before v1.1 must be extended with `realloc_main_state` first
+pub fn handle_configure_pause_authority(
     ctx: Context<AdminAndMainStateAccounts>,
     auth: Pubkey,
 ) -> Result<()> {
-    ctx.accounts.main_state.operator_auth = auth;
+    let main_state = &mut ctx.accounts.main_state;
+    let old_pause_authority = main_state.pause_authority;
+    main_state.pause_authority = auth;
+    emit!(crate::events::PauseAuthorityChangedEvent {
+        main_state: main_state.key(),
+        old_pause_authority,
+        new_pause_authority: auth,
+    });
     Ok(())
 }
-pub fn handle_configure_new_admin(
+pub fn handle_configure_instant_unstake_fee(
     ctx: Context<AdminAndMainStateAccounts>,
-    new_admin: Pubkey,
+    bp: u16,
 ) -> Result<()> {
-    ctx.accounts.main_state.admin = new_admin;
-    Ok(())
+    require_gte!(
+        MAX_INSTANT_UNSTAKE_FEE_BP,
+        bp,
+        ErrorCode::InstantUnstakeFeeTooHigh
+    );
+    change_config(
+        &mut ctx.accounts.main_state,
+        ConfigParam::InstantUnstakeFeeBp,
+        bp,
+    )
 }
@@ -1,13 +1,25 @@
+use crate::constants::MAX_PRICE_AGE_SECONDS_LIMIT;
+use crate::error::ErrorCode;
+use crate::price_adapters::{check_price_source, PriceEpochPolicy, PriceSourceKind};
 use crate::state::MainVaultState;
 use crate::SecondaryVaultState;
 use anchor_lang::prelude::*;
+use shared_lib::BASIS_POINTS_100_PERCENT;
 
-use anchor_spl::token::Mint;
+use anchor_spl::token_interface::Mint;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ConfigureSecondaryVaultValues {
-    deposits_disabled: Option<bool>,
-    token_deposit_cap: Option<u64>,
+    pub deposits_disabled: Option<bool>,
+    pub token_deposit_cap: Option<u64>,
+    /// 0 => instant unstake disabled
+    pub instant_unstake_cap_per_epoch: Option<u64>,
+    pub price_epoch_policy: Option<PriceEpochPolicy>,
+    /// price circuit breaker limits, 0 => no limit
+    pub max_price_increase_bp: Option<u16>,
+    pub max_price_decrease_bp: Option<u16>,
+    /// max age of the stored LST/SOL price, 0 => DEFAULT_MAX_PRICE_AGE_SECONDS
+    pub max_price_age_seconds: Option<u64>,
 }
 
 #[derive(Accounts)]
@@ -19,8 +31,7 @@ pub struct ConfigureSecondaryVault<'info> {
     pub main_state: Account<'info, MainVaultState>,
 
     #[account()]
-    // all mints must have 9 decimals, to simplify x/SOL price calculations
-    pub lst_mint: Account<'info, Mint>,
+    pub lst_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
@@ -43,6 +54,60 @@ pub fn handle_configure_secondary_vault(
     if let Some(token_deposit_cap) = values.token_deposit_cap {
         ctx.accounts.secondary_state.token_deposit_cap = token_deposit_cap
     }
+    if let Some(instant_unstake_cap_per_epoch) = values.instant_unstake_cap_per_epoch {
+        ctx.accounts.secondary_state.instant_unstake_cap_per_epoch = instant_unstake_cap_per_epoch
+    }
+    if let Some(price_epoch_policy) = values.price_epoch_policy {
+        ctx.accounts.secondary_state.price_epoch_policy = price_epoch_policy
+    }
+    if let Some(max_price_increase_bp) = values.max_price_increase_bp {
+        require_gte!(
+            BASIS_POINTS_100_PERCENT,
+            max_price_increase_bp,
+            ErrorCode::InvalidPriceChangeLimit
+        );
+        ctx.accounts.secondary_state.max_price_increase_bp = max_price_increase_bp
+    }
+    if let Some(max_price_decrease_bp) = values.max_price_decrease_bp {
+        require_gte!(
+            BASIS_POINTS_100_PERCENT,
+            max_price_decrease_bp,
+            ErrorCode::InvalidPriceChangeLimit
+        );
+        ctx.accounts.secondary_state.max_price_decrease_bp = max_price_decrease_bp
+    }
+    if let Some(max_price_age_seconds) = values.max_price_age_seconds {
+        require_gte!(
+            MAX_PRICE_AGE_SECONDS_LIMIT,
+            max_price_age_seconds,
+            ErrorCode::InvalidMaxPriceAge
+        );
+        ctx.accounts.secondary_state.max_price_age_seconds = max_price_age_seconds
+    }
 
     Ok(())
 }
+
+/// sets the price source of a vault created before v1.1 (after `realloc_secondary_vault`).
+/// Once set, the price source can not be changed.
+/// The price source state account, if the kind requires one, is expected in remaining_accounts[0]
+pub fn handle_configure_vault_price_source(
+    ctx: Context<ConfigureSecondaryVault>,
+    price_source_kind: PriceSourceKind,
+    price_source_state: Pubkey,
+) -> Result<()> {
+    require!(
+        ctx.accounts.secondary_state.price_source_kind == PriceSourceKind::Unset,
+        ErrorCode::PriceSourceAlreadySet
+    );
+    check_price_source(
+        &ctx.accounts.lst_mint.key(),
+        ctx.accounts.secondary_state.lst_decimals(),
+        price_source_kind,
+        &price_source_state,
+        ctx.remaining_accounts.first(),
+    )?;
+    ctx.accounts.secondary_state.price_source_kind = price_source_kind;
+    ctx.accounts.secondary_state.price_source_state = price_source_state;
+    Ok(())
+}
@@ -1,10 +1,12 @@
+use crate::error::ErrorCode;
+use crate::lst_token::check_lst_mint_extensions;
+use crate::price_adapters::{check_price_source, price_adapter, PriceSourceKind};
 use crate::state::MainVaultState;
-use crate::{constants::*, SecondaryVaultState};
+use crate::{constants::*, SecondaryVaultState, VaultRegistry};
 use anchor_lang::prelude::*;
 
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::TokenAccount;
-use anchor_spl::token::{Mint, Token};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 /// Note: Before adding a secondary vault
 /// THE CONTRACT CODE OF THE LST HAS TO BE VERIFIED
@@ -19,9 +21,10 @@ pub struct CreateSecondaryVault<'info> {
     #[account(mut, has_one=admin)]
     pub main_state: Account<'info, MainVaultState>,
 
-    #[account(mint::decimals = 9)]
-    // all LST mints must have 9 decimals, to simplify x/SOL price calculations
-    pub lst_mint: Account<'info, Mint>,
+    #[account()]
+    // Token or Token-2022 mint, extensions are checked by `check_lst_mint_extensions`,
+    // decimals are checked and stored in the vault state
+    pub lst_mint: InterfaceAccount<'info, Mint>,
 
     // secondary vaults are PDAs of main_state
     // only this program & main_state can create a secondary vault
@@ -46,16 +49,49 @@ pub struct CreateSecondaryVault<'info> {
 
     #[account(
         associated_token::mint = lst_mint,
-        associated_token::authority = vaults_ata_pda_auth
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
     )]
-    pub vault_lst_account: Account<'info, TokenAccount>,
+    pub vault_lst_account: InterfaceAccount<'info, TokenAccount>,
+
+    // the new vault is appended to the registry, index = previous vaults count
+    #[account(mut, has_one = main_state,
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULT_REGISTRY_SEED
+        ],
+        bump,
+        realloc = VaultRegistry::space(vault_registry.vaults.len() + 1),
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_create_secondary_vault(ctx: Context<CreateSecondaryVault>) -> Result<()> {
+/// the price source state account, if the kind requires one, is expected in remaining_accounts[0]
+pub fn handle_create_secondary_vault(
+    ctx: Context<CreateSecondaryVault>,
+    price_source_kind: PriceSourceKind,
+    price_source_state: Pubkey,
+) -> Result<()> {
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
+    let lst_mint_decimals = ctx.accounts.lst_mint.decimals;
+    require!(
+        (MIN_LST_DECIMALS..=MAX_LST_DECIMALS).contains(&lst_mint_decimals),
+        ErrorCode::UnsupportedLstDecimals
+    );
+    check_price_source(
+        &ctx.accounts.lst_mint.key(),
+        lst_mint_decimals,
+        price_source_kind,
+        &price_source_state,
+        ctx.remaining_accounts.first(),
+    )?;
     ctx.accounts.vault_state.set_inner(SecondaryVaultState {
         lst_mint: ctx.accounts.lst_mint.key(),
         vault_total_lst_amount: 0,
@@ -66,6 +102,25 @@ pub fn handle_create_secondary_vault(ctx: Context<CreateSecondaryVault>) -> Resu
         tickets_target_sol_amount: 0,
         deposits_disabled: true,
         token_deposit_cap: 0,
+        instant_unstake_cap_per_epoch: 0,
+        instant_unstake_epoch: 0,
+        instant_unstaked_in_epoch: 0,
+        price_source_kind,
+        price_source_state,
+        lst_mint_decimals,
+        price_epoch_policy: price_adapter(price_source_kind)?.default_price_epoch_policy(),
+        lst_sol_price_outdated: false,
+        max_price_increase_bp: 0,
+        max_price_decrease_bp: 0,
+        price_reference_p32: 0,
+        price_reference_epoch: 0,
+        price_frozen: false,
+        pending_lst_sol_price_p32: 0,
+        pending_lst_sol_price_timestamp: 0,
+        pending_lst_sol_price_epoch: 0,
+        max_price_age_seconds: 0,
     });
-    Ok(())
+    ctx.accounts
+        .vault_registry
+        .add_vault(ctx.accounts.vault_state.key())
 }
@@ -0,0 +1,72 @@
+use crate::constants::*;
+use crate::state::{MainVaultState, SecondaryVaultState, VaultRegistry};
+use anchor_lang::prelude::*;
+
+/// creates the (empty) vault registry of a main_state
+/// secondary vaults created before the registry existed are added with `register_secondary_vault`
+#[derive(Accounts)]
+pub struct CreateVaultRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(has_one=admin)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(init, payer = admin, space = VaultRegistry::space(0),
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULT_REGISTRY_SEED
+        ],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_create_vault_registry(ctx: Context<CreateVaultRegistry>) -> Result<()> {
+    ctx.accounts.vault_registry.set_inner(VaultRegistry {
+        main_state: ctx.accounts.main_state.key(),
+        vaults: vec![],
+    });
+    Ok(())
+}
+
+/// adds an existing secondary vault (created before the registry existed) to the vault registry
+#[derive(Accounts)]
+pub struct RegisterSecondaryVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(has_one=admin)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            &vault_state.lst_mint.to_bytes(),
+        ],
+        bump
+    )]
+    pub vault_state: Account<'info, SecondaryVaultState>,
+
+    #[account(mut, has_one = main_state,
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULT_REGISTRY_SEED
+        ],
+        bump,
+        realloc = VaultRegistry::space(vault_registry.vaults.len() + 1),
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_register_secondary_vault(ctx: Context<RegisterSecondaryVault>) -> Result<()> {
+    ctx.accounts
+        .vault_registry
+        .add_vault(ctx.accounts.vault_state.key())
+}
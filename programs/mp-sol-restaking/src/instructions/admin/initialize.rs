@@ -7,6 +7,7 @@ use anchor_spl::token::{Mint, Token};
 const INITIAL_PERFORMANCE_FEE_BP: u16 = 1000;
 const INITIAL_WITHDRAW_FEE_BP: u16 = 10;
 const INITIAL_UNSTAKE_TICKET_WAITING_HOURS: u16 = 48;
+const INITIAL_INSTANT_UNSTAKE_FEE_BP: u16 = 30;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -41,13 +42,23 @@ pub fn handle_initialize(ctx: Context<Initialize>, operator_auth: Pubkey) -> Res
         admin: ctx.accounts.admin.key(),
         operator_auth,
         withdraw_fee_bp: INITIAL_WITHDRAW_FEE_BP,
-        _reserved_space: [0; 30],
+        paused: false,
+        ticket_claims_paused: false,
+        instant_unstake_fee_bp: INITIAL_INSTANT_UNSTAKE_FEE_BP,
+        _reserved_space: [0; 26],
         mpsol_mint: ctx.accounts.mpsol_token_mint.key(),
         treasury_mpsol_account: None,
         performance_fee_bp: INITIAL_PERFORMANCE_FEE_BP,
         backing_sol_value: 0,
         outstanding_tickets_sol_value: 0,
         unstake_ticket_waiting_hours: INITIAL_UNSTAKE_TICKET_WAITING_HOURS,
+        pause_authority: Pubkey::default(),
+        pending_admin: Pubkey::default(),
+        pending_operator_auth: Pubkey::default(),
+        pending_withdraw_fee_bp: None,
+        pending_performance_fee_bp: None,
+        pending_unstake_ticket_waiting_hours: None,
+        pending_instant_unstake_fee_bp: None,
     });
     Ok(())
 }
@@ -1,13 +1,25 @@
+pub mod acknowledge_vault_price;
 pub mod attach_common_strategy_state;
+pub mod authority_handover;
 pub mod configure_main_vault;
 pub mod configure_secondary_vault;
 pub mod create_secondary_vault;
+pub mod create_vault_registry;
 pub mod init_metadata;
 pub mod initialize;
+pub mod pause;
+pub mod realloc_main_state;
+pub mod realloc_secondary_vault;
 
+pub use acknowledge_vault_price::*;
 pub use attach_common_strategy_state::*;
+pub use authority_handover::*;
 pub use configure_main_vault::*;
 pub use configure_secondary_vault::*;
 pub use create_secondary_vault::*;
+pub use create_vault_registry::*;
 pub use init_metadata::*;
 pub use initialize::*;
+pub use pause::*;
+pub use realloc_main_state::*;
+pub use realloc_secondary_vault::*;
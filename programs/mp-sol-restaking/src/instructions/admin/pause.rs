@@ -0,0 +1,42 @@
+use crate::{error::ErrorCode, state::MainVaultState};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct PauseAuthorityAndMainState<'info> {
+    /// main_state.pause_authority or main_state.admin
+    #[account()]
+    pub authority: Signer<'info>,
+
+    #[account(mut,
+        constraint = main_state.is_pause_authority(&authority.key()) @ ErrorCode::InvalidPauseAuthority
+    )]
+    pub main_state: Account<'info, MainVaultState>,
+}
+
+/// emergency pause: blocks stake, unstake & transfer_lst_to_strat
+/// if `pause_ticket_claims`, also blocks ticket_claim
+pub fn handle_pause(
+    ctx: Context<PauseAuthorityAndMainState>,
+    pause_ticket_claims: bool,
+) -> Result<()> {
+    let main_state = &mut ctx.accounts.main_state;
+    main_state.paused = true;
+    main_state.ticket_claims_paused = pause_ticket_claims;
+    emit!(crate::events::PauseEvent {
+        main_state: main_state.key(),
+        authority: ctx.accounts.authority.key(),
+        ticket_claims_paused: pause_ticket_claims,
+    });
+    Ok(())
+}
+
+pub fn handle_unpause(ctx: Context<PauseAuthorityAndMainState>) -> Result<()> {
+    let main_state = &mut ctx.accounts.main_state;
+    main_state.paused = false;
+    main_state.ticket_claims_paused = false;
+    emit!(crate::events::UnpauseEvent {
+        main_state: main_state.key(),
+        authority: ctx.accounts.authority.key(),
+    });
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::state::MainVaultState;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+/// extends a main_state created with an older (shorter) MainVaultState layout
+/// to the current size, new fields are zero-initialized.
+/// Upgrade note: the fields added after v1.0 (`pause_authority`, handover & pending config values)
+/// are appended after `_reserved_space`, so every live main_state must be reallocated
+/// right after the program upgrade: until then no instruction taking it, including `pause`/`unpause`,
+/// can deserialize it. Zero-initialized means no pause authority (only the admin can pause)
+#[derive(Accounts)]
+pub struct ReallocMainState<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: older layout can not be deserialized, discriminator & admin checked manually
+    #[account(mut, owner = crate::ID)]
+    pub main_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_realloc_main_state(ctx: Context<ReallocMainState>) -> Result<()> {
+    let main_state = ctx.accounts.main_state.to_account_info();
+    {
+        // discriminator + admin (first field)
+        let data = main_state.try_borrow_data()?;
+        require!(
+            data.len() >= 8 + 32 && data[..8] == MainVaultState::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require_keys_eq!(
+            Pubkey::try_from(&data[8..40]).unwrap(),
+            ctx.accounts.admin.key(),
+            anchor_lang::error::ErrorCode::ConstraintHasOne
+        );
+    }
+    realloc_zeroed(
+        &main_state,
+        8 + MainVaultState::INIT_SPACE,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )
+}
+
+/// grows `account` to `new_len` (no-op if already there), the payer covers the extra rent
+pub(crate) fn realloc_zeroed<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+    let rent_missing = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if rent_missing > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent_missing,
+        )?;
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
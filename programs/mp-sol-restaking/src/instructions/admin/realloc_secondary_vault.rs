@@ -0,0 +1,44 @@
+use crate::instructions::admin::realloc_main_state::realloc_zeroed;
+use crate::state::{MainVaultState, SecondaryVaultState};
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// extends a secondary vault created with an older (shorter) SecondaryVaultState layout
+/// to the current size, new fields are zero-initialized
+#[derive(Accounts)]
+pub struct ReallocSecondaryVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(has_one = admin)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    /// CHECK: no need to deserialize the mint
+    pub lst_mint: UncheckedAccount<'info>,
+
+    /// CHECK: older layout can not be deserialized, PDA & discriminator checked
+    #[account(mut, owner = crate::ID,
+        seeds = [
+            &main_state.key().to_bytes(),
+            &lst_mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub vault_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_realloc_secondary_vault(ctx: Context<ReallocSecondaryVault>) -> Result<()> {
+    let vault_state = ctx.accounts.vault_state.to_account_info();
+    require!(
+        vault_state.try_borrow_data()?[..8] == SecondaryVaultState::DISCRIMINATOR,
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    realloc_zeroed(
+        &vault_state,
+        8 + SecondaryVaultState::INIT_SPACE,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )
+}
@@ -0,0 +1,42 @@
+use crate::error::ErrorCode;
+use crate::events::{ConfigChangeAppliedEvent, ConfigParam};
+use crate::state::MainVaultState;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+// permissionless
+pub struct ApplyPendingConfig<'info> {
+    #[account(mut)]
+    pub main_state: Account<'info, MainVaultState>,
+}
+
+/// applies all the queued config changes that reached their effective timestamp
+pub fn handle_apply_pending_config(ctx: Context<ApplyPendingConfig>) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp as u64;
+    let main_state = &mut ctx.accounts.main_state;
+    let mut applied = 0;
+    for param in [
+        ConfigParam::WithdrawFeeBp,
+        ConfigParam::PerformanceFeeBp,
+        ConfigParam::UnstakeTicketWaitingHours,
+        ConfigParam::InstantUnstakeFeeBp,
+    ] {
+        match *main_state.pending_config_mut(param) {
+            Some(pending) if pending.effective_timestamp <= now_ts => {
+                *main_state.pending_config_mut(param) = None;
+                let old_value = main_state.config_value(param);
+                main_state.set_config_value(param, pending.value);
+                emit!(ConfigChangeAppliedEvent {
+                    main_state: main_state.key(),
+                    param,
+                    old_value,
+                    new_value: pending.value,
+                });
+                applied += 1;
+            }
+            _ => {}
+        }
+    }
+    require_gt!(applied, 0, ErrorCode::PendingConfigNotEffectiveYet);
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use crate::{constants::*, error::ErrorCode, MainVaultState, SecondaryVaultState, VaultRegistry};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+// permissionless, except `pause_deposits_on_violation`
+/// remaining_accounts: for each vault in the registry, in registry order,
+/// [vault_state, vault_lst_account]. vault_state must be writable if `pause_deposits_on_violation`
+pub struct CheckInvariants<'info> {
+    /// anyone can check, only main_state.operator_auth or a pause authority can pause deposits
+    pub caller: Signer<'info>,
+
+    #[account()]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(has_one = main_state,
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULT_REGISTRY_SEED
+        ],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+
+    /// CHECK: Vaults ATA PDA Auth
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULTS_ATA_AUTH_SEED
+        ],
+        bump
+    )]
+    pub vaults_ata_pda_auth: UncheckedAccount<'info>,
+}
+
+/// verifies:
+/// - main invariant: sum(secondary_vault.vault_total_sol_value) = backing_sol_value + outstanding_tickets_sol_value
+///   (with INVARIANT_CHECK_TOLERANCE_LAMPORTS, sol-values are rounded down on each operation)
+/// - per vault: vault_total_lst_amount = in_strategies_amount + locally_stored_amount
+/// - per vault: vault_lst_account.amount >= locally_stored_amount
+///
+/// a vault with a price older than its max age is reported in `stale_vaults`: its amounts are still
+/// checked, but the sol-values are not comparable, so the main invariant is not checked (nor reported
+/// as a violation) while any vault price is stale.
+/// emits CheckInvariantsEvent. Violations do not fail the tx, so the report (and the pause) is recorded.
+/// if `pause_deposits_on_violation`, deposits are disabled in all vaults when a violation is found,
+/// the caller must be main_state.operator_auth or a pause authority
+pub fn handle_check_invariants<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CheckInvariants<'info>>,
+    pause_deposits_on_violation: bool,
+) -> Result<()> {
+    if pause_deposits_on_violation {
+        let caller = ctx.accounts.caller.key();
+        let main_state = &ctx.accounts.main_state;
+        require!(
+            caller == main_state.operator_auth || main_state.is_pause_authority(&caller),
+            ErrorCode::InvalidPauseAuthority
+        );
+    }
+
+    let registered_vaults = ctx.accounts.vault_registry.vaults.len();
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        registered_vaults * 2,
+        ErrorCode::InvalidVaultIndex
+    );
+
+    let mut vaults = Vec::with_capacity(registered_vaults);
+    let mut vaults_total_sol_value: u64 = 0;
+    let mut violating_vaults: Vec<u8> = vec![];
+    let mut stale_vaults: Vec<u8> = vec![];
+    for (index, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        let vault_state = Account::<SecondaryVaultState>::try_from(&accounts[0])?;
+        ctx.accounts
+            .vault_registry
+            .check_vault_at_index(index, &vault_state.key())?;
+        // a stale price can make a sound vault look like a violation
+        if vault_state.check_price_not_stale().is_err() {
+            stale_vaults.push(index as u8);
+        }
+
+        // the lst account must be the vault ATA, of the Token or the Token-2022 program
+        let vault_lst_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        require_keys_eq!(
+            accounts[1].key(),
+            get_associated_token_address_with_program_id(
+                &ctx.accounts.vaults_ata_pda_auth.key(),
+                &vault_state.lst_mint,
+                accounts[1].owner
+            ),
+            ErrorCode::InvalidVaultLstAccount
+        );
+
+        vaults_total_sol_value += vault_state.vault_total_sol_value()?;
+        if vault_state.vault_total_lst_amount
+            != vault_state.in_strategies_amount + vault_state.locally_stored_amount
+            || vault_lst_account.amount < vault_state.locally_stored_amount
+        {
+            violating_vaults.push(index as u8);
+        }
+        vaults.push(vault_state);
+    }
+
+    let main_state = &ctx.accounts.main_state;
+    let expected_sol_value =
+        main_state.backing_sol_value + main_state.outstanding_tickets_sol_value;
+    let main_invariant_checked = stale_vaults.is_empty();
+    let main_invariant_ok =
+        vaults_total_sol_value.abs_diff(expected_sol_value) <= INVARIANT_CHECK_TOLERANCE_LAMPORTS;
+
+    let violation = (main_invariant_checked && !main_invariant_ok) || !violating_vaults.is_empty();
+    let deposits_paused = violation && pause_deposits_on_violation;
+    if deposits_paused {
+        for vault_state in vaults.iter_mut() {
+            require!(
+                vault_state.to_account_info().is_writable,
+                ErrorCode::VaultStateMustBeWritable
+            );
+            vault_state.deposits_disabled = true;
+            vault_state.exit(&crate::ID)?;
+        }
+    }
+
+    emit!(crate::events::CheckInvariantsEvent {
+        main_state: main_state.key(),
+        vaults_count: registered_vaults as u8,
+        vaults_total_sol_value,
+        backing_sol_value: main_state.backing_sol_value,
+        outstanding_tickets_sol_value: main_state.outstanding_tickets_sol_value,
+        main_invariant_checked,
+        main_invariant_ok,
+        violating_vaults,
+        stale_vaults,
+        deposits_paused,
+    });
+    Ok(())
+}
@@ -1,18 +1,20 @@
 use crate::error::ErrorCode;
+use crate::lst_token::check_lst_mint_extensions;
 use crate::state::MainVaultState;
 use crate::{constants::*, SecondaryVaultState, VaultStrategyRelationEntry};
 use anchor_lang::prelude::*;
 
-use anchor_spl::token::{Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
 #[derive(Accounts)]
 pub struct GetLstFromStrat<'info> {
     #[account()]
     pub main_state: Account<'info, MainVaultState>,
 
-    /// CHECK: no need to decode mint
     #[account()]
-    pub lst_mint: UncheckedAccount<'info>,
+    pub lst_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(mut,
         has_one = lst_mint,
@@ -38,9 +40,10 @@ pub struct GetLstFromStrat<'info> {
     #[account(
         mut,
         associated_token::mint = lst_mint,
-        associated_token::authority = vaults_ata_pda_auth
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
     )]
-    pub vault_lst_account: Account<'info, TokenAccount>,
+    pub vault_lst_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// vault->strat relation entry
     /// if this account exists, the common_strategy_state was correctly attached to the system
@@ -75,10 +78,12 @@ pub struct GetLstFromStrat<'info> {
     #[account(mut,
         associated_token::mint = lst_mint,
         associated_token::authority = vault_strat_withdraw_auth,
+        associated_token::token_program = lst_token_program
     )]
-    lst_withdraw_account: Account<'info, TokenAccount>,
+    lst_withdraw_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn handle_get_lst_from_strat(ctx: Context<GetLstFromStrat>) -> Result<()> {
@@ -87,6 +92,8 @@ pub fn handle_get_lst_from_strat(ctx: Context<GetLstFromStrat>) -> Result<()> {
         .vault_strategy_relation_entry
         .next_withdraw_lst_amount;
     require_gt!(desired_amount, 0, ErrorCode::AmountIsZero);
+    // the mint transfer hook program can be changed after the vault is created
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
 
     let existent_amount = ctx.accounts.lst_withdraw_account.amount;
     require_gt!(existent_amount, 0, ErrorCode::ExistingAmountIsZero);
@@ -94,11 +101,13 @@ pub fn handle_get_lst_from_strat(ctx: Context<GetLstFromStrat>) -> Result<()> {
     let lst_amount = std::cmp::min(existent_amount, desired_amount);
 
     // Transfer tokens from strat deposited temp lst account to vault account
-    anchor_spl::token::transfer(
+    let vault_lst_amount_before = ctx.accounts.vault_lst_account.amount;
+    transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.lst_token_program.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.lst_withdraw_account.to_account_info(),
+                mint: ctx.accounts.lst_mint.to_account_info(),
                 to: ctx.accounts.vault_lst_account.to_account_info(),
                 authority: ctx.accounts.vault_strat_withdraw_auth.to_account_info(),
             },
@@ -109,12 +118,18 @@ pub fn handle_get_lst_from_strat(ctx: Context<GetLstFromStrat>) -> Result<()> {
             ]],
         ),
         lst_amount,
+        ctx.accounts.lst_mint.decimals,
     )?;
+    // Token-2022 transfer fees are withheld in the vault account, only the net amount is received.
+    // The fee is left in the strategy accounting, where the next `update_attached_strat_lst_amount`
+    // records it as a strategy loss
+    ctx.accounts.vault_lst_account.reload()?;
+    let received_lst_amount = ctx.accounts.vault_lst_account.amount - vault_lst_amount_before;
 
     // compute as locally stored amount
-    ctx.accounts.vault_state.locally_stored_amount += lst_amount;
+    ctx.accounts.vault_state.locally_stored_amount += received_lst_amount;
     // no longer in strategies
-    ctx.accounts.vault_state.in_strategies_amount -= lst_amount;
+    ctx.accounts.vault_state.in_strategies_amount -= received_lst_amount;
 
     // reset field next_withdraw_lst_amount
     ctx.accounts
@@ -123,7 +138,7 @@ pub fn handle_get_lst_from_strat(ctx: Context<GetLstFromStrat>) -> Result<()> {
     // this decrease of the strat lst amount is not a loss
     ctx.accounts
         .vault_strategy_relation_entry
-        .last_read_strat_lst_amount -= lst_amount;
+        .last_read_strat_lst_amount -= received_lst_amount;
 
     emit!(crate::events::GetLstFromStratEvent {
         main_state: ctx.accounts.main_state.key(),
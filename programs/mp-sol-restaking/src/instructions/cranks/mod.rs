@@ -1,4 +1,8 @@
+pub mod apply_pending_config;
+pub mod check_invariants;
 pub mod get_lst_from_strat;
+pub mod realloc_unstake_ticket;
+pub mod realloc_vault_strategy_relation_entry;
 pub mod set_next_withdraw_amount;
 pub mod transfer_lst_to_strat;
 pub mod update_attached_strat_lst_amount;
@@ -6,7 +10,11 @@ pub mod update_vault_ticket_target;
 pub mod update_vault_token_sol_price;
 pub mod verify_treasury_account;
 
+pub use apply_pending_config::*;
+pub use check_invariants::*;
 pub use get_lst_from_strat::*;
+pub use realloc_unstake_ticket::*;
+pub use realloc_vault_strategy_relation_entry::*;
 pub use set_next_withdraw_amount::*;
 pub use transfer_lst_to_strat::*;
 pub use update_attached_strat_lst_amount::*;
@@ -0,0 +1,34 @@
+use crate::instructions::admin::realloc_main_state::realloc_zeroed;
+use crate::state::UnstakeTicket;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// extends a ticket created with the v1.0 UnstakeTicket layout to the current size,
+/// `mpsol_amount` is zero-initialized (the ticket can not be cancelled).
+/// Permissionless, the payer covers the extra rent.
+/// Upgrade note: live tickets can not be claimed, cancelled or moved until reallocated
+#[derive(Accounts)]
+pub struct ReallocUnstakeTicket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: older layout can not be deserialized, discriminator checked
+    #[account(mut, owner = crate::ID)]
+    pub ticket_account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_realloc_unstake_ticket(ctx: Context<ReallocUnstakeTicket>) -> Result<()> {
+    let ticket_account = ctx.accounts.ticket_account.to_account_info();
+    require!(
+        ticket_account.try_borrow_data()?[..8] == UnstakeTicket::DISCRIMINATOR,
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    realloc_zeroed(
+        &ticket_account,
+        8 + UnstakeTicket::INIT_SPACE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )
+}
@@ -0,0 +1,36 @@
+use crate::instructions::admin::realloc_main_state::realloc_zeroed;
+use crate::state::VaultStrategyRelationEntry;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// extends a vault-strategy relation entry created with the v1.0 layout to the current size,
+/// `unrecovered_loss_lst_amount` is zero-initialized.
+/// Permissionless, the payer covers the extra rent.
+/// Upgrade note: the strategy can not be updated or moved until its entry is reallocated
+#[derive(Accounts)]
+pub struct ReallocVaultStrategyRelationEntry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: older layout can not be deserialized, discriminator checked
+    #[account(mut, owner = crate::ID)]
+    pub vault_strategy_relation_entry: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_realloc_vault_strategy_relation_entry(
+    ctx: Context<ReallocVaultStrategyRelationEntry>,
+) -> Result<()> {
+    let entry = ctx.accounts.vault_strategy_relation_entry.to_account_info();
+    require!(
+        entry.try_borrow_data()?[..8] == VaultStrategyRelationEntry::DISCRIMINATOR,
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    realloc_zeroed(
+        &entry,
+        8 + VaultStrategyRelationEntry::INIT_SPACE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )
+}
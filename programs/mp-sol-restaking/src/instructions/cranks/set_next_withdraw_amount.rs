@@ -1,6 +1,6 @@
 use crate::{constants::*, error::ErrorCode, MainVaultState, VaultStrategyRelationEntry};
 use anchor_lang::prelude::*;
-use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface::{self, TokenInterface};
 
 #[derive(Accounts)]
 /// signal the strategy how much lst we need to withdraw
@@ -49,8 +49,12 @@ pub struct SetNextWithdrawAmount<'info> {
     #[account(mut,
         associated_token::mint = lst_mint,
         associated_token::authority = vault_strat_withdraw_auth,
+        associated_token::token_program = lst_token_program
     )]
-    lst_withdraw_account: Account<'info, TokenAccount>,
+    lst_withdraw_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn handle_set_next_withdraw_amount(
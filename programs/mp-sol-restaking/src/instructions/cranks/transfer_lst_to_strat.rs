@@ -1,9 +1,12 @@
 use crate::error::ErrorCode;
+use crate::lst_token::check_lst_mint_extensions;
 use crate::state::MainVaultState;
 use crate::{constants::*, SecondaryVaultState, VaultStrategyRelationEntry};
 use anchor_lang::prelude::*;
 
-use anchor_spl::token::{Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
 #[derive(Accounts)]
 pub struct TransferLstToStrat<'info> {
@@ -14,9 +17,8 @@ pub struct TransferLstToStrat<'info> {
     #[account()]
     pub operator_auth: Signer<'info>,
 
-    /// CHECK: no need to decode mint
     #[account()]
-    pub lst_mint: UncheckedAccount<'info>,
+    pub lst_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(mut,
         has_one = lst_mint,
@@ -41,9 +43,10 @@ pub struct TransferLstToStrat<'info> {
 
     #[account(mut,
         associated_token::mint = lst_mint,
-        associated_token::authority = vaults_ata_pda_auth
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
     )]
-    pub vault_lst_account: Account<'info, TokenAccount>,
+    pub vault_lst_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// vault->strat relation entry
     /// if this account exists, the common_strategy_state was correctly attached to the system
@@ -83,24 +86,36 @@ pub struct TransferLstToStrat<'info> {
     #[account(mut,
         associated_token::mint = lst_mint,
         associated_token::authority = strategy_authority,
+        associated_token::token_program = lst_token_program
     )]
-    strategy_deposit_account: Account<'info, TokenAccount>,
+    strategy_deposit_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn handle_transfer_lst_to_strat(
     ctx: Context<TransferLstToStrat>,
     lst_amount: u64,
 ) -> Result<()> {
+    ctx.accounts.main_state.check_not_paused()?;
     require_gt!(lst_amount, 0, ErrorCode::AmountIsZero);
+    // the mint transfer hook program can be changed after the vault is created
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
+    // the liquidity reserved for tickets stays in the vault
+    require_gte!(
+        ctx.accounts.vault_state.available_for_strategies_amount()?,
+        lst_amount,
+        ErrorCode::NotEnoughTokensInTheVault
+    );
 
     // Transfer tokens from vault to strat lst
-    anchor_spl::token::transfer(
+    transfer_checked(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            ctx.accounts.lst_token_program.to_account_info(),
+            TransferChecked {
                 from: ctx.accounts.vault_lst_account.to_account_info(),
+                mint: ctx.accounts.lst_mint.to_account_info(),
                 to: ctx.accounts.strategy_deposit_account.to_account_info(),
                 authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
             },
@@ -111,9 +126,12 @@ pub fn handle_transfer_lst_to_strat(
             ]],
         ),
         lst_amount,
+        ctx.accounts.lst_mint.decimals,
     )?;
 
     // now in strategies
+    // Note: a Token-2022 transfer fee withheld in the strategy account is not in the strategy,
+    // it is recorded as a strategy loss by the next `update_attached_strat_lst_amount`
     ctx.accounts.vault_state.in_strategies_amount += lst_amount;
     // no longer locally stored amount
     ctx.accounts.vault_state.locally_stored_amount -= lst_amount;
@@ -4,13 +4,15 @@ use crate::{
     VaultStrategyRelationEntry,
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
-use shared_lib::{apply_bp, lst_amount_to_sol_value, sol_value_to_mpsol_amount};
+use anchor_spl::token::{mint_to, Mint, MintTo, Token};
+use anchor_spl::token_interface::{self, TokenInterface};
+use shared_lib::{apply_bp, sol_value_to_mpsol_amount};
 
 #[derive(Accounts)]
 /// permissionless
 /// check how much lst is now in the strategy
 /// if more lst than before => yield
+/// if less lst than before => loss, socialized among mpSOL holders
 pub struct UpdateAttachedStratLstAmount<'info> {
     #[account(mut, has_one = mpsol_mint)]
     pub main_state: Account<'info, MainVaultState>,
@@ -60,8 +62,9 @@ pub struct UpdateAttachedStratLstAmount<'info> {
     #[account(
         associated_token::mint = lst_mint,
         associated_token::authority = strategy_authority,
+        associated_token::token_program = lst_token_program
     )]
-    strategy_deposit_account: Account<'info, TokenAccount>,
+    strategy_deposit_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
     /// CHECK: get vault Auth PDA
     /// for temp-ATA to move lst from strat back to the vault
@@ -78,8 +81,9 @@ pub struct UpdateAttachedStratLstAmount<'info> {
     #[account(
         associated_token::mint = lst_mint,
         associated_token::authority = vault_strat_withdraw_auth,
+        associated_token::token_program = lst_token_program
     )]
-    lst_withdraw_account: Account<'info, TokenAccount>,
+    lst_withdraw_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
     #[account(mut, mint::authority = mpsol_mint_authority)]
     pub mpsol_mint: Box<Account<'info, Mint>>,
@@ -98,6 +102,8 @@ pub struct UpdateAttachedStratLstAmount<'info> {
     pub treasury_mpsol_account: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn handle_update_attached_strat_lst_amount(
@@ -136,13 +142,14 @@ pub fn handle_update_attached_strat_lst_amount(
         }
     };
 
-    if profit == 0 || loss > 0 {
-        // during the epoch, for leveraged strategies the SOL borrow fees can cause
-        // a temporal loss because the LST hasn't updated its price yet,
-        // in that case we ignore this update call. This instruction should be called
-        // after the LST has updated it's price (4 hs after epoch start for example)
+    if profit == 0 && loss == 0 {
+        // no change in the strategy lst amount
         return Ok(());
     }
+    // Note: during the epoch, for leveraged strategies the SOL borrow fees can cause
+    // a temporal loss because the LST hasn't updated its price yet.
+    // Losses are now recorded, so this instruction should be called
+    // after the LST has updated it's price (4 hs after epoch start for example)
 
     // if the amount of LSTs changed, update accounting in the secondary_vault
     // add to the total
@@ -153,30 +160,62 @@ pub fn handle_update_attached_strat_lst_amount(
         ctx.accounts.vault_state.in_strategies_amount + profit - loss;
 
     // compute profit/slashing in terms of SOL-value, to update main-state backing_sol_value
-    // LST/SOL price must not be stale
-    check_price_not_stale(ctx.accounts.vault_state.lst_sol_price_timestamp)?;
-    let profit_sol_value =
-        lst_amount_to_sol_value(profit, ctx.accounts.vault_state.lst_sol_price_p32);
-    // Note: dead code - kept for consistency. Because of the "if loss>0 return" loss_sol_value is always zero
-    let loss_sol_value = lst_amount_to_sol_value(loss, ctx.accounts.vault_state.lst_sol_price_p32);
+    // LST/SOL price must be current: not stale, not frozen by the circuit breaker
+    ctx.accounts.vault_state.check_price_current()?;
+    let profit_sol_value = ctx.accounts.vault_state.lst_amount_to_sol_value(profit)?;
+    let loss_sol_value = ctx.accounts.vault_state.lst_amount_to_sol_value(loss)?;
     // update main_state.backing_sol_value with delta sol-value
-    ctx.accounts.main_state.backing_sol_value =
-        ctx.accounts.main_state.backing_sol_value + profit_sol_value - loss_sol_value;
+    // Note: the loss is socialized among mpSOL holders, outstanding tickets keep their sol-value
+    ctx.accounts.main_state.backing_sol_value = (ctx.accounts.main_state.backing_sol_value
+        + profit_sol_value)
+        .checked_sub(loss_sol_value)
+        .ok_or(error!(ErrorCode::StrategyLossExceedsBacking))?;
 
     // update last read amount in vault_strategy_relation_entry
-    ctx.accounts
-        .vault_strategy_relation_entry
-        .last_read_strat_lst_amount = strat_reported_lst_amount;
+    let entry = &mut ctx.accounts.vault_strategy_relation_entry;
+    entry.last_read_strat_lst_amount = strat_reported_lst_amount;
 
-    // compute protocol fees
+    // high-water mark: a profit first recovers the previous losses, only the rest is charged the fee
+    let unrecovered_loss_lst_amount = entry.unrecovered_loss_lst_amount;
+    entry.unrecovered_loss_lst_amount = (unrecovered_loss_lst_amount + loss).saturating_sub(profit);
+    let profit_above_high_water_mark = profit.saturating_sub(unrecovered_loss_lst_amount);
+
+    if loss > 0 {
+        // slashed or hacked strategy, mpSOL price goes down
+        // no performance fee
+        emit!(crate::events::StrategyLossEvent {
+            main_state: ctx.accounts.main_state.key(),
+            lst_mint: ctx.accounts.lst_mint.key(),
+            vault_strategy_relation_entry: ctx.accounts.vault_strategy_relation_entry.key(),
+            old_lst_amount: last_read_lst_amount,
+            new_lst_amount: strat_reported_lst_amount,
+            loss_lst_amount: loss,
+            lst_price_p32: ctx.accounts.vault_state.lst_sol_price_p32,
+            loss_sol_value,
+            unrecovered_loss_lst_amount: ctx
+                .accounts
+                .vault_strategy_relation_entry
+                .unrecovered_loss_lst_amount,
+            main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+            mpsol_supply: ctx.accounts.mpsol_mint.supply,
+        });
+        return Ok(());
+    }
+
+    // compute protocol fees, on the profit above the high-water mark
     let performance_fee_mpsol_amount = {
-        let performance_fee_sol_value =
-            apply_bp(profit_sol_value, ctx.accounts.main_state.performance_fee_bp);
+        let performance_fee_sol_value = apply_bp(
+            ctx.accounts
+                .vault_state
+                .lst_amount_to_sol_value(profit_above_high_water_mark)?,
+            ctx.accounts.main_state.performance_fee_bp,
+        );
         sol_value_to_mpsol_amount(
             performance_fee_sol_value,
             ctx.accounts.main_state.backing_sol_value,
             ctx.accounts.mpsol_mint.supply,
         )
+        .ok_or(ErrorCode::AmountConversionOverflow)?
     };
 
     emit!(crate::events::UpdateAttachedStratLstAmountEvent {
@@ -187,6 +226,7 @@ pub fn handle_update_attached_strat_lst_amount(
         new_lst_amount: strat_reported_lst_amount,
         lst_price_p32: ctx.accounts.vault_state.lst_sol_price_p32,
         main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+        recovered_loss_lst_amount: profit - profit_above_high_water_mark,
         performance_fee_mpsol_amount
     });
 
@@ -229,23 +269,3 @@ pub fn handle_update_attached_strat_lst_amount(
 
     Ok(())
 }
-
-pub const ONE_DAY_IN_SECONDS: u64 = 60 * 60 * 24;
-
-fn check_price_not_stale_seconds(
-    token_sol_price_timestamp: u64,
-    max_seconds_allowed: u64,
-) -> Result<()> {
-    let now_ts = Clock::get().unwrap().unix_timestamp as u64;
-    let elapsed_seconds = now_ts - token_sol_price_timestamp;
-    require_gte!(
-        max_seconds_allowed,
-        elapsed_seconds,
-        ErrorCode::TokenSolPriceIsStale
-    );
-    Ok(())
-}
-
-pub fn check_price_not_stale(token_sol_price_timestamp: u64) -> Result<()> {
-    check_price_not_stale_seconds(token_sol_price_timestamp, ONE_DAY_IN_SECONDS)
-}
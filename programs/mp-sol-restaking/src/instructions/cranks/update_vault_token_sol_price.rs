@@ -1,15 +1,9 @@
-use crate::state::external::marinade_pool_state::{
-    MarinadeState, MARINADE_MSOL_MINT, MARINADE_STATE_ADDRESS,
-};
-use crate::state::external::spl_stake_pool_state::{
-    AccountType, SplStakePoolState, SPL_STAKE_POOL_PROGRAM,
-};
+use crate::price_adapters::{vault_lst_sol_price, vault_requires_lst_state, PriceEpochPolicy};
 use crate::state::MainVaultState;
 use crate::{error::ErrorCode, SecondaryVaultState};
 use anchor_lang::prelude::*;
-use shared_lib::{lst_amount_to_sol_value, mul_div, TWO_POW_32};
+use shared_lib::lst_amount_to_sol_value;
 
-use ::borsh::BorshDeserialize;
 use anchor_lang::solana_program::{pubkey, pubkey::Pubkey};
 
 #[derive(Accounts)]
@@ -40,19 +34,16 @@ pub const SANCTUM_SPL_1: Pubkey = pubkey!("SP12tWFxD9oJsVWNavTTBZvMbA6gkAmxtVgxd
 pub const SANCTUM_SPL_2: Pubkey = pubkey!("SPMBzsVUuoHA4Jm6KunbsotaahvVikZs1JyTW6iJvbn");
 
 pub fn handle_update_vault_token_sol_price(ctx: Context<UpdateVaultTokenSolPrice>) -> Result<()> {
-    // obtain lst-state account info if required
-    let lst_state = match ctx.accounts.lst_mint.key() {
-        // wSol is simple, always 1 - no state required
-        WSOL_MINT => None,
-        _ => {
-            // assume the corresponding marinade state account sent in remaining_accounts
-            require_eq!(
-                ctx.remaining_accounts.len(),
-                1,
-                ErrorCode::MissingLstStateInRemainingAccounts
-            );
-            Some(ctx.remaining_accounts[0].to_account_info())
-        }
+    // obtain lst-state account info if the vault price source requires it
+    let lst_state = if vault_requires_lst_state(&ctx.accounts.secondary_state)? {
+        require_eq!(
+            ctx.remaining_accounts.len(),
+            1,
+            ErrorCode::MissingLstStateInRemainingAccounts
+        );
+        Some(ctx.remaining_accounts[0].to_account_info())
+    } else {
+        None
     };
 
     internal_update_vault_token_sol_price(
@@ -62,57 +53,6 @@ pub fn handle_update_vault_token_sol_price(ctx: Context<UpdateVaultTokenSolPrice
     )
 }
 
-fn marinade_msol_price(lst_state: Option<AccountInfo>) -> Result<u64> {
-    let lst_state = lst_state.expect("must provide marinade state at remaining_accounts[0]");
-    // marinade state address is known, verify
-    require_keys_eq!(
-        *lst_state.key,
-        MARINADE_STATE_ADDRESS,
-        ErrorCode::IncorrectMarinadeStateAddress
-    );
-    // try deserialize
-    let mut data_slice = &lst_state.data.borrow()[..];
-    let marinade_state: MarinadeState = MarinadeState::deserialize(&mut data_slice)?;
-    // compute true price = total_lamports / pool_token_supply
-    // https://docs.marinade.finance/marinade-protocol/system-overview/msol-token#msol-price
-    // marinade already uses 32-bit precision price
-    Ok(marinade_state.msol_price)
-}
-
-fn spl_stake_pool_price(lst_state: Option<AccountInfo>, lst_mint: Pubkey) -> Result<u64> {
-    // verify owner program & data_len
-    let lst_state = lst_state.expect("must provide spl-stake-pool state at remaining_accounts[0]");
-
-    // lst_state.owner must be one of: SPL_STAKE_POOL_PROGRAM, SANCTUM_SPL_1 or SANCTUM_SPL_2
-    let program_id = lst_state.owner.key();
-    require!(
-        program_id == SPL_STAKE_POOL_PROGRAM
-            || program_id == SANCTUM_SPL_1
-            || program_id == SANCTUM_SPL_2,
-        ErrorCode::SplStakePoolStateAccountOwnerIsNotTheSplStakePoolProgram
-    );
-
-    // try deserialize
-    let mut data_slice = &lst_state.data.borrow()[..];
-    let spl_stake_pool_state: SplStakePoolState = SplStakePoolState::deserialize(&mut data_slice)?;
-    // debug log show data
-    // msg!("stake_pool={:?}", spl_stake_pool_state);
-    // verify mint
-    require_keys_eq!(spl_stake_pool_state.pool_mint, lst_mint);
-    // verify type
-    require!(
-        spl_stake_pool_state.account_type == AccountType::StakePool,
-        ErrorCode::AccountTypeIsNotStakePool
-    );
-    // compute true price = total_lamports / pool_token_supply
-    // with 32-bit precision
-    Ok(mul_div(
-        spl_stake_pool_state.total_lamports,
-        TWO_POW_32,
-        spl_stake_pool_state.pool_token_supply,
-    ))
-}
-
 pub fn internal_update_vault_token_sol_price(
     main_state: &mut Account<MainVaultState>,
     secondary_state: &mut Account<SecondaryVaultState>,
@@ -121,24 +61,70 @@ pub fn internal_update_vault_token_sol_price(
     //
     let old_price_p32 = secondary_state.lst_sol_price_p32;
 
-    let new_price_p32 = match secondary_state.lst_mint.key() {
-        // wSol is simple, always 1
-        WSOL_MINT => TWO_POW_32,
-        // mSol, read marinade state
-        MARINADE_MSOL_MINT => marinade_msol_price(lst_state)?,
-        // none of the above, try a generic SPL-stake-pool
-        // or Sanctum stake pools
-        _ => spl_stake_pool_price(lst_state, secondary_state.lst_mint.key())?,
+    let new_price = vault_lst_sol_price(secondary_state, lst_state.as_ref())?;
+    let new_price_p32 = new_price.price_p32;
+    let clock = Clock::get()?;
+    let now_ts = clock.unix_timestamp as u64;
+
+    // a source not updated this epoch does not include the last epoch rewards (or slashing) yet
+    let price_outdated = new_price.is_outdated(clock.epoch);
+    secondary_state.lst_sol_price_outdated = match secondary_state.price_epoch_policy {
+        PriceEpochPolicy::Reject => {
+            require!(
+                !price_outdated,
+                ErrorCode::LstPriceSourceNotUpdatedThisEpoch
+            );
+            false
+        }
+        PriceEpochPolicy::MarkOutdated => price_outdated,
+        PriceEpochPolicy::Ignore => false,
     };
 
-    secondary_state.lst_sol_price_timestamp = Clock::get().unwrap().unix_timestamp as u64;
+    // circuit breaker: a price moving more than the vault limits is not applied,
+    // the vault is frozen until the admin acknowledges the new price
+    if secondary_state.price_frozen {
+        secondary_state.set_pending_price(new_price_p32, now_ts, clock.epoch);
+        return Ok(());
+    }
+    secondary_state.update_price_reference(clock.epoch);
+    if secondary_state.price_change_exceeds_limits(new_price_p32) {
+        secondary_state.price_frozen = true;
+        secondary_state.set_pending_price(new_price_p32, now_ts, clock.epoch);
+        emit!(crate::events::VaultPriceCircuitBreakerEvent {
+            main_state: main_state.key(),
+            lst_mint: secondary_state.lst_mint.key(),
+            reference_price_p32: secondary_state.price_reference_p32,
+            stored_price_p32: old_price_p32,
+            new_price_p32,
+            max_price_increase_bp: secondary_state.max_price_increase_bp,
+            max_price_decrease_bp: secondary_state.max_price_decrease_bp,
+        });
+        return Ok(());
+    }
+
+    apply_vault_token_sol_price(main_state, secondary_state, new_price_p32, now_ts)
+}
+
+/// stores a new LST/SOL price in the vault, read at `price_timestamp`,
+/// updating main_state.backing_sol_value with the vault sol-value change
+pub fn apply_vault_token_sol_price(
+    main_state: &mut Account<MainVaultState>,
+    secondary_state: &mut Account<SecondaryVaultState>,
+    new_price_p32: u64,
+    price_timestamp: u64,
+) -> Result<()> {
+    let old_price_p32 = secondary_state.lst_sol_price_p32;
+    secondary_state.lst_sol_price_timestamp = price_timestamp;
     // only if price changed
     if new_price_p32 != old_price_p32 {
         //
         // Phase 1. Collect values
         let lst_amount = secondary_state.vault_total_lst_amount;
-        let old_sol_value = lst_amount_to_sol_value(lst_amount, old_price_p32);
-        let new_sol_value = lst_amount_to_sol_value(lst_amount, new_price_p32);
+        let lst_decimals = secondary_state.lst_decimals();
+        let old_sol_value = lst_amount_to_sol_value(lst_amount, old_price_p32, lst_decimals)
+            .ok_or(ErrorCode::AmountConversionOverflow)?;
+        let new_sol_value = lst_amount_to_sol_value(lst_amount, new_price_p32, lst_decimals)
+            .ok_or(ErrorCode::AmountConversionOverflow)?;
         let (profit, slashing) = {
             // Phase 2. ?
             if new_sol_value >= old_sol_value {
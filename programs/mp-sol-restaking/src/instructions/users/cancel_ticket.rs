@@ -0,0 +1,102 @@
+use crate::{constants::*, error::ErrorCode, MainVaultState, UnstakeTicket};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
+use shared_lib::sol_value_to_mpsol_amount;
+
+#[derive(Accounts)]
+/// Cancel an unstake-ticket: the remaining ticket sol-value is re-staked and the ticket is closed.
+/// The mpSOL minted is the ticket sol-value at the current price, capped to the mpSOL burned
+/// for the ticket: tickets do not absorb strategy losses, so after a loss the ticket only gets
+/// back what it burned and the excess sol-value goes to the mpSOL holders.
+/// The withdrawal fee is not refunded. Tickets created before v1.1 can not be cancelled
+pub struct CancelTicket<'info> {
+    #[account(mut, has_one = mpsol_mint)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, has_one = beneficiary, has_one = main_state, close = beneficiary)]
+    pub ticket_account: Account<'info, UnstakeTicket>,
+
+    #[account(mut, mint::authority = mpsol_mint_authority)]
+    pub mpsol_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Auth PDA
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            MAIN_VAULT_MINT_AUTH_SEED
+        ],
+        bump
+    )]
+    pub mpsol_mint_authority: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = mpsol_mint, token::authority = beneficiary)]
+    pub beneficiary_mpsol_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_cancel_ticket(ctx: Context<CancelTicket>) -> Result<()> {
+    ctx.accounts.main_state.check_not_paused()?;
+
+    let ticket_sol_value = ctx.accounts.ticket_account.ticket_sol_value;
+    // a fully claimed ticket is a tombstone
+    require_gt!(ticket_sol_value, 0, ErrorCode::NotEnoughSolValueInTicket);
+
+    // the mpSOL burned for the ticket is unknown for tickets created before v1.1
+    let ticket_mpsol_amount = ctx.accounts.ticket_account.mpsol_amount;
+    require_gt!(ticket_mpsol_amount, 0, ErrorCode::TicketCanNotBeCancelled);
+
+    // how much mpSOL is the ticket sol-value, at current price,
+    // capped to the mpSOL burned: the mpSOL price can not fall on cancel
+    // Note: do this computation before altering main_vault_backing_sol_value
+    let mpsol_amount = sol_value_to_mpsol_amount(
+        ticket_sol_value,
+        ctx.accounts.main_state.backing_sol_value,
+        ctx.accounts.mpsol_mint.supply,
+    )
+    .ok_or(ErrorCode::AmountConversionOverflow)?
+    .min(ticket_mpsol_amount);
+
+    // -------
+    // move the sol-value from the outstanding tickets back to the mpSOL backing
+    // -------
+    ctx.accounts.main_state.outstanding_tickets_sol_value -= ticket_sol_value;
+    ctx.accounts.main_state.backing_sol_value += ticket_sol_value;
+    // the ticket account is closed (`close = beneficiary`), zero it anyway as tombstone
+    ctx.accounts
+        .ticket_account
+        .debit_sol_value(ticket_sol_value)?;
+
+    // mint mpSOL for the user
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mpsol_mint.to_account_info(),
+                to: ctx.accounts.beneficiary_mpsol_account.to_account_info(),
+                authority: ctx.accounts.mpsol_mint_authority.to_account_info(),
+            },
+            &[&[
+                &ctx.accounts.main_state.key().to_bytes(),
+                MAIN_VAULT_MINT_AUTH_SEED,
+                &[ctx.bumps.mpsol_mint_authority],
+            ]],
+        ),
+        mpsol_amount,
+    )?;
+
+    emit!(crate::events::TicketCancelEvent {
+        main_state: ctx.accounts.main_state.key(),
+        ticket_account: ctx.accounts.ticket_account.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        ticket_sol_value,
+        mpsol_received: mpsol_amount,
+        //--- mpSOL price components after the cancel
+        main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+        mpsol_supply: ctx.accounts.mpsol_mint.supply + mpsol_amount,
+    });
+
+    Ok(())
+}
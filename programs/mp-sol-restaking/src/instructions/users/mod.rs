@@ -1,7 +1,19 @@
+pub mod cancel_ticket;
 pub mod stake;
 pub mod ticket_claim;
+pub mod ticket_claim_multi;
+pub mod ticket_nft;
+pub mod transfer_ticket;
 pub mod unstake;
+pub mod unstake_instant;
+pub mod unstake_pda_ticket;
 
+pub use cancel_ticket::*;
 pub use stake::*;
 pub use ticket_claim::*;
+pub use ticket_claim_multi::*;
+pub use ticket_nft::*;
+pub use transfer_ticket::*;
 pub use unstake::*;
+pub use unstake_instant::*;
+pub use unstake_pda_ticket::*;
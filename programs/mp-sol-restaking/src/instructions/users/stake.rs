@@ -1,10 +1,13 @@
-use crate::internal_update_vault_token_sol_price;
+use crate::lst_token::check_lst_mint_extensions;
 use crate::{constants::*, error::ErrorCode, MainVaultState, SecondaryVaultState};
-/// Stake any of the supported LST tokens
+use crate::{internal_update_vault_token_sol_price, WSOL_MINT};
+/// Stake any of the supported LST tokens, or native SOL into the wSOL vault
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
-use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount, Transfer};
-use shared_lib::{lst_amount_to_sol_value, sol_value_to_mpsol_amount, TWO_POW_32};
+use anchor_lang::system_program;
+use anchor_spl::token::{mint_to, sync_native, Mint, MintTo, SyncNative, Token, TokenAccount};
+use anchor_spl::token_interface::{self, transfer_checked, TokenInterface, TransferChecked};
+use shared_lib::{sol_value_to_mpsol_amount, TWO_POW_32};
 
 #[derive(Accounts)]
 /// Stake a LST in one of the secondary vaults
@@ -14,7 +17,7 @@ pub struct Stake<'info> {
     pub main_state: Account<'info, MainVaultState>,
 
     #[account()]
-    pub lst_mint: Box<Account<'info, Mint>>,
+    pub lst_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
 
     #[account(mut, has_one=lst_mint,
         seeds = [
@@ -35,15 +38,17 @@ pub struct Stake<'info> {
     pub vaults_ata_pda_auth: UncheckedAccount<'info>,
     #[account(mut,
         associated_token::mint = lst_mint,
-        associated_token::authority = vaults_ata_pda_auth
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
     )]
     // where the lst tokens are stored while not in strategies
-    pub vault_lst_account: Account<'info, TokenAccount>,
+    pub vault_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
     #[account()]
     pub depositor: Signer<'info>,
-    #[account(mut, token::mint = lst_mint, token::authority = depositor)]
-    pub depositor_lst_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = lst_mint, token::authority = depositor,
+        token::token_program = lst_token_program)]
+    pub depositor_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
     #[account(mut, mint::authority = mpsol_mint_authority)]
     pub mpsol_mint: Box<Account<'info, Mint>>,
@@ -61,74 +66,167 @@ pub struct Stake<'info> {
     pub depositor_mpsol_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
 }
 
 /// amount is an lst amount
 pub fn handle_stake(ctx: Context<Stake>, lst_amount: u64, ref_code: u32) -> Result<()> {
-    // check deposits are enabled in this secondary-vault
-    require_eq!(
-        ctx.accounts.vault_state.deposits_disabled,
-        false,
-        ErrorCode::DepositsInThisVaultAreDisabled
-    );
+    // the mint transfer hook program can be changed after the vault is created
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
 
-    // check token_sol_price is in range and not stale
-    // LST/SOL price must be > 1
-    require_gte!(
-        ctx.accounts.vault_state.lst_sol_price_p32,
-        TWO_POW_32,
-        ErrorCode::InvalidStoredLstPrice
-    );
+    // Transfer tokens to vault account
+    let vault_lst_amount_before = ctx.accounts.vault_lst_account.amount;
+    transfer_checked(
+        CpiContext::new(
+            ctx.accounts.lst_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.depositor_lst_account.to_account_info(),
+                mint: ctx.accounts.lst_mint.to_account_info(),
+                to: ctx.accounts.vault_lst_account.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        lst_amount,
+        ctx.accounts.lst_mint.decimals,
+    )?;
+    // Token-2022 transfer fees are withheld in the vault account,
+    // only the net amount received is staked
+    ctx.accounts.vault_lst_account.reload()?;
+    let lst_amount = ctx.accounts.vault_lst_account.amount - vault_lst_amount_before;
 
-    // we need the LST/SOL price to be updated
-    // update LST/SOL price now
-    internal_update_vault_token_sol_price(
+    let (deposited_sol_value, mpsol_amount) = internal_stake(
         &mut ctx.accounts.main_state,
         &mut ctx.accounts.vault_state,
+        ctx.accounts.mpsol_mint.supply,
         if ctx.remaining_accounts.len() >= 1 {
             Some(ctx.remaining_accounts[0].to_account_info())
         } else {
             None
         },
+        lst_amount,
     )?;
 
-    // compute the sol value of deposited lst_amount
-    let deposited_sol_value =
-        lst_amount_to_sol_value(lst_amount, ctx.accounts.vault_state.lst_sol_price_p32);
-    // check Sol-value > MIN_MOVEMENT_LAMPORTS
-    require_gte!(
-        deposited_sol_value,
-        MIN_MOVEMENT_LAMPORTS,
-        ErrorCode::DepositAmountToSmall
-    );
+    // mint mpSOL for the user
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mpsol_mint.to_account_info(),
+                to: ctx.accounts.depositor_mpsol_account.to_account_info(),
+                authority: ctx.accounts.mpsol_mint_authority.to_account_info(),
+            },
+            &[&[
+                &ctx.accounts.main_state.key().to_bytes(),
+                MAIN_VAULT_MINT_AUTH_SEED,
+                &[ctx.bumps.mpsol_mint_authority],
+            ]],
+        ),
+        mpsol_amount,
+    )?;
 
-    // how much mpSOL is sol_value_deposited, at current price
-    // Note: do this computation before altering main_vault_backing_sol_value
-    let mpsol_amount = sol_value_to_mpsol_amount(
+    emit!(crate::events::StakeEvent {
+        main_state: ctx.accounts.main_state.key(),
+        ref_code,
+        depositor: ctx.accounts.depositor.key(),
+        lst_mint: ctx.accounts.lst_mint.key(),
+        lst_amount,
         deposited_sol_value,
-        ctx.accounts.main_state.backing_sol_value,
-        ctx.accounts.mpsol_mint.supply,
-    );
+        depositor_lst_account: ctx.accounts.depositor_lst_account.key(),
+        depositor_mpsol_account: ctx.accounts.depositor_mpsol_account.key(),
+        mpsol_received: mpsol_amount,
+        //--- mpSOL price components after the stake
+        main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+        mpsol_supply: ctx.accounts.mpsol_mint.supply + mpsol_amount,
+    });
 
-    // Transfer tokens to vault account
-    {
-        let transfer_instruction = Transfer {
-            from: ctx.accounts.depositor_lst_account.to_account_info(),
-            to: ctx.accounts.vault_lst_account.to_account_info(),
-            authority: ctx.accounts.depositor.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
-        );
-        anchor_spl::token::transfer(cpi_ctx, lst_amount)?;
-    }
-    // the tokens are added to the vault total
-    ctx.accounts.vault_state.vault_total_lst_amount += lst_amount;
-    // and computed as locally stored amount
-    ctx.accounts.vault_state.locally_stored_amount += lst_amount;
+    Ok(())
+}
+
+#[derive(Accounts)]
+/// Stake native SOL: the lamports are wrapped into the wSOL secondary vault
+/// get mpSOL minted for the SOL-value of the deposit
+pub struct StakeSol<'info> {
+    #[account(mut, has_one = mpsol_mint)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(address = WSOL_MINT)]
+    pub lst_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, has_one=lst_mint,
+        seeds = [
+            &main_state.key().to_bytes(),
+            &lst_mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub vault_state: Account<'info, SecondaryVaultState>,
+    /// CHECK: Vaults ATA PDA Auth
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULTS_ATA_AUTH_SEED
+        ],
+        bump
+    )]
+    pub vaults_ata_pda_auth: UncheckedAccount<'info>,
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = vaults_ata_pda_auth
+    )]
+    // the wSOL vault account, lamports are wrapped here
+    pub vault_lst_account: Account<'info, TokenAccount>,
 
-    ctx.accounts.vault_state.check_cap()?;
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(mut, mint::authority = mpsol_mint_authority)]
+    pub mpsol_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Auth PDA
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            MAIN_VAULT_MINT_AUTH_SEED
+        ],
+        bump
+    )]
+    pub mpsol_mint_authority: UncheckedAccount<'info>,
+
+    #[account(mut, token::mint = mpsol_mint, token::authority = depositor)]
+    pub depositor_mpsol_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// amount is in lamports
+pub fn handle_stake_sol(ctx: Context<StakeSol>, lamports: u64, ref_code: u32) -> Result<()> {
+    // wrap: move the lamports to the vault wSOL account and sync its token amount
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.vault_lst_account.to_account_info(),
+            },
+        ),
+        lamports,
+    )?;
+    sync_native(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.vault_lst_account.to_account_info(),
+        },
+    ))?;
+
+    // wSOL is priced at 1, no lst state required
+    let (deposited_sol_value, mpsol_amount) = internal_stake(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.vault_state,
+        ctx.accounts.mpsol_mint.supply,
+        None,
+        lamports,
+    )?;
 
     // mint mpSOL for the user
     mint_to(
@@ -148,20 +246,15 @@ pub fn handle_stake(ctx: Context<Stake>, lst_amount: u64, ref_code: u32) -> Resu
         mpsol_amount,
     )?;
 
-    // -------
-    // keep main-state internal accounting
-    // -------
-    // by adding to main_state.backing_sol_value, mpSOL price remains the same after the mint
-    ctx.accounts.main_state.backing_sol_value += deposited_sol_value;
-
     emit!(crate::events::StakeEvent {
         main_state: ctx.accounts.main_state.key(),
         ref_code,
         depositor: ctx.accounts.depositor.key(),
         lst_mint: ctx.accounts.lst_mint.key(),
-        lst_amount,
+        lst_amount: lamports,
         deposited_sol_value,
-        depositor_lst_account: ctx.accounts.depositor_lst_account.key(),
+        // native SOL, the lamports came from the depositor system account
+        depositor_lst_account: ctx.accounts.depositor.key(),
         depositor_mpsol_account: ctx.accounts.depositor_mpsol_account.key(),
         mpsol_received: mpsol_amount,
         //--- mpSOL price components after the stake
@@ -171,3 +264,68 @@ pub fn handle_stake(ctx: Context<Stake>, lst_amount: u64, ref_code: u32) -> Resu
 
     Ok(())
 }
+
+/// common stake accounting, after `lst_amount` was moved into the vault lst account
+/// returns (deposited_sol_value, mpsol_amount to mint)
+fn internal_stake(
+    main_state: &mut Account<MainVaultState>,
+    vault_state: &mut Account<SecondaryVaultState>,
+    mpsol_supply: u64,
+    lst_state: Option<AccountInfo>,
+    lst_amount: u64,
+) -> Result<(u64, u64)> {
+    main_state.check_not_paused()?;
+
+    // check deposits are enabled in this secondary-vault
+    require_eq!(
+        vault_state.deposits_disabled,
+        false,
+        ErrorCode::DepositsInThisVaultAreDisabled
+    );
+
+    // check token_sol_price is in range and not stale
+    // LST/SOL price must be > 1
+    require_gte!(
+        vault_state.lst_sol_price_p32,
+        TWO_POW_32,
+        ErrorCode::InvalidStoredLstPrice
+    );
+
+    // we need the LST/SOL price to be updated
+    // update LST/SOL price now
+    internal_update_vault_token_sol_price(main_state, vault_state, lst_state)?;
+    vault_state.check_price_current()?;
+
+    // compute the sol value of deposited lst_amount
+    let deposited_sol_value = vault_state.lst_amount_to_sol_value(lst_amount)?;
+    // check Sol-value > MIN_MOVEMENT_LAMPORTS
+    require_gte!(
+        deposited_sol_value,
+        MIN_MOVEMENT_LAMPORTS,
+        ErrorCode::DepositAmountToSmall
+    );
+
+    // how much mpSOL is sol_value_deposited, at current price
+    // Note: do this computation before altering main_vault_backing_sol_value
+    let mpsol_amount = sol_value_to_mpsol_amount(
+        deposited_sol_value,
+        main_state.backing_sol_value,
+        mpsol_supply,
+    )
+    .ok_or(ErrorCode::AmountConversionOverflow)?;
+
+    // the tokens are added to the vault total
+    vault_state.vault_total_lst_amount += lst_amount;
+    // and computed as locally stored amount
+    vault_state.locally_stored_amount += lst_amount;
+
+    vault_state.check_cap()?;
+
+    // -------
+    // keep main-state internal accounting
+    // -------
+    // by adding to main_state.backing_sol_value, mpSOL price remains the same after the mint
+    main_state.backing_sol_value += deposited_sol_value;
+
+    Ok((deposited_sol_value, mpsol_amount))
+}
@@ -1,15 +1,17 @@
+use crate::lst_token::{check_lst_mint_extensions, lst_amount_after_transfer_fee};
 use crate::{constants::*, error::ErrorCode, MainVaultState, UnstakeTicket};
-use crate::{internal_update_vault_token_sol_price, SecondaryVaultState};
+use crate::{internal_update_vault_token_sol_price, SecondaryVaultState, WSOL_MINT};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
-use anchor_spl::token::{Token, TokenAccount, Transfer};
-use shared_lib::sol_value_to_lst_amount;
+use anchor_spl::token::{close_account, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, transfer_checked, TokenInterface, TransferChecked};
 
 #[derive(Accounts)]
 /// Claim-ticket: total o partial claim of the SOL-value of an unstake-ticket
 /// This instruction allows the ticket-beneficiary to withdraw
 /// any of the available LST tokens, up to the specified SOL-value of the ticket
 /// If all the sol-value is withdrawn, the ticket is closed
+/// `min_lst_amount` is checked against the amount received, net of the Token-2022 transfer fee
 pub struct TicketClaim<'info> {
     #[account(mut)]
     pub main_state: Account<'info, MainVaultState>,
@@ -20,12 +22,12 @@ pub struct TicketClaim<'info> {
     #[account(mut, has_one = beneficiary, has_one = main_state)]
     pub ticket_account: Account<'info, UnstakeTicket>,
 
-    /// CHECK: no need to decode mint
     #[account()]
-    pub lst_mint: UncheckedAccount<'info>,
+    pub lst_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
 
-    #[account(mut, token::mint = lst_mint, token::authority = beneficiary)]
-    pub beneficiary_lst_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = lst_mint, token::authority = beneficiary,
+        token::token_program = lst_token_program)]
+    pub beneficiary_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
     // secondary vaults are PDAs of main_state
     // only this program & main_state can create a secondary vault
@@ -38,6 +40,128 @@ pub struct TicketClaim<'info> {
     )]
     pub vault_state: Account<'info, SecondaryVaultState>,
 
+    /// CHECK: Auth PDA
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULTS_ATA_AUTH_SEED
+        ],
+        bump
+    )]
+    pub vaults_ata_pda_auth: UncheckedAccount<'info>,
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
+    )]
+    pub vault_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_ticket_claim(
+    ctx: Context<TicketClaim>,
+    withdraw_sol_value_amount: u64,
+    min_lst_amount: u64,
+) -> Result<()> {
+    // the mint transfer hook program can be changed after the vault is created
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
+
+    internal_ticket_debit(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.ticket_account,
+        &ctx.accounts.beneficiary,
+        withdraw_sol_value_amount,
+    )?;
+    let lst_amount_to_deliver = internal_vault_ticket_payout(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.vault_state,
+        if !ctx.remaining_accounts.is_empty() {
+            Some(ctx.remaining_accounts[0].to_account_info())
+        } else {
+            None
+        },
+        withdraw_sol_value_amount,
+    )?;
+    // check enough lst in vault
+    require_gte!(
+        ctx.accounts.vault_lst_account.amount,
+        lst_amount_to_deliver,
+        ErrorCode::NotEnoughLstInVault
+    );
+    require_gte!(
+        lst_amount_after_transfer_fee(
+            &ctx.accounts.lst_mint.to_account_info(),
+            lst_amount_to_deliver
+        )?,
+        min_lst_amount,
+        ErrorCode::LstAmountBelowMinimum
+    );
+    // send tokens to the user
+    // Note: Token-2022 transfer fees are paid by the beneficiary
+    {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.lst_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault_lst_account.to_account_info(),
+                    mint: ctx.accounts.lst_mint.to_account_info(),
+                    to: ctx.accounts.beneficiary_lst_account.to_account_info(),
+                    authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
+                },
+                &[&[
+                    &ctx.accounts.main_state.key().to_bytes(),
+                    VAULTS_ATA_AUTH_SEED,
+                    &[ctx.bumps.vaults_ata_pda_auth],
+                ]],
+            ),
+            lst_amount_to_deliver,
+            ctx.accounts.lst_mint.decimals,
+        )?;
+    }
+
+    emit!(crate::events::TicketClaimEvent {
+        main_state: ctx.accounts.main_state.key(),
+        lst_mint: ctx.accounts.lst_mint.key(),
+        ticket_account: ctx.accounts.ticket_account.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        claimed_sol_value: withdraw_sol_value_amount,
+        ticket_sol_value_remaining: ctx.accounts.ticket_account.ticket_sol_value,
+        lst_amount_delivered: lst_amount_to_deliver,
+        ticket_due_timestamp: ctx.accounts.ticket_account.ticket_due_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+/// Claim-ticket as native SOL: same as `ticket_claim` from the wSOL vault,
+/// the wSOL is unwrapped through a temporary token account closed in the same instruction,
+/// so the beneficiary receives lamports
+pub struct TicketClaimSol<'info> {
+    #[account(mut)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, has_one = beneficiary, has_one = main_state)]
+    pub ticket_account: Account<'info, UnstakeTicket>,
+
+    #[account(address = WSOL_MINT)]
+    pub lst_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut,
+        seeds = [
+            &main_state.key().to_bytes(),
+            &lst_mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub vault_state: Account<'info, SecondaryVaultState>,
+
     /// CHECK: Auth PDA
     #[account(
         seeds = [
@@ -53,23 +177,110 @@ pub struct TicketClaim<'info> {
     )]
     pub vault_lst_account: Account<'info, TokenAccount>,
 
+    // temporary wSOL account, rent paid by the beneficiary and returned on close
+    #[account(init, payer = beneficiary,
+        seeds = [
+            &main_state.key().to_bytes(),
+            UNWRAP_WSOL_SEED,
+            &beneficiary.key().to_bytes(),
+        ],
+        bump,
+        token::mint = lst_mint,
+        token::authority = vaults_ata_pda_auth,
+    )]
+    pub unwrap_wsol_account: Account<'info, TokenAccount>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handle_ticket_claim(
-    ctx: Context<TicketClaim>,
+pub fn handle_ticket_claim_sol(
+    ctx: Context<TicketClaimSol>,
     withdraw_sol_value_amount: u64,
 ) -> Result<()> {
+    // wSOL is priced at 1, no lst state required
+    internal_ticket_debit(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.ticket_account,
+        &ctx.accounts.beneficiary,
+        withdraw_sol_value_amount,
+    )?;
+    let lst_amount_to_deliver = internal_vault_ticket_payout(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.vault_state,
+        None,
+        withdraw_sol_value_amount,
+    )?;
+    // check enough lst in vault
+    require_gte!(
+        ctx.accounts.vault_lst_account.amount,
+        lst_amount_to_deliver,
+        ErrorCode::NotEnoughLstInVault
+    );
+
+    let main_state_key = ctx.accounts.main_state.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        &main_state_key.to_bytes(),
+        VAULTS_ATA_AUTH_SEED,
+        &[ctx.bumps.vaults_ata_pda_auth],
+    ]];
+    // move the wSOL to the temporary account
+    anchor_spl::token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_lst_account.to_account_info(),
+                to: ctx.accounts.unwrap_wsol_account.to_account_info(),
+                authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        lst_amount_to_deliver,
+    )?;
+    // unwrap: closing the native account sends all its lamports (amount + rent) to the beneficiary
+    close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.unwrap_wsol_account.to_account_info(),
+            destination: ctx.accounts.beneficiary.to_account_info(),
+            authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    emit!(crate::events::TicketClaimEvent {
+        main_state: ctx.accounts.main_state.key(),
+        lst_mint: ctx.accounts.lst_mint.key(),
+        ticket_account: ctx.accounts.ticket_account.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        claimed_sol_value: withdraw_sol_value_amount,
+        ticket_sol_value_remaining: ctx.accounts.ticket_account.ticket_sol_value,
+        lst_amount_delivered: lst_amount_to_deliver,
+        ticket_due_timestamp: ctx.accounts.ticket_account.ticket_due_timestamp,
+    });
+
+    Ok(())
+}
+
+/// common ticket-claim checks & accounting: debits `withdraw_sol_value_amount` from the ticket
+/// and closes it if fully claimed
+pub(crate) fn internal_ticket_debit(
+    main_state: &mut Account<MainVaultState>,
+    ticket_account: &mut Account<UnstakeTicket>,
+    beneficiary: &Signer,
+    withdraw_sol_value_amount: u64,
+) -> Result<()> {
+    main_state.check_ticket_claims_not_paused()?;
+
     // check ticket is due
     let now_ts = Clock::get().unwrap().unix_timestamp as u64;
     require_gte!(
         now_ts,
-        ctx.accounts.ticket_account.ticket_due_timestamp,
+        ticket_account.ticket_due_timestamp,
         ErrorCode::TicketIsNotDueYet
     );
 
-    let ticket_sol_value = ctx.accounts.ticket_account.ticket_sol_value;
+    let ticket_sol_value = ticket_account.ticket_sol_value;
 
     // check enough sol_value in ticket
     require_gte!(
@@ -91,95 +302,59 @@ pub fn handle_ticket_claim(
     // keep outstanding unstake tickets global accounting
     // this will make the bot remove sol-value from strategies before the ticket is due
     // -------
-    ctx.accounts.main_state.outstanding_tickets_sol_value -= withdraw_sol_value_amount;
-    // update current ticket_sol_value
-    ctx.accounts.ticket_account.ticket_sol_value -= withdraw_sol_value_amount;
-    // subtract from tickets_target_sol_amount
-    // Note: `tickets_target_sol_amount` is a target value eventually updated.
-    //       Even if this value is not correct, the beneficiary should be able to claim the ticket
-    ctx.accounts.vault_state.tickets_target_sol_amount = ctx
-        .accounts
-        .vault_state
-        .tickets_target_sol_amount
-        .saturating_sub(withdraw_sol_value_amount);
+    main_state.outstanding_tickets_sol_value -= withdraw_sol_value_amount;
+    // update current ticket_sol_value (and its pro-rata withdraw fee)
+    ticket_account.debit_sol_value(withdraw_sol_value_amount)?;
 
     // if total withdraw
-    if ctx.accounts.ticket_account.ticket_sol_value == 0 {
+    if ticket_account.ticket_sol_value == 0 {
         // close ticket,
         // mark ticket-account for deletion by moving all raw.account-storage lamports to beneficiary.
-        // at this point ctx.accounts.ticket_account.ticket_sol_value = 0, and this works as a tombstone
-        let ticket_account_info = ctx.accounts.ticket_account.to_account_info();
+        // at this point ticket_account.ticket_sol_value = 0, and this works as a tombstone
+        let ticket_account_info = ticket_account.to_account_info();
         let mut ticket_account_lamports = ticket_account_info.lamports.borrow_mut();
-        let beneficiary_account_info = ctx.accounts.beneficiary.to_account_info();
+        let beneficiary_account_info = beneficiary.to_account_info();
         let mut beneficiary_lamports = beneficiary_account_info.lamports.borrow_mut();
         **beneficiary_lamports += **ticket_account_lamports;
         **ticket_account_lamports = 0;
     } else {
         // can't leave dust, remainder > MIN_MOVEMENT_LAMPORTS
         require_gte!(
-            ctx.accounts.ticket_account.ticket_sol_value,
+            ticket_account.ticket_sol_value,
             MIN_MOVEMENT_LAMPORTS,
             ErrorCode::CantLeaveDustInTicket
         );
     }
+    Ok(())
+}
+
+/// common vault accounting to pay `sol_value_amount` of a ticket from a secondary vault,
+/// returns the lst amount the caller must deliver from the vault lst account
+pub(crate) fn internal_vault_ticket_payout(
+    main_state: &mut Account<MainVaultState>,
+    vault_state: &mut Account<SecondaryVaultState>,
+    lst_state: Option<AccountInfo>,
+    sol_value_amount: u64,
+) -> Result<u64> {
+    // subtract from tickets_target_sol_amount
+    // Note: `tickets_target_sol_amount` is a target value eventually updated.
+    //       Even if this value is not correct, the beneficiary should be able to claim the ticket
+    vault_state.tickets_target_sol_amount = vault_state
+        .tickets_target_sol_amount
+        .saturating_sub(sol_value_amount);
 
     // we need the LST/SOL price to be updated
     // update LST/SOL price now
-    internal_update_vault_token_sol_price(
-        &mut ctx.accounts.main_state,
-        &mut ctx.accounts.vault_state,
-        if ctx.remaining_accounts.len() >= 1 {
-            Some(ctx.remaining_accounts[0].to_account_info())
-        } else {
-            None
-        },
-    )?;
+    internal_update_vault_token_sol_price(main_state, vault_state, lst_state)?;
+    vault_state.check_price_current()?;
+
+    // compute how much lst is required to honor sol_value_amount
+    let lst_amount_to_deliver = vault_state.sol_value_to_lst_amount(sol_value_amount)?;
 
-    // compute how much lst is required to honor withdraw_sol_value_amount
-    let lst_amount_to_deliver = sol_value_to_lst_amount(
-        withdraw_sol_value_amount,
-        ctx.accounts.vault_state.lst_sol_price_p32,
-    );
-    // check enough lst in vault
-    require_gte!(
-        ctx.accounts.vault_lst_account.amount,
-        lst_amount_to_deliver,
-        ErrorCode::NotEnoughLstInVault
-    );
-    // send tokens to the user
-    {
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_lst_account.to_account_info(),
-                    to: ctx.accounts.beneficiary_lst_account.to_account_info(),
-                    authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
-                },
-                &[&[
-                    &ctx.accounts.main_state.key().to_bytes(),
-                    VAULTS_ATA_AUTH_SEED,
-                    &[ctx.bumps.vaults_ata_pda_auth],
-                ]],
-            ),
-            lst_amount_to_deliver,
-        )?;
-    }
     // the tokens are removed from the vault total
-    ctx.accounts.vault_state.vault_total_lst_amount -= lst_amount_to_deliver;
+    vault_state.vault_total_lst_amount -= lst_amount_to_deliver;
     // and computed as locally stored amount
-    ctx.accounts.vault_state.locally_stored_amount -= lst_amount_to_deliver;
+    vault_state.locally_stored_amount -= lst_amount_to_deliver;
 
-    emit!(crate::events::TicketClaimEvent {
-        main_state: ctx.accounts.main_state.key(),
-        lst_mint: ctx.accounts.lst_mint.key(),
-        ticket_account: ctx.accounts.ticket_account.key(),
-        beneficiary: ctx.accounts.beneficiary.key(),
-        claimed_sol_value: withdraw_sol_value_amount,
-        ticket_sol_value_remaining: ctx.accounts.ticket_account.ticket_sol_value,
-        lst_amount_delivered: lst_amount_to_deliver,
-        ticket_due_timestamp: ctx.accounts.ticket_account.ticket_due_timestamp,
-    });
-
-    Ok(())
+    Ok(lst_amount_to_deliver)
 }
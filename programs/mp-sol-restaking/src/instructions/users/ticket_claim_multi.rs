@@ -0,0 +1,191 @@
+use crate::lst_token::{check_lst_mint_extensions, lst_amount_after_transfer_fee};
+use crate::{
+    constants::*, error::ErrorCode, internal_ticket_debit, internal_vault_ticket_payout,
+    MainVaultState, SecondaryVaultState, UnstakeTicket,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{transfer_checked, Mint, TokenAccount, TransferChecked};
+
+/// number of remaining accounts per vault in `ticket_claim_multi`
+pub const TICKET_CLAIM_MULTI_ACCOUNTS_PER_VAULT: usize = 6;
+
+#[derive(Accounts)]
+/// Claim-ticket from several secondary vaults at once
+/// remaining_accounts: for each entry in `sol_value_amounts`, in the same order,
+/// [vault_state (mut), lst_mint, vault_lst_account (mut), beneficiary_lst_account (mut), lst_state, lst_token_program]
+/// lst_state is the Marinade/SPL stake-pool state to update the LST/SOL price,
+/// for wSOL any account can be used (it is not read)
+/// lst_token_program is the Token or Token-2022 program, owner of the lst mint
+/// The ticket is debited with the sum of `sol_value_amounts`, if it reaches zero the ticket is closed
+/// `min_lst_amounts[i]` is checked against the lst received for `sol_value_amounts[i]`,
+/// net of the Token-2022 transfer fee
+pub struct TicketClaimMulti<'info> {
+    #[account(mut)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, has_one = beneficiary, has_one = main_state)]
+    pub ticket_account: Account<'info, UnstakeTicket>,
+
+    /// CHECK: Auth PDA
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULTS_ATA_AUTH_SEED
+        ],
+        bump
+    )]
+    pub vaults_ata_pda_auth: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_ticket_claim_multi<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TicketClaimMulti<'info>>,
+    sol_value_amounts: Vec<u64>,
+    min_lst_amounts: Vec<u64>,
+) -> Result<()> {
+    require_eq!(
+        min_lst_amounts.len(),
+        sol_value_amounts.len(),
+        ErrorCode::InvalidMinLstAmountsCount
+    );
+    require!(
+        !sol_value_amounts.is_empty()
+            && ctx.remaining_accounts.len()
+                == sol_value_amounts.len() * TICKET_CLAIM_MULTI_ACCOUNTS_PER_VAULT,
+        ErrorCode::InvalidRemainingAccountsCount
+    );
+
+    // debit the total from the ticket, all checks (due, dust, pause) apply to the total
+    // user-supplied amounts: an overflowing total is more than any ticket is worth
+    let total_sol_value = sol_value_amounts
+        .iter()
+        .try_fold(0u64, |total, amount| total.checked_add(*amount))
+        .ok_or(ErrorCode::NotEnoughSolValueInTicket)?;
+    internal_ticket_debit(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.ticket_account,
+        &ctx.accounts.beneficiary,
+        total_sol_value,
+    )?;
+
+    let main_state_key = ctx.accounts.main_state.key();
+    let mut paid_vaults: Vec<Pubkey> = Vec::with_capacity(sol_value_amounts.len());
+    for ((accounts, sol_value_amount), min_lst_amount) in ctx
+        .remaining_accounts
+        .chunks(TICKET_CLAIM_MULTI_ACCOUNTS_PER_VAULT)
+        .zip(sol_value_amounts)
+        .zip(min_lst_amounts)
+    {
+        let mut vault_state = Account::<SecondaryVaultState>::try_from(&accounts[0])?;
+        // the vault must be a secondary vault of this main_state, used once
+        let (expected_vault_state, _) = Pubkey::find_program_address(
+            &[&main_state_key.to_bytes(), &vault_state.lst_mint.to_bytes()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            vault_state.key(),
+            expected_vault_state,
+            ErrorCode::InvalidVaultState
+        );
+        require!(
+            !paid_vaults.contains(&vault_state.key()),
+            ErrorCode::DuplicatedVaultInClaim
+        );
+        paid_vaults.push(vault_state.key());
+        require!(
+            vault_state.to_account_info().is_writable,
+            ErrorCode::VaultStateMustBeWritable
+        );
+
+        // the mint of the vault, owned by the Token or the Token-2022 program
+        require_keys_eq!(
+            accounts[1].key(),
+            vault_state.lst_mint,
+            ErrorCode::InvalidVaultState
+        );
+        let lst_mint = InterfaceAccount::<Mint>::try_from(&accounts[1])?;
+        // the mint transfer hook program can be changed after the vault is created
+        check_lst_mint_extensions(&accounts[1])?;
+        let lst_token_program = &accounts[5];
+        require_keys_eq!(
+            lst_token_program.key(),
+            *accounts[1].owner,
+            ErrorCode::InvalidLstTokenProgram
+        );
+
+        // the lst account must be the vault ATA
+        require_keys_eq!(
+            accounts[2].key(),
+            get_associated_token_address_with_program_id(
+                &ctx.accounts.vaults_ata_pda_auth.key(),
+                &vault_state.lst_mint,
+                lst_token_program.key
+            ),
+            ErrorCode::InvalidVaultLstAccount
+        );
+        let vault_lst_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+
+        let beneficiary_lst_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+        require!(
+            beneficiary_lst_account.mint == vault_state.lst_mint
+                && beneficiary_lst_account.owner == ctx.accounts.beneficiary.key(),
+            ErrorCode::InvalidBeneficiaryLstAccount
+        );
+
+        let lst_amount_to_deliver = internal_vault_ticket_payout(
+            &mut ctx.accounts.main_state,
+            &mut vault_state,
+            Some(accounts[4].clone()),
+            sol_value_amount,
+        )?;
+        // check enough lst in vault
+        require_gte!(
+            vault_lst_account.amount,
+            lst_amount_to_deliver,
+            ErrorCode::NotEnoughLstInVault
+        );
+        require_gte!(
+            lst_amount_after_transfer_fee(&accounts[1], lst_amount_to_deliver)?,
+            min_lst_amount,
+            ErrorCode::LstAmountBelowMinimum
+        );
+        // send tokens to the user, Token-2022 transfer fees are paid by the beneficiary
+        transfer_checked(
+            CpiContext::new_with_signer(
+                lst_token_program.clone(),
+                TransferChecked {
+                    from: accounts[2].clone(),
+                    mint: accounts[1].clone(),
+                    to: accounts[3].clone(),
+                    authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
+                },
+                &[&[
+                    &main_state_key.to_bytes(),
+                    VAULTS_ATA_AUTH_SEED,
+                    &[ctx.bumps.vaults_ata_pda_auth],
+                ]],
+            ),
+            lst_amount_to_deliver,
+            lst_mint.decimals,
+        )?;
+        vault_state.exit(&crate::ID)?;
+
+        emit!(crate::events::TicketClaimEvent {
+            main_state: main_state_key,
+            lst_mint: vault_state.lst_mint,
+            ticket_account: ctx.accounts.ticket_account.key(),
+            beneficiary: ctx.accounts.beneficiary.key(),
+            claimed_sol_value: sol_value_amount,
+            ticket_sol_value_remaining: ctx.accounts.ticket_account.ticket_sol_value,
+            lst_amount_delivered: lst_amount_to_deliver,
+            ticket_due_timestamp: ctx.accounts.ticket_account.ticket_due_timestamp,
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,370 @@
+use crate::lst_token::{check_lst_mint_extensions, lst_amount_after_transfer_fee};
+use crate::{
+    constants::*, error::ErrorCode, internal_ticket_debit, internal_vault_ticket_payout,
+    SecondaryVaultState, UnstakeTicket,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{
+    burn_nft, create_master_edition_v3, create_metadata_accounts_v3,
+    mpl_token_metadata::types::DataV2, BurnNft, CreateMasterEditionV3, CreateMetadataAccountsV3,
+    Metadata as Metaplex,
+};
+use anchor_spl::token::{mint_to, Mint, MintTo, Token, TokenAccount};
+use anchor_spl::token_interface::{self, transfer_checked, TokenInterface, TransferChecked};
+
+#[derive(Accounts)]
+/// Tokenize an unstake-ticket: a Metaplex NFT, PDA(TICKET_NFT_MINT_SEED, ticket_account),
+/// is minted to the beneficiary and becomes the ticket beneficiary.
+/// `unstake` and `unstake_pda_ticket` can also mint the NFT, see `Unstake`.
+/// The NFT holder can claim with `ticket_claim_nft`, the NFT is burned on full claim.
+/// Since the ticket beneficiary is the NFT mint, the account-owned ticket instructions
+/// (ticket_claim, transfer_ticket, split_ticket...) can not be used with NFT tickets
+pub struct TokenizeTicket<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, has_one = beneficiary)]
+    pub ticket_account: Box<Account<'info, UnstakeTicket>>,
+
+    /// CHECK: Auth PDA, mint, freeze & metadata update authority for the NFT tickets
+    #[account(
+        seeds = [
+            &ticket_account.main_state.to_bytes(),
+            TICKET_NFT_AUTH_SEED
+        ],
+        bump
+    )]
+    pub ticket_nft_authority: UncheckedAccount<'info>,
+
+    #[account(init, payer = beneficiary,
+        seeds = [
+            TICKET_NFT_MINT_SEED,
+            &ticket_account.key().to_bytes(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = ticket_nft_authority,
+        mint::freeze_authority = ticket_nft_authority,
+    )]
+    pub ticket_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(init, payer = beneficiary,
+        associated_token::mint = ticket_nft_mint,
+        associated_token::authority = beneficiary
+    )]
+    pub beneficiary_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: New Metaplex Account being created, PDA("metadata",token_metadata_program,mint)
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: New Metaplex Account being created, PDA("metadata",token_metadata_program,mint,"edition")
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metaplex>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_tokenize_ticket(ctx: Context<TokenizeTicket>) -> Result<()> {
+    internal_tokenize_ticket(
+        &mut ctx.accounts.ticket_account,
+        TicketNftAccounts {
+            beneficiary: ctx.accounts.beneficiary.to_account_info(),
+            ticket_nft_authority: ctx.accounts.ticket_nft_authority.to_account_info(),
+            ticket_nft_mint: ctx.accounts.ticket_nft_mint.to_account_info(),
+            beneficiary_nft_account: ctx.accounts.beneficiary_nft_account.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        },
+        ctx.bumps.ticket_nft_authority,
+    )
+}
+
+/// accounts to mint a ticket NFT, see `TokenizeTicket`
+/// `beneficiary` is the current ticket beneficiary, pays for the NFT accounts
+pub(crate) struct TicketNftAccounts<'info> {
+    pub beneficiary: AccountInfo<'info>,
+    pub ticket_nft_authority: AccountInfo<'info>,
+    pub ticket_nft_mint: AccountInfo<'info>,
+    pub beneficiary_nft_account: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_metadata_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// mints the ticket NFT to the beneficiary and makes the NFT mint the ticket beneficiary
+/// used by `tokenize_ticket` and by `unstake`/`unstake_pda_ticket` when the NFT accounts are sent
+pub(crate) fn internal_tokenize_ticket<'info>(
+    ticket_account: &mut Account<'info, UnstakeTicket>,
+    nft: TicketNftAccounts<'info>,
+    ticket_nft_authority_bump: u8,
+) -> Result<()> {
+    // a fully claimed ticket is a tombstone
+    require_gt!(
+        ticket_account.ticket_sol_value,
+        0,
+        ErrorCode::NotEnoughSolValueInTicket
+    );
+    // from now on, the ticket is claimed by the NFT holder
+    ticket_account.beneficiary = nft.ticket_nft_mint.key();
+
+    let main_state_key = ticket_account.main_state;
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        &main_state_key.to_bytes(),
+        TICKET_NFT_AUTH_SEED,
+        &[ticket_nft_authority_bump],
+    ]];
+
+    // mint the NFT to the beneficiary
+    mint_to(
+        CpiContext::new_with_signer(
+            nft.token_program.clone(),
+            MintTo {
+                mint: nft.ticket_nft_mint.clone(),
+                to: nft.beneficiary_nft_account.clone(),
+                authority: nft.ticket_nft_authority.clone(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+
+    let token_data: DataV2 = DataV2 {
+        name: String::from("mpSOL Unstake Ticket"),
+        symbol: String::from("mpSOL-T"),
+        uri: String::from("https://metapool.app/static/mpSOL.json"),
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            nft.token_metadata_program.clone(),
+            CreateMetadataAccountsV3 {
+                payer: nft.beneficiary.clone(),
+                update_authority: nft.ticket_nft_authority.clone(),
+                mint: nft.ticket_nft_mint.clone(),
+                metadata: nft.metadata.clone(),
+                mint_authority: nft.ticket_nft_authority.clone(),
+                system_program: nft.system_program.clone(),
+                rent: nft.rent.clone(),
+            },
+            signer_seeds,
+        ),
+        token_data,
+        false,
+        true,
+        None,
+    )?;
+
+    // the master edition takes the mint & freeze authority, supply stays at 1
+    create_master_edition_v3(
+        CpiContext::new_with_signer(
+            nft.token_metadata_program.clone(),
+            CreateMasterEditionV3 {
+                edition: nft.master_edition.clone(),
+                mint: nft.ticket_nft_mint.clone(),
+                update_authority: nft.ticket_nft_authority.clone(),
+                mint_authority: nft.ticket_nft_authority.clone(),
+                payer: nft.beneficiary.clone(),
+                metadata: nft.metadata.clone(),
+                token_program: nft.token_program.clone(),
+                system_program: nft.system_program.clone(),
+                rent: nft.rent.clone(),
+            },
+            signer_seeds,
+        ),
+        Some(0),
+    )?;
+
+    emit!(crate::events::TicketTokenizedEvent {
+        main_state: main_state_key,
+        ticket_account: ticket_account.key(),
+        old_beneficiary: nft.beneficiary.key(),
+        ticket_nft_mint: nft.ticket_nft_mint.key(),
+        ticket_sol_value: ticket_account.ticket_sol_value,
+        ticket_due_timestamp: ticket_account.ticket_due_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+/// Claim-ticket for NFT tickets: total o partial claim, authorized by holding the ticket NFT
+/// If all the sol-value is withdrawn, the ticket is closed and the NFT burned
+/// remaining_accounts[0]: lst state, see `ticket_claim`
+/// `min_lst_amount` is checked against the amount received, net of the Token-2022 transfer fee
+pub struct TicketClaimNft<'info> {
+    #[account(mut)]
+    pub main_state: Box<Account<'info, crate::MainVaultState>>,
+
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(mut, has_one = main_state,
+        constraint = ticket_account.beneficiary == ticket_nft_mint.key() @ ErrorCode::NotAnNftTicket
+    )]
+    pub ticket_account: Box<Account<'info, UnstakeTicket>>,
+
+    #[account(mut,
+        seeds = [
+            TICKET_NFT_MINT_SEED,
+            &ticket_account.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub ticket_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, token::mint = ticket_nft_mint, token::authority = holder,
+        constraint = holder_nft_account.amount == 1 @ ErrorCode::NotTheTicketNftHolder
+    )]
+    pub holder_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex metadata of the ticket NFT, checked by the metadata program on burn
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: Metaplex master edition of the ticket NFT, checked by the metadata program on burn
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account()]
+    pub lst_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(mut, token::mint = lst_mint, token::authority = holder,
+        token::token_program = lst_token_program)]
+    pub holder_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut,
+        seeds = [
+            &main_state.key().to_bytes(),
+            &lst_mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub vault_state: Box<Account<'info, SecondaryVaultState>>,
+
+    /// CHECK: Auth PDA
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULTS_ATA_AUTH_SEED
+        ],
+        bump
+    )]
+    pub vaults_ata_pda_auth: UncheckedAccount<'info>,
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
+    )]
+    pub vault_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metaplex>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_ticket_claim_nft(
+    ctx: Context<TicketClaimNft>,
+    withdraw_sol_value_amount: u64,
+    min_lst_amount: u64,
+) -> Result<()> {
+    // the mint transfer hook program can be changed after the vault is created
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
+
+    internal_ticket_debit(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.ticket_account,
+        &ctx.accounts.holder,
+        withdraw_sol_value_amount,
+    )?;
+    let lst_amount_to_deliver = internal_vault_ticket_payout(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.vault_state,
+        if !ctx.remaining_accounts.is_empty() {
+            Some(ctx.remaining_accounts[0].to_account_info())
+        } else {
+            None
+        },
+        withdraw_sol_value_amount,
+    )?;
+    // check enough lst in vault
+    require_gte!(
+        ctx.accounts.vault_lst_account.amount,
+        lst_amount_to_deliver,
+        ErrorCode::NotEnoughLstInVault
+    );
+    require_gte!(
+        lst_amount_after_transfer_fee(
+            &ctx.accounts.lst_mint.to_account_info(),
+            lst_amount_to_deliver
+        )?,
+        min_lst_amount,
+        ErrorCode::LstAmountBelowMinimum
+    );
+    // send tokens to the holder, Token-2022 transfer fees are paid by the holder
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.lst_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_lst_account.to_account_info(),
+                mint: ctx.accounts.lst_mint.to_account_info(),
+                to: ctx.accounts.holder_lst_account.to_account_info(),
+                authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
+            },
+            &[&[
+                &ctx.accounts.main_state.key().to_bytes(),
+                VAULTS_ATA_AUTH_SEED,
+                &[ctx.bumps.vaults_ata_pda_auth],
+            ]],
+        ),
+        lst_amount_to_deliver,
+        ctx.accounts.lst_mint.decimals,
+    )?;
+
+    // if total withdraw, burn the NFT
+    // (closes the holder token account, metadata & edition, rent goes to the holder)
+    if ctx.accounts.ticket_account.ticket_sol_value == 0 {
+        burn_nft(
+            CpiContext::new(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                BurnNft {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    owner: ctx.accounts.holder.to_account_info(),
+                    mint: ctx.accounts.ticket_nft_mint.to_account_info(),
+                    token: ctx.accounts.holder_nft_account.to_account_info(),
+                    edition: ctx.accounts.master_edition.to_account_info(),
+                    spl_token: ctx.accounts.token_program.to_account_info(),
+                },
+            ),
+            None,
+        )?;
+    }
+
+    emit!(crate::events::TicketClaimEvent {
+        main_state: ctx.accounts.main_state.key(),
+        lst_mint: ctx.accounts.lst_mint.key(),
+        ticket_account: ctx.accounts.ticket_account.key(),
+        beneficiary: ctx.accounts.holder.key(),
+        claimed_sol_value: withdraw_sol_value_amount,
+        ticket_sol_value_remaining: ctx.accounts.ticket_account.ticket_sol_value,
+        lst_amount_delivered: lst_amount_to_deliver,
+        ticket_due_timestamp: ctx.accounts.ticket_account.ticket_due_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use crate::{constants::*, error::ErrorCode, UnstakeTicket, UserTicketIndex};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(new_beneficiary: Pubkey)]
+/// Transfer an unstake-ticket to a new beneficiary,
+/// so tickets can be sold, moved between wallets, or used as collateral by other protocols.
+/// The ticket is re-created as the next PDA ticket in the new beneficiary `UserTicketIndex`
+/// (so it can be enumerated by the new owner) and the old ticket account is closed.
+/// The new beneficiary index must exist, see `create_user_ticket_index`
+pub struct TransferTicket<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, has_one = beneficiary, close = beneficiary)]
+    pub ticket_account: Account<'info, UnstakeTicket>,
+
+    #[account(mut,
+        seeds = [
+            &ticket_account.main_state.to_bytes(),
+            USER_TICKET_INDEX_SEED,
+            &new_beneficiary.to_bytes(),
+        ],
+        bump
+    )]
+    pub new_beneficiary_ticket_index: Account<'info, UserTicketIndex>,
+
+    #[account(init, payer = beneficiary, space = 8 + UnstakeTicket::INIT_SPACE,
+        seeds = [
+            &ticket_account.main_state.to_bytes(),
+            TICKET_SEED,
+            &new_beneficiary.to_bytes(),
+            &new_beneficiary_ticket_index.next_ticket_seq.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_ticket_account: Account<'info, UnstakeTicket>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_transfer_ticket(ctx: Context<TransferTicket>, new_beneficiary: Pubkey) -> Result<()> {
+    require_keys_neq!(
+        new_beneficiary,
+        Pubkey::default(),
+        ErrorCode::InvalidNewBeneficiary
+    );
+    require_keys_neq!(
+        new_beneficiary,
+        ctx.accounts.beneficiary.key(),
+        ErrorCode::InvalidNewBeneficiary
+    );
+    // a fully claimed ticket is a tombstone
+    require_gt!(
+        ctx.accounts.ticket_account.ticket_sol_value,
+        0,
+        ErrorCode::NotEnoughSolValueInTicket
+    );
+
+    // main_state.outstanding_tickets_sol_value is not affected, the ticket is only moved
+    let ticket = &ctx.accounts.ticket_account;
+    ctx.accounts.new_ticket_account.set_inner(UnstakeTicket {
+        beneficiary: new_beneficiary,
+        ..(**ticket).clone()
+    });
+    ctx.accounts.new_beneficiary_ticket_index.next_ticket_seq += 1;
+
+    emit!(crate::events::TicketTransferEvent {
+        main_state: ticket.main_state,
+        ticket_account: ticket.key(),
+        new_ticket_account: ctx.accounts.new_ticket_account.key(),
+        old_beneficiary: ctx.accounts.beneficiary.key(),
+        new_beneficiary,
+        ticket_sol_value: ticket.ticket_sol_value,
+        ticket_due_timestamp: ticket.ticket_due_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+/// Split an unstake-ticket: `sol_value_amount` is moved to a new ticket
+/// with the same beneficiary and due timestamp, created as the next PDA ticket
+/// in the beneficiary `UserTicketIndex`.
+/// Both tickets must keep at least MIN_MOVEMENT_LAMPORTS
+pub struct SplitTicket<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(mut, has_one = beneficiary)]
+    pub ticket_account: Account<'info, UnstakeTicket>,
+
+    #[account(mut,
+        seeds = [
+            &ticket_account.main_state.to_bytes(),
+            USER_TICKET_INDEX_SEED,
+            &beneficiary.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub user_ticket_index: Account<'info, UserTicketIndex>,
+
+    #[account(init, payer = beneficiary, space = 8 + UnstakeTicket::INIT_SPACE,
+        seeds = [
+            &ticket_account.main_state.to_bytes(),
+            TICKET_SEED,
+            &beneficiary.key().to_bytes(),
+            &user_ticket_index.next_ticket_seq.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_ticket_account: Account<'info, UnstakeTicket>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_split_ticket(ctx: Context<SplitTicket>, sol_value_amount: u64) -> Result<()> {
+    require_gte!(
+        ctx.accounts.ticket_account.ticket_sol_value,
+        sol_value_amount,
+        ErrorCode::NotEnoughSolValueInTicket
+    );
+    // can't create dust tickets
+    require_gte!(
+        sol_value_amount,
+        MIN_MOVEMENT_LAMPORTS,
+        ErrorCode::CantLeaveDustInTicket
+    );
+    let mpsol_amount = ctx
+        .accounts
+        .ticket_account
+        .debit_sol_value(sol_value_amount)?;
+    require_gte!(
+        ctx.accounts.ticket_account.ticket_sol_value,
+        MIN_MOVEMENT_LAMPORTS,
+        ErrorCode::CantLeaveDustInTicket
+    );
+
+    // main_state.outstanding_tickets_sol_value is not affected, the sol-value is only moved
+    let ticket = &ctx.accounts.ticket_account;
+    ctx.accounts.new_ticket_account.set_inner(UnstakeTicket {
+        main_state: ticket.main_state,
+        beneficiary: ticket.beneficiary,
+        ticket_sol_value: sol_value_amount,
+        ticket_due_timestamp: ticket.ticket_due_timestamp,
+        mpsol_amount,
+    });
+    ctx.accounts.user_ticket_index.next_ticket_seq += 1;
+
+    emit!(crate::events::TicketSplitEvent {
+        main_state: ticket.main_state,
+        ticket_account: ticket.key(),
+        new_ticket_account: ctx.accounts.new_ticket_account.key(),
+        beneficiary: ticket.beneficiary,
+        ticket_sol_value_remaining: ticket.ticket_sol_value,
+        new_ticket_sol_value: sol_value_amount,
+        ticket_due_timestamp: ticket.ticket_due_timestamp,
+    });
+    Ok(())
+}
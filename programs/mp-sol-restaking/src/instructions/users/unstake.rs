@@ -1,7 +1,10 @@
+use crate::instructions::users::ticket_nft::{internal_tokenize_ticket, TicketNftAccounts};
 use crate::{
     constants::*, error::ErrorCode, verify_treasury_mp_sol_balance, MainVaultState, UnstakeTicket,
 };
 use anchor_lang::{prelude::*, solana_program::pubkey::Pubkey};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata as Metaplex;
 use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer};
 use shared_lib::{apply_bp, mpsol_amount_to_sol_value};
 
@@ -9,6 +12,8 @@ use shared_lib::{apply_bp, mpsol_amount_to_sol_value};
 /// Unstake: burn mpSOL and get an unstake-ticket for the SOL-value of the mpSOL burned
 /// This instruction creates an Unstake-ticket with a SOL-value, that when due,
 /// can be exchanged for any of the available LST tokens, for the specified SOL-value
+/// If the optional NFT accounts are sent, the ticket is minted as an NFT to the unstaker,
+/// as `tokenize_ticket` does, the NFT mint is PDA(TICKET_NFT_MINT_SEED, new_ticket_account)
 pub struct Unstake<'info> {
     #[account(mut, has_one = mpsol_mint)]
     pub main_state: Account<'info, MainVaultState>,
@@ -29,36 +34,183 @@ pub struct Unstake<'info> {
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    //--- optional: send them all to get the ticket as an NFT, see `tokenize_ticket`
+    /// CHECK: Auth PDA, mint, freeze & metadata update authority for the NFT tickets
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            TICKET_NFT_AUTH_SEED
+        ],
+        bump
+    )]
+    pub ticket_nft_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(init, payer = unstaker,
+        seeds = [
+            TICKET_NFT_MINT_SEED,
+            &new_ticket_account.key().to_bytes(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = ticket_nft_authority,
+        mint::freeze_authority = ticket_nft_authority,
+    )]
+    pub ticket_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(init, payer = unstaker,
+        associated_token::mint = ticket_nft_mint,
+        associated_token::authority = unstaker
+    )]
+    pub unstaker_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: New Metaplex Account being created, PDA("metadata",token_metadata_program,mint)
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: New Metaplex Account being created, PDA("metadata",token_metadata_program,mint,"edition")
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+
+    pub rent: Option<Sysvar<'info, Rent>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub token_metadata_program: Option<Program<'info, Metaplex>>,
+}
+
+impl<'info> Unstake<'info> {
+    /// the optional NFT accounts, all of them or none
+    fn ticket_nft_accounts(&self) -> Result<Option<TicketNftAccounts<'info>>> {
+        let (
+            Some(ticket_nft_authority),
+            Some(ticket_nft_mint),
+            Some(unstaker_nft_account),
+            Some(metadata),
+            Some(master_edition),
+            Some(rent),
+            Some(_),
+            Some(token_metadata_program),
+        ) = (
+            &self.ticket_nft_authority,
+            &self.ticket_nft_mint,
+            &self.unstaker_nft_account,
+            &self.metadata,
+            &self.master_edition,
+            &self.rent,
+            &self.associated_token_program,
+            &self.token_metadata_program,
+        )
+        else {
+            require!(
+                self.ticket_nft_mint.is_none(),
+                ErrorCode::MissingTicketNftAccounts
+            );
+            return Ok(None);
+        };
+        Ok(Some(TicketNftAccounts {
+            beneficiary: self.unstaker.to_account_info(),
+            ticket_nft_authority: ticket_nft_authority.to_account_info(),
+            ticket_nft_mint: ticket_nft_mint.to_account_info(),
+            beneficiary_nft_account: unstaker_nft_account.to_account_info(),
+            metadata: metadata.to_account_info(),
+            master_edition: master_edition.to_account_info(),
+            rent: rent.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_metadata_program: token_metadata_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        }))
+    }
 }
 
 pub fn handle_unstake(ctx: Context<Unstake>, mpsol_amount: u64) -> Result<()> {
+    let (ticket_sol_value, withdrawal_fee_mpsol, ticket_due_timestamp) = internal_unstake(
+        &mut ctx.accounts.main_state,
+        &ctx.accounts.unstaker,
+        &ctx.accounts.unstaker_mpsol_account,
+        &ctx.accounts.mpsol_mint,
+        &ctx.accounts.treasury_mpsol_account,
+        &ctx.accounts.token_program,
+        mpsol_amount,
+    )?;
+
+    // initialize new_ticket_account
+    ctx.accounts
+        .new_ticket_account
+        .set_inner(crate::state::UnstakeTicket {
+            main_state: ctx.accounts.main_state.key(),
+            beneficiary: ctx.accounts.unstaker.key(),
+            ticket_sol_value,
+            ticket_due_timestamp,
+            mpsol_amount: mpsol_amount - withdrawal_fee_mpsol,
+        });
+
+    emit!(crate::events::UnstakeEvent {
+        main_state: ctx.accounts.main_state.key(),
+        unstaker: ctx.accounts.unstaker.key(),
+        mpsol_amount: mpsol_amount,
+        withdrawal_fee_mpsol,
+        ticket_account: ctx.accounts.new_ticket_account.key(),
+        ticket_sol_value,
+        unstaker_mpsol_account: ctx.accounts.unstaker_mpsol_account.key(),
+        mpsol_burned: mpsol_amount - withdrawal_fee_mpsol,
+        ticket_due_timestamp,
+        //--- mpSOL price components after the unstake
+        main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+        mpsol_supply: ctx.accounts.mpsol_mint.supply - mpsol_amount,
+    });
+
+    // optional: the ticket as an NFT
+    if let Some(nft_accounts) = ctx.accounts.ticket_nft_accounts()? {
+        internal_tokenize_ticket(
+            &mut ctx.accounts.new_ticket_account,
+            nft_accounts,
+            ctx.bumps
+                .ticket_nft_authority
+                .ok_or(ErrorCode::MissingTicketNftAccounts)?,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// burns the mpSOL (charging the withdrawal fee) and keeps main-state accounting
+/// returns (ticket_sol_value, withdrawal_fee_mpsol, ticket_due_timestamp)
+/// for the new ticket
+pub(crate) fn internal_unstake<'info>(
+    main_state: &mut Account<'info, MainVaultState>,
+    unstaker: &Signer<'info>,
+    unstaker_mpsol_account: &Account<'info, TokenAccount>,
+    mpsol_mint: &Account<'info, Mint>,
+    treasury_mpsol_account: &UncheckedAccount<'info>,
+    token_program: &Program<'info, Token>,
+    mpsol_amount: u64,
+) -> Result<(u64, u64, u64)> {
+    main_state.check_not_paused()?;
+
     // compute effective withdrawal fee
     let withdrawal_fee_mpsol: u64 = {
         // if the treasury account is set...
-        if let Some(treasury_mpsol_account) = ctx.accounts.main_state.treasury_mpsol_account {
+        if let Some(main_state_treasury_mpsol_account) = main_state.treasury_mpsol_account {
             require_keys_eq!(
-                treasury_mpsol_account,
-                ctx.accounts.treasury_mpsol_account.key(),
+                main_state_treasury_mpsol_account,
+                treasury_mpsol_account.key(),
                 ErrorCode::InvalidTreasuryMpsolAccount
             );
-            let computed_withdrawal_fee_mpsol =
-                apply_bp(mpsol_amount, ctx.accounts.main_state.withdraw_fee_bp);
+            let computed_withdrawal_fee_mpsol = apply_bp(mpsol_amount, main_state.withdraw_fee_bp);
             // transfer withdrawal_fee_mpsol to treasury
             if computed_withdrawal_fee_mpsol > 0 {
                 // if the treasury account is valid
                 if verify_treasury_mp_sol_balance(
-                    &ctx.accounts.main_state.mpsol_mint.key(),
-                    &ctx.accounts.treasury_mpsol_account,
+                    &main_state.mpsol_mint.key(),
+                    treasury_mpsol_account,
                 )
                 .is_some()
                 {
                     anchor_spl::token::transfer(
                         CpiContext::new(
-                            ctx.accounts.token_program.to_account_info(),
+                            token_program.to_account_info(),
                             Transfer {
-                                from: ctx.accounts.unstaker_mpsol_account.to_account_info(),
-                                to: ctx.accounts.treasury_mpsol_account.to_account_info(),
-                                authority: ctx.accounts.unstaker.to_account_info(),
+                                from: unstaker_mpsol_account.to_account_info(),
+                                to: treasury_mpsol_account.to_account_info(),
+                                authority: unstaker.to_account_info(),
                             },
                         ),
                         computed_withdrawal_fee_mpsol,
@@ -82,9 +234,10 @@ pub fn handle_unstake(ctx: Context<Unstake>, mpsol_amount: u64) -> Result<()> {
     // compute the sol value of the mpsol to burn
     let ticket_sol_value = mpsol_amount_to_sol_value(
         mpsol_amount - withdrawal_fee_mpsol,
-        ctx.accounts.main_state.backing_sol_value,
-        ctx.accounts.mpsol_mint.supply,
-    );
+        main_state.backing_sol_value,
+        mpsol_mint.supply,
+    )
+    .ok_or(ErrorCode::AmountConversionOverflow)?;
 
     // check sol_amount > MIN_MOVEMENT_LAMPORTS
     require_gte!(
@@ -98,55 +251,30 @@ pub fn handle_unstake(ctx: Context<Unstake>, mpsol_amount: u64) -> Result<()> {
     // -------
     burn(
         CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
+            token_program.to_account_info(),
             Burn {
-                mint: ctx.accounts.mpsol_mint.to_account_info(),
-                from: ctx.accounts.unstaker_mpsol_account.to_account_info(),
-                authority: ctx.accounts.unstaker.to_account_info(),
+                mint: mpsol_mint.to_account_info(),
+                from: unstaker_mpsol_account.to_account_info(),
+                authority: unstaker.to_account_info(),
             },
         ),
         mpsol_amount - withdrawal_fee_mpsol,
     )?;
     // by removing from main_state.backing_sol_value,
     // mpSOL price remains the same after the burn
-    ctx.accounts.main_state.backing_sol_value -= ticket_sol_value;
+    main_state.backing_sol_value -= ticket_sol_value;
     // -------
 
     // compute ticket due timestamp
+    // stored in the ticket: later changes to unstake_ticket_waiting_hours do not affect issued tickets
     let now_ts = Clock::get().unwrap().unix_timestamp as u64;
-    let ticket_due_timestamp =
-        now_ts + (ctx.accounts.main_state.unstake_ticket_waiting_hours as u64 * 60 * 60);
-
-    // initialize new_ticket_account
-    ctx.accounts
-        .new_ticket_account
-        .set_inner(crate::state::UnstakeTicket {
-            main_state: ctx.accounts.main_state.key(),
-            beneficiary: ctx.accounts.unstaker.key(),
-            ticket_sol_value,
-            ticket_due_timestamp,
-        });
+    let ticket_due_timestamp = now_ts + (main_state.unstake_ticket_waiting_hours as u64 * 60 * 60);
 
     // -------
     // keep outstanding unstake tickets global accounting
     // this will make the bot remove sol-value from strategies before the ticket is due
     // -------
-    ctx.accounts.main_state.outstanding_tickets_sol_value += ticket_sol_value;
+    main_state.outstanding_tickets_sol_value += ticket_sol_value;
 
-    emit!(crate::events::UnstakeEvent {
-        main_state: ctx.accounts.main_state.key(),
-        unstaker: ctx.accounts.unstaker.key(),
-        mpsol_amount: mpsol_amount,
-        withdrawal_fee_mpsol,
-        ticket_account: ctx.accounts.new_ticket_account.key(),
-        ticket_sol_value,
-        unstaker_mpsol_account: ctx.accounts.unstaker_mpsol_account.key(),
-        mpsol_burned: mpsol_amount - withdrawal_fee_mpsol,
-        ticket_due_timestamp,
-        //--- mpSOL price components after the unstake
-        main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
-        mpsol_supply: ctx.accounts.mpsol_mint.supply - mpsol_amount,
-    });
-
-    Ok(())
+    Ok((ticket_sol_value, withdrawal_fee_mpsol, ticket_due_timestamp))
 }
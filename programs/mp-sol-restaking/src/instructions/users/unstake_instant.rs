@@ -0,0 +1,222 @@
+use crate::lst_token::{check_lst_mint_extensions, lst_amount_after_transfer_fee};
+use crate::{
+    constants::*, error::ErrorCode, internal_update_vault_token_sol_price,
+    verify_treasury_mp_sol_balance, MainVaultState, SecondaryVaultState,
+};
+use anchor_lang::{prelude::*, solana_program::pubkey::Pubkey};
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, transfer_checked, TokenInterface, TransferChecked};
+use shared_lib::{apply_bp, mpsol_amount_to_sol_value};
+
+#[derive(Accounts)]
+/// Instant unstake: burn mpSOL and immediately get LST from the vault liquidity buffer,
+/// the part of `locally_stored_amount` not reserved for tickets (`available_for_strategies_amount`)
+/// pays `instant_unstake_fee_bp`, limited by the vault `instant_unstake_cap_per_epoch`
+/// remaining_accounts[0]: lst state (Marinade/SPL stake-pool) to update the LST/SOL price, none for wSOL
+pub struct UnstakeInstant<'info> {
+    #[account(mut, has_one = mpsol_mint)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(mut)]
+    pub unstaker: Signer<'info>,
+    #[account(mut, token::mint = mpsol_mint, token::authority = unstaker)]
+    pub unstaker_mpsol_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub mpsol_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    /// CHECK: compare to set acc in main state
+    pub treasury_mpsol_account: UncheckedAccount<'info>,
+
+    #[account()]
+    pub lst_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(mut, token::mint = lst_mint, token::authority = unstaker,
+        token::token_program = lst_token_program)]
+    pub unstaker_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(mut, has_one = lst_mint,
+        seeds = [
+            &main_state.key().to_bytes(),
+            &lst_mint.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub vault_state: Account<'info, SecondaryVaultState>,
+
+    /// CHECK: Auth PDA
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            VAULTS_ATA_AUTH_SEED
+        ],
+        bump
+    )]
+    pub vaults_ata_pda_auth: UncheckedAccount<'info>,
+    #[account(mut,
+        associated_token::mint = lst_mint,
+        associated_token::authority = vaults_ata_pda_auth,
+        associated_token::token_program = lst_token_program
+    )]
+    pub vault_lst_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handle_unstake_instant(
+    ctx: Context<UnstakeInstant>,
+    mpsol_amount: u64,
+    min_lst_amount: u64,
+) -> Result<()> {
+    ctx.accounts.main_state.check_not_paused()?;
+    require_gt!(
+        ctx.accounts.vault_state.instant_unstake_cap_per_epoch,
+        0,
+        ErrorCode::InstantUnstakeDisabledInVault
+    );
+    // the mint transfer hook program can be changed after the vault is created
+    check_lst_mint_extensions(&ctx.accounts.lst_mint.to_account_info())?;
+
+    // we need the LST/SOL price (and so the backing sol value) to be updated before pricing mpSOL
+    internal_update_vault_token_sol_price(
+        &mut ctx.accounts.main_state,
+        &mut ctx.accounts.vault_state,
+        if !ctx.remaining_accounts.is_empty() {
+            Some(ctx.remaining_accounts[0].to_account_info())
+        } else {
+            None
+        },
+    )?;
+    ctx.accounts.vault_state.check_price_current()?;
+
+    // the fee goes to the treasury if it is set & valid,
+    // else the fee mpSOL is burned without removing its sol-value, so it benefits all mpSOL holders
+    let fee_mpsol = apply_bp(mpsol_amount, ctx.accounts.main_state.instant_unstake_fee_bp);
+    let fee_to_treasury_mpsol = match ctx.accounts.main_state.treasury_mpsol_account {
+        Some(treasury_mpsol_account) if fee_mpsol > 0 => {
+            require_keys_eq!(
+                treasury_mpsol_account,
+                ctx.accounts.treasury_mpsol_account.key(),
+                ErrorCode::InvalidTreasuryMpsolAccount
+            );
+            if verify_treasury_mp_sol_balance(
+                &ctx.accounts.main_state.mpsol_mint.key(),
+                &ctx.accounts.treasury_mpsol_account,
+            )
+            .is_some()
+            {
+                anchor_spl::token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.unstaker_mpsol_account.to_account_info(),
+                            to: ctx.accounts.treasury_mpsol_account.to_account_info(),
+                            authority: ctx.accounts.unstaker.to_account_info(),
+                        },
+                    ),
+                    fee_mpsol,
+                )?;
+                fee_mpsol
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    };
+
+    // compute the sol value to pay (mpSOL price before the burn)
+    let sol_value = mpsol_amount_to_sol_value(
+        mpsol_amount - fee_mpsol,
+        ctx.accounts.main_state.backing_sol_value,
+        ctx.accounts.mpsol_mint.supply,
+    )
+    .ok_or(ErrorCode::AmountConversionOverflow)?;
+    require_gte!(
+        sol_value,
+        MIN_MOVEMENT_LAMPORTS,
+        ErrorCode::UnstakeAmountTooSmall
+    );
+
+    // -------
+    // burn the mpSOL and keep contract internal accounting
+    // -------
+    let mpsol_burned = mpsol_amount - fee_to_treasury_mpsol;
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mpsol_mint.to_account_info(),
+                from: ctx.accounts.unstaker_mpsol_account.to_account_info(),
+                authority: ctx.accounts.unstaker.to_account_info(),
+            },
+        ),
+        mpsol_burned,
+    )?;
+    ctx.accounts.main_state.backing_sol_value -= sol_value;
+
+    let lst_amount_to_deliver = ctx
+        .accounts
+        .vault_state
+        .sol_value_to_lst_amount(sol_value)?;
+    // the unstaker receives the amount net of the Token-2022 transfer fee
+    require_gte!(
+        lst_amount_after_transfer_fee(
+            &ctx.accounts.lst_mint.to_account_info(),
+            lst_amount_to_deliver
+        )?,
+        min_lst_amount,
+        ErrorCode::LstAmountBelowMinimum
+    );
+    // only the liquidity not reserved for tickets can be used
+    require_gte!(
+        ctx.accounts.vault_state.available_for_strategies_amount()?,
+        lst_amount_to_deliver,
+        ErrorCode::NotEnoughLiquidityForInstantUnstake
+    );
+    ctx.accounts
+        .vault_state
+        .add_instant_unstaked(lst_amount_to_deliver, Clock::get()?.epoch)?;
+
+    // send tokens to the user, Token-2022 transfer fees are paid by the unstaker
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.lst_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_lst_account.to_account_info(),
+                mint: ctx.accounts.lst_mint.to_account_info(),
+                to: ctx.accounts.unstaker_lst_account.to_account_info(),
+                authority: ctx.accounts.vaults_ata_pda_auth.to_account_info(),
+            },
+            &[&[
+                &ctx.accounts.main_state.key().to_bytes(),
+                VAULTS_ATA_AUTH_SEED,
+                &[ctx.bumps.vaults_ata_pda_auth],
+            ]],
+        ),
+        lst_amount_to_deliver,
+        ctx.accounts.lst_mint.decimals,
+    )?;
+    // the tokens are removed from the vault total
+    ctx.accounts.vault_state.vault_total_lst_amount -= lst_amount_to_deliver;
+    // and computed as locally stored amount
+    ctx.accounts.vault_state.locally_stored_amount -= lst_amount_to_deliver;
+
+    emit!(crate::events::UnstakeInstantEvent {
+        main_state: ctx.accounts.main_state.key(),
+        lst_mint: ctx.accounts.lst_mint.key(),
+        unstaker: ctx.accounts.unstaker.key(),
+        mpsol_amount,
+        fee_mpsol,
+        fee_to_treasury_mpsol,
+        mpsol_burned,
+        sol_value,
+        lst_amount_delivered: lst_amount_to_deliver,
+        //--- mpSOL price components after the unstake
+        main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+        mpsol_supply: ctx.accounts.mpsol_mint.supply - mpsol_burned,
+    });
+
+    Ok(())
+}
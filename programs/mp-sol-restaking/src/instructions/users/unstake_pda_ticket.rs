@@ -0,0 +1,220 @@
+use crate::instructions::users::ticket_nft::{internal_tokenize_ticket, TicketNftAccounts};
+use crate::instructions::users::unstake::internal_unstake;
+use crate::{constants::*, error::ErrorCode, MainVaultState, UnstakeTicket, UserTicketIndex};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata as Metaplex;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[derive(Accounts)]
+/// creates the ticket index of a user, required by `unstake_pda_ticket`
+/// anyone can pay for it
+pub struct CreateUserTicketIndex<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: any account, the owner of the tickets
+    pub beneficiary: UncheckedAccount<'info>,
+
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(init, payer = payer, space = 8 + UserTicketIndex::INIT_SPACE,
+        seeds = [
+            &main_state.key().to_bytes(),
+            USER_TICKET_INDEX_SEED,
+            &beneficiary.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub user_ticket_index: Account<'info, UserTicketIndex>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnstakePdaTicket<'info> {
+    /// the optional NFT accounts, all of them or none
+    fn ticket_nft_accounts(&self) -> Result<Option<TicketNftAccounts<'info>>> {
+        let (
+            Some(ticket_nft_authority),
+            Some(ticket_nft_mint),
+            Some(unstaker_nft_account),
+            Some(metadata),
+            Some(master_edition),
+            Some(rent),
+            Some(_),
+            Some(token_metadata_program),
+        ) = (
+            &self.ticket_nft_authority,
+            &self.ticket_nft_mint,
+            &self.unstaker_nft_account,
+            &self.metadata,
+            &self.master_edition,
+            &self.rent,
+            &self.associated_token_program,
+            &self.token_metadata_program,
+        )
+        else {
+            require!(
+                self.ticket_nft_mint.is_none(),
+                ErrorCode::MissingTicketNftAccounts
+            );
+            return Ok(None);
+        };
+        Ok(Some(TicketNftAccounts {
+            beneficiary: self.unstaker.to_account_info(),
+            ticket_nft_authority: ticket_nft_authority.to_account_info(),
+            ticket_nft_mint: ticket_nft_mint.to_account_info(),
+            beneficiary_nft_account: unstaker_nft_account.to_account_info(),
+            metadata: metadata.to_account_info(),
+            master_edition: master_edition.to_account_info(),
+            rent: rent.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_metadata_program: token_metadata_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        }))
+    }
+}
+
+pub fn handle_create_user_ticket_index(ctx: Context<CreateUserTicketIndex>) -> Result<()> {
+    ctx.accounts.user_ticket_index.set_inner(UserTicketIndex {
+        main_state: ctx.accounts.main_state.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+        next_ticket_seq: 0,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+/// Unstake: same as `unstake`, but the ticket is created at a deterministic address,
+/// PDA(main_state, TICKET_SEED, unstaker, user_ticket_index.next_ticket_seq),
+/// so wallets can enumerate the user tickets without `getProgramAccounts`
+/// The optional NFT accounts mint the ticket as an NFT, see `Unstake`
+pub struct UnstakePdaTicket<'info> {
+    #[account(mut, has_one = mpsol_mint)]
+    pub main_state: Account<'info, MainVaultState>,
+
+    #[account(mut)]
+    pub unstaker: Signer<'info>,
+    #[account(mut, token::mint = mpsol_mint, token::authority = unstaker)]
+    pub unstaker_mpsol_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub mpsol_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    /// CHECK: compare to set acc in main state
+    pub treasury_mpsol_account: UncheckedAccount<'info>,
+
+    #[account(mut,
+        seeds = [
+            &main_state.key().to_bytes(),
+            USER_TICKET_INDEX_SEED,
+            &unstaker.key().to_bytes(),
+        ],
+        bump
+    )]
+    pub user_ticket_index: Account<'info, UserTicketIndex>,
+
+    #[account(init, payer = unstaker, space = 8 + UnstakeTicket::INIT_SPACE,
+        seeds = [
+            &main_state.key().to_bytes(),
+            TICKET_SEED,
+            &unstaker.key().to_bytes(),
+            &user_ticket_index.next_ticket_seq.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_ticket_account: Account<'info, UnstakeTicket>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    //--- optional: send them all to get the ticket as an NFT, see `tokenize_ticket`
+    /// CHECK: Auth PDA, mint, freeze & metadata update authority for the NFT tickets
+    #[account(
+        seeds = [
+            &main_state.key().to_bytes(),
+            TICKET_NFT_AUTH_SEED
+        ],
+        bump
+    )]
+    pub ticket_nft_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(init, payer = unstaker,
+        seeds = [
+            TICKET_NFT_MINT_SEED,
+            &new_ticket_account.key().to_bytes(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = ticket_nft_authority,
+        mint::freeze_authority = ticket_nft_authority,
+    )]
+    pub ticket_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(init, payer = unstaker,
+        associated_token::mint = ticket_nft_mint,
+        associated_token::authority = unstaker
+    )]
+    pub unstaker_nft_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    /// CHECK: New Metaplex Account being created, PDA("metadata",token_metadata_program,mint)
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: New Metaplex Account being created, PDA("metadata",token_metadata_program,mint,"edition")
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+
+    pub rent: Option<Sysvar<'info, Rent>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub token_metadata_program: Option<Program<'info, Metaplex>>,
+}
+
+pub fn handle_unstake_pda_ticket(ctx: Context<UnstakePdaTicket>, mpsol_amount: u64) -> Result<()> {
+    let (ticket_sol_value, withdrawal_fee_mpsol, ticket_due_timestamp) = internal_unstake(
+        &mut ctx.accounts.main_state,
+        &ctx.accounts.unstaker,
+        &ctx.accounts.unstaker_mpsol_account,
+        &ctx.accounts.mpsol_mint,
+        &ctx.accounts.treasury_mpsol_account,
+        &ctx.accounts.token_program,
+        mpsol_amount,
+    )?;
+
+    // initialize new_ticket_account
+    ctx.accounts.new_ticket_account.set_inner(UnstakeTicket {
+        main_state: ctx.accounts.main_state.key(),
+        beneficiary: ctx.accounts.unstaker.key(),
+        ticket_sol_value,
+        ticket_due_timestamp,
+        mpsol_amount: mpsol_amount - withdrawal_fee_mpsol,
+    });
+    ctx.accounts.user_ticket_index.next_ticket_seq += 1;
+
+    emit!(crate::events::UnstakeEvent {
+        main_state: ctx.accounts.main_state.key(),
+        unstaker: ctx.accounts.unstaker.key(),
+        mpsol_amount,
+        withdrawal_fee_mpsol,
+        ticket_account: ctx.accounts.new_ticket_account.key(),
+        ticket_sol_value,
+        unstaker_mpsol_account: ctx.accounts.unstaker_mpsol_account.key(),
+        mpsol_burned: mpsol_amount - withdrawal_fee_mpsol,
+        ticket_due_timestamp,
+        //--- mpSOL price components after the unstake
+        main_vault_backing_sol_value: ctx.accounts.main_state.backing_sol_value,
+        mpsol_supply: ctx.accounts.mpsol_mint.supply - mpsol_amount,
+    });
+
+    // optional: the ticket as an NFT
+    if let Some(nft_accounts) = ctx.accounts.ticket_nft_accounts()? {
+        internal_tokenize_ticket(
+            &mut ctx.accounts.new_ticket_account,
+            nft_accounts,
+            ctx.bumps
+                .ticket_nft_authority
+                .ok_or(ErrorCode::MissingTicketNftAccounts)?,
+        )?;
+    }
+
+    Ok(())
+}
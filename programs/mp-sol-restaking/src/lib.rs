@@ -2,13 +2,18 @@ pub mod constants;
 pub mod error;
 pub mod events;
 pub mod instructions;
+pub mod lst_token;
+pub mod price_adapters;
 pub mod state;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
 pub use events::*;
 pub use instructions::*;
+pub use price_adapters::{PriceEpochPolicy, PriceSourceKind};
 pub use state::*;
 
 declare_id!("MPSoLoEnfNRFReRZSVH2V8AffSmWSR4dVoBLFm1YpAW");
@@ -42,8 +47,24 @@ pub mod mp_sol_restaking {
         init_metadata::handle_init_metadata(ctx)
     }
 
-    pub fn create_secondary_vault(ctx: Context<CreateSecondaryVault>) -> Result<()> {
-        create_secondary_vault::handle_create_secondary_vault(ctx)
+    pub fn create_secondary_vault(
+        ctx: Context<CreateSecondaryVault>,
+        price_source_kind: PriceSourceKind,
+        price_source_state: Pubkey,
+    ) -> Result<()> {
+        create_secondary_vault::handle_create_secondary_vault(
+            ctx,
+            price_source_kind,
+            price_source_state,
+        )
+    }
+
+    pub fn create_vault_registry(ctx: Context<CreateVaultRegistry>) -> Result<()> {
+        create_vault_registry::handle_create_vault_registry(ctx)
+    }
+
+    pub fn register_secondary_vault(ctx: Context<RegisterSecondaryVault>) -> Result<()> {
+        create_vault_registry::handle_register_secondary_vault(ctx)
     }
 
     // ---------------------------------
@@ -78,18 +99,80 @@ pub mod mp_sol_restaking {
         configure_main_vault::handle_configure_performance_fee(ctx, bp)
     }
 
-    pub fn configure_operator_auth(
+    pub fn configure_instant_unstake_fee(
         ctx: Context<AdminAndMainStateAccounts>,
-        auth: Pubkey,
+        bp: u16,
     ) -> Result<()> {
-        configure_main_vault::handle_configure_operator_auth(ctx, auth)
+        configure_main_vault::handle_configure_instant_unstake_fee(ctx, bp)
     }
 
-    pub fn configure_new_admin(
+    pub fn cancel_pending_config(
         ctx: Context<AdminAndMainStateAccounts>,
-        new_admin: Pubkey,
+        param: ConfigParam,
     ) -> Result<()> {
-        configure_main_vault::handle_configure_new_admin(ctx, new_admin)
+        configure_main_vault::handle_cancel_pending_config(ctx, param)
+    }
+
+    // ---------------------------------
+    // two-step admin & operator_auth handover
+
+    pub fn propose_admin(ctx: Context<AdminAndMainStateAccounts>, new_admin: Pubkey) -> Result<()> {
+        authority_handover::handle_propose_handover(ctx, HandoverRole::Admin, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        authority_handover::handle_accept_admin(ctx)
+    }
+
+    pub fn cancel_admin_proposal(ctx: Context<AdminAndMainStateAccounts>) -> Result<()> {
+        authority_handover::handle_cancel_handover(ctx, HandoverRole::Admin)
+    }
+
+    pub fn propose_operator_auth(
+        ctx: Context<AdminAndMainStateAccounts>,
+        new_operator_auth: Pubkey,
+    ) -> Result<()> {
+        authority_handover::handle_propose_handover(
+            ctx,
+            HandoverRole::OperatorAuth,
+            new_operator_auth,
+        )
+    }
+
+    pub fn accept_operator_auth(ctx: Context<AcceptOperatorAuth>) -> Result<()> {
+        authority_handover::handle_accept_operator_auth(ctx)
+    }
+
+    pub fn cancel_operator_auth_proposal(ctx: Context<AdminAndMainStateAccounts>) -> Result<()> {
+        authority_handover::handle_cancel_handover(ctx, HandoverRole::OperatorAuth)
+    }
+
+    pub fn configure_pause_authority(
+        ctx: Context<AdminAndMainStateAccounts>,
+        auth: Pubkey,
+    ) -> Result<()> {
+        configure_main_vault::handle_configure_pause_authority(ctx, auth)
+    }
+
+    pub fn realloc_main_state(ctx: Context<ReallocMainState>) -> Result<()> {
+        realloc_main_state::handle_realloc_main_state(ctx)
+    }
+
+    pub fn realloc_secondary_vault(ctx: Context<ReallocSecondaryVault>) -> Result<()> {
+        realloc_secondary_vault::handle_realloc_secondary_vault(ctx)
+    }
+
+    // ---------------------------------
+    // emergency pause, pause_authority or admin
+    pub fn pause(
+        ctx: Context<PauseAuthorityAndMainState>,
+        pause_ticket_claims: bool,
+    ) -> Result<()> {
+        pause::handle_pause(ctx, pause_ticket_claims)
+    }
+
+    pub fn unpause(ctx: Context<PauseAuthorityAndMainState>) -> Result<()> {
+        pause::handle_unpause(ctx)
     }
 
     // ---------------------------------
@@ -100,6 +183,22 @@ pub mod mp_sol_restaking {
         configure_secondary_vault::handle_configure_secondary_vault(ctx, values)
     }
 
+    pub fn configure_vault_price_source(
+        ctx: Context<ConfigureSecondaryVault>,
+        price_source_kind: PriceSourceKind,
+        price_source_state: Pubkey,
+    ) -> Result<()> {
+        configure_secondary_vault::handle_configure_vault_price_source(
+            ctx,
+            price_source_kind,
+            price_source_state,
+        )
+    }
+
+    pub fn acknowledge_vault_price(ctx: Context<AcknowledgeVaultPrice>) -> Result<()> {
+        acknowledge_vault_price::handle_acknowledge_vault_price(ctx)
+    }
+
     pub fn attach_common_strategy_state(ctx: Context<AttachCommonStrategyState>) -> Result<()> {
         attach_common_strategy_state::handle_attach_common_strategy_state(ctx)
     }
@@ -124,6 +223,27 @@ pub mod mp_sol_restaking {
         handle_update_vault_ticket_target(ctx, new_ticket_target_sol_amount)
     }
 
+    pub fn apply_pending_config(ctx: Context<ApplyPendingConfig>) -> Result<()> {
+        handle_apply_pending_config(ctx)
+    }
+
+    pub fn realloc_unstake_ticket(ctx: Context<ReallocUnstakeTicket>) -> Result<()> {
+        handle_realloc_unstake_ticket(ctx)
+    }
+
+    pub fn realloc_vault_strategy_relation_entry(
+        ctx: Context<ReallocVaultStrategyRelationEntry>,
+    ) -> Result<()> {
+        handle_realloc_vault_strategy_relation_entry(ctx)
+    }
+
+    pub fn check_invariants<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckInvariants<'info>>,
+        pause_deposits_on_violation: bool,
+    ) -> Result<()> {
+        handle_check_invariants(ctx, pause_deposits_on_violation)
+    }
+
     pub fn get_lst_from_strat(ctx: Context<GetLstFromStrat>) -> Result<()> {
         handle_get_lst_from_strat(ctx)
     }
@@ -146,11 +266,74 @@ pub mod mp_sol_restaking {
         users::stake::handle_stake(ctx, lst_amount, ref_code)
     }
 
+    pub fn stake_sol(ctx: Context<StakeSol>, lamports: u64, ref_code: u32) -> Result<()> {
+        users::stake::handle_stake_sol(ctx, lamports, ref_code)
+    }
+
     pub fn unstake(ctx: Context<Unstake>, mpsol_amount: u64) -> Result<()> {
         users::unstake::handle_unstake(ctx, mpsol_amount)
     }
 
-    pub fn ticket_claim(ctx: Context<TicketClaim>, withdraw_sol_value_amount: u64) -> Result<()> {
-        users::ticket_claim::handle_ticket_claim(ctx, withdraw_sol_value_amount)
+    pub fn create_user_ticket_index(ctx: Context<CreateUserTicketIndex>) -> Result<()> {
+        users::unstake_pda_ticket::handle_create_user_ticket_index(ctx)
+    }
+
+    pub fn unstake_pda_ticket(ctx: Context<UnstakePdaTicket>, mpsol_amount: u64) -> Result<()> {
+        users::unstake_pda_ticket::handle_unstake_pda_ticket(ctx, mpsol_amount)
+    }
+
+    pub fn tokenize_ticket(ctx: Context<TokenizeTicket>) -> Result<()> {
+        users::ticket_nft::handle_tokenize_ticket(ctx)
+    }
+
+    pub fn ticket_claim(
+        ctx: Context<TicketClaim>,
+        withdraw_sol_value_amount: u64,
+        min_lst_amount: u64,
+    ) -> Result<()> {
+        users::ticket_claim::handle_ticket_claim(ctx, withdraw_sol_value_amount, min_lst_amount)
+    }
+
+    pub fn ticket_claim_sol(
+        ctx: Context<TicketClaimSol>,
+        withdraw_sol_value_amount: u64,
+    ) -> Result<()> {
+        users::ticket_claim::handle_ticket_claim_sol(ctx, withdraw_sol_value_amount)
+    }
+
+    pub fn ticket_claim_nft(
+        ctx: Context<TicketClaimNft>,
+        withdraw_sol_value_amount: u64,
+        min_lst_amount: u64,
+    ) -> Result<()> {
+        users::ticket_nft::handle_ticket_claim_nft(ctx, withdraw_sol_value_amount, min_lst_amount)
+    }
+
+    pub fn ticket_claim_multi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TicketClaimMulti<'info>>,
+        sol_value_amounts: Vec<u64>,
+        min_lst_amounts: Vec<u64>,
+    ) -> Result<()> {
+        users::ticket_claim_multi::handle_ticket_claim_multi(ctx, sol_value_amounts, min_lst_amounts)
+    }
+
+    pub fn cancel_ticket(ctx: Context<CancelTicket>) -> Result<()> {
+        users::cancel_ticket::handle_cancel_ticket(ctx)
+    }
+
+    pub fn transfer_ticket(ctx: Context<TransferTicket>, new_beneficiary: Pubkey) -> Result<()> {
+        users::transfer_ticket::handle_transfer_ticket(ctx, new_beneficiary)
+    }
+
+    pub fn split_ticket(ctx: Context<SplitTicket>, sol_value_amount: u64) -> Result<()> {
+        users::transfer_ticket::handle_split_ticket(ctx, sol_value_amount)
+    }
+
+    pub fn unstake_instant(
+        ctx: Context<UnstakeInstant>,
+        mpsol_amount: u64,
+        min_lst_amount: u64,
+    ) -> Result<()> {
+        users::unstake_instant::handle_unstake_instant(ctx, mpsol_amount, min_lst_amount)
     }
 }
@@ -0,0 +1,69 @@
+//! LST mints can be owned by the Token or by the Token-2022 program.
+//! Secondary vaults move LSTs with `transfer_checked` through the token interface,
+//! and only list Token-2022 mints whose extensions keep the vault custody intact
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig,
+    transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+
+/// transfer-hook programs accepted in LST mints.
+/// Note: the vault transfers do not forward the hook extra accounts yet,
+/// a program can only be listed here once the transfer CPIs support it
+pub const TRANSFER_HOOK_PROGRAM_ALLOW_LIST: [Pubkey; 0] = [];
+
+/// rejects Token-2022 mints with extensions that break the custody of the vault tokens:
+/// a permanent delegate can move the vault tokens, non-transferable tokens can not leave the vault,
+/// and a transfer hook runs unknown code (or fails) on every vault transfer
+pub fn check_lst_mint_extensions(lst_mint: &AccountInfo) -> Result<()> {
+    if *lst_mint.owner != spl_token_2022::ID {
+        // classic Token program, no extensions
+        return Ok(());
+    }
+    let data = lst_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension_type in mint.get_extension_types()? {
+        match extension_type {
+            ExtensionType::PermanentDelegate => {
+                let permanent_delegate = mint.get_extension::<PermanentDelegate>()?;
+                require!(
+                    Option::<Pubkey>::from(permanent_delegate.delegate).is_none(),
+                    ErrorCode::LstMintHasPermanentDelegate
+                );
+            }
+            ExtensionType::NonTransferable => {
+                return err!(ErrorCode::LstMintIsNonTransferable);
+            }
+            ExtensionType::TransferHook => {
+                let transfer_hook = mint.get_extension::<TransferHook>()?;
+                if let Some(program_id) = Option::<Pubkey>::from(transfer_hook.program_id) {
+                    require!(
+                        TRANSFER_HOOK_PROGRAM_ALLOW_LIST.contains(&program_id),
+                        ErrorCode::LstMintTransferHookNotAllowed
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// amount received by the destination when transferring `amount` of `lst_mint`:
+/// a Token-2022 transfer fee (current epoch) is withheld in the destination account
+pub fn lst_amount_after_transfer_fee(lst_mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *lst_mint.owner != spl_token_2022::ID {
+        return Ok(amount);
+    }
+    let data = lst_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let fee = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ErrorCode::TransferFeeCalculationFailed)?,
+        Err(_) => 0,
+    };
+    Ok(amount - fee)
+}
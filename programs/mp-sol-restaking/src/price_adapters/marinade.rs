@@ -0,0 +1,93 @@
+use crate::error::ErrorCode;
+use crate::price_adapters::{LstPriceAdapter, LstSolPrice, PriceEpochPolicy, PriceSourceKind};
+use crate::state::external::marinade_pool_state::{MarinadeState, MARINADE_STATE_ADDRESS};
+use anchor_lang::prelude::*;
+
+use ::borsh::BorshDeserialize;
+
+pub struct MarinadePriceAdapter;
+
+impl LstPriceAdapter for MarinadePriceAdapter {
+    fn kind(&self) -> PriceSourceKind {
+        PriceSourceKind::Marinade
+    }
+
+    fn lst_sol_price(
+        &self,
+        lst_mint: &Pubkey,
+        _lst_decimals: u8,
+        lst_state: Option<&AccountInfo>,
+    ) -> Result<LstSolPrice> {
+        let lst_state = lst_state.ok_or(error!(ErrorCode::MissingLstStateInRemainingAccounts))?;
+        // marinade state address is known, verify
+        require_keys_eq!(
+            *lst_state.key,
+            MARINADE_STATE_ADDRESS,
+            ErrorCode::IncorrectMarinadeStateAddress
+        );
+        // try deserialize
+        let mut data_slice = &lst_state.data.borrow()[..];
+        let marinade_state: MarinadeState = MarinadeState::deserialize(&mut data_slice)?;
+        require_keys_eq!(
+            marinade_state.msol_mint,
+            *lst_mint,
+            ErrorCode::InvalidPriceSourceLstMint
+        );
+        // compute true price = total_lamports / pool_token_supply
+        // https://docs.marinade.finance/marinade-protocol/system-overview/msol-token#msol-price
+        // marinade already uses 32-bit precision price, mSOL has 9 decimals like SOL
+        Ok(LstSolPrice {
+            price_p32: marinade_state.msol_price,
+            // Marinade does not store the epoch of its last price update, and no other field
+            // tells it: msol_price is updated per stake account during the epoch.
+            // Marinade prices are never outdated for the vault epoch policy,
+            // they are only limited by the vault max price age and circuit breaker
+            update_epoch: None,
+        })
+    }
+
+    fn default_price_epoch_policy(&self) -> PriceEpochPolicy {
+        PriceEpochPolicy::Ignore
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::external::marinade_pool_state::{MARINADE_MSOL_MINT, MARINADE_POOL_PROGRAM};
+    use shared_lib::TWO_POW_32;
+
+    #[test]
+    fn marinade_prices_are_never_outdated() {
+        let mut marinade_state = MarinadeState::deserialize(&mut &[0u8; 1024][..]).unwrap();
+        marinade_state.msol_mint = MARINADE_MSOL_MINT;
+        marinade_state.msol_price = TWO_POW_32 + 1;
+        // a stake-delta epochs ago says nothing about the msol_price epoch
+        marinade_state.stake_system.last_stake_delta_epoch = 10;
+        let mut data = ::borsh::to_vec(&marinade_state).unwrap();
+        let mut lamports = 0;
+        let account_info = AccountInfo::new(
+            &MARINADE_STATE_ADDRESS,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &MARINADE_POOL_PROGRAM,
+            false,
+            0,
+        );
+
+        let price = MarinadePriceAdapter
+            .lst_sol_price(&MARINADE_MSOL_MINT, 9, Some(&account_info))
+            .unwrap();
+        assert_eq!(price.price_p32, TWO_POW_32 + 1);
+        assert_eq!(price.update_epoch, None);
+        assert!(!price.is_outdated(10));
+        assert!(!price.is_outdated(11));
+        assert!(!price.is_outdated(u64::MAX));
+        assert_eq!(
+            MarinadePriceAdapter.default_price_epoch_policy(),
+            PriceEpochPolicy::Ignore
+        );
+    }
+}
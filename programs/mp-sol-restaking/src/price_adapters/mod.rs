@@ -0,0 +1,210 @@
+pub mod marinade;
+pub mod spl_stake_pool;
+pub mod wsol;
+
+use crate::error::ErrorCode;
+use crate::price_adapters::marinade::MarinadePriceAdapter;
+use crate::price_adapters::spl_stake_pool::SplStakePoolPriceAdapter;
+use crate::price_adapters::wsol::WsolPriceAdapter;
+use crate::state::external::marinade_pool_state::MARINADE_MSOL_MINT;
+use crate::{SecondaryVaultState, WSOL_MINT};
+use anchor_lang::prelude::borsh;
+use anchor_lang::prelude::*;
+
+/// where the LST/SOL price of a secondary vault is read from.
+/// Stored in the vault at `create_secondary_vault`, the byte value is part of the account layout
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PriceSourceKind {
+    /// vaults created before price sources were stored, the kind is inferred from the lst mint
+    Unset,
+    /// wSOL, price is always 1
+    Wsol,
+    /// Marinade mSOL, price read from the Marinade state
+    Marinade,
+    /// SPL stake-pool or Sanctum stake-pool deployments, price read from the stake-pool state
+    SplStakePool,
+}
+
+impl PriceSourceKind {
+    /// kind used to read the price, `Unset` is resolved from the lst mint as v1.0 did
+    pub fn resolve(self, lst_mint: &Pubkey) -> PriceSourceKind {
+        match self {
+            PriceSourceKind::Unset => match *lst_mint {
+                WSOL_MINT => PriceSourceKind::Wsol,
+                MARINADE_MSOL_MINT => PriceSourceKind::Marinade,
+                _ => PriceSourceKind::SplStakePool,
+            },
+            kind => kind,
+        }
+    }
+}
+
+/// per-vault policy for prices read from a source not updated in the current epoch.
+/// The byte value is part of the account layout, zero (vaults created before v1.1) is `Reject`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PriceEpochPolicy {
+    /// reading the price fails until the source is updated
+    Reject,
+    /// the price is stored and marked outdated, stake & claims fail until a current price is read
+    MarkOutdated,
+    /// no epoch check, for price sources that do not track epochs
+    Ignore,
+}
+
+/// a price read from a price source
+#[derive(Clone, Copy, Debug)]
+pub struct LstSolPrice {
+    /// LST/SOL price with 32-bit precision, SOL price of one whole LST token (not of 1 base unit)
+    pub price_p32: u64,
+    /// epoch the source price is current for,
+    /// None if the source does not tell it (wSOL, Marinade): the price is never outdated
+    pub update_epoch: Option<u64>,
+}
+
+impl LstSolPrice {
+    /// true if the source was not updated in `current_epoch`
+    pub fn is_outdated(&self, current_epoch: u64) -> bool {
+        self.update_epoch
+            .is_some_and(|update_epoch| update_epoch < current_epoch)
+    }
+}
+
+/// reads the LST/SOL price of one LST family.
+/// To support a new family: add a `PriceSourceKind` variant, implement this trait
+/// and add the adapter to `PRICE_ADAPTERS`
+pub trait LstPriceAdapter {
+    fn kind(&self) -> PriceSourceKind;
+
+    /// false if the price is known without reading a state account
+    fn requires_state_account(&self) -> bool {
+        true
+    }
+
+    /// epoch policy of new vaults. Sources with no `update_epoch` default to `Ignore`:
+    /// the other policies need the epoch of the source price
+    fn default_price_epoch_policy(&self) -> PriceEpochPolicy {
+        PriceEpochPolicy::Reject
+    }
+
+    /// LST/SOL price and the epoch it was updated in.
+    /// `lst_state` is Some if `requires_state_account()`, and it is already checked
+    /// to be the price source state of the vault
+    fn lst_sol_price(
+        &self,
+        lst_mint: &Pubkey,
+        lst_decimals: u8,
+        lst_state: Option<&AccountInfo>,
+    ) -> Result<LstSolPrice>;
+}
+
+pub const PRICE_ADAPTERS: [&dyn LstPriceAdapter; 3] = [
+    &WsolPriceAdapter,
+    &MarinadePriceAdapter,
+    &SplStakePoolPriceAdapter,
+];
+
+pub fn price_adapter(kind: PriceSourceKind) -> Result<&'static dyn LstPriceAdapter> {
+    PRICE_ADAPTERS
+        .into_iter()
+        .find(|adapter| adapter.kind() == kind)
+        .ok_or(error!(ErrorCode::InvalidPriceSourceKind))
+}
+
+/// reads the price with the adapter for `kind`,
+/// enforcing `lst_state` is the account stored as `price_source_state`
+fn read_lst_sol_price(
+    lst_mint: &Pubkey,
+    lst_decimals: u8,
+    kind: PriceSourceKind,
+    price_source_state: &Pubkey,
+    lst_state: Option<&AccountInfo>,
+) -> Result<LstSolPrice> {
+    let adapter = price_adapter(kind.resolve(lst_mint))?;
+    if !adapter.requires_state_account() {
+        return adapter.lst_sol_price(lst_mint, lst_decimals, None);
+    }
+    let lst_state = lst_state.ok_or(error!(ErrorCode::MissingLstStateInRemainingAccounts))?;
+    // Unset (v1.0) vaults have no stored state address, the adapter checks are the only ones
+    if kind != PriceSourceKind::Unset {
+        require_keys_eq!(
+            *lst_state.key,
+            *price_source_state,
+            ErrorCode::InvalidPriceSourceState
+        );
+    }
+    adapter.lst_sol_price(lst_mint, lst_decimals, Some(lst_state))
+}
+
+/// current LST/SOL price of the vault, read from its price source
+pub fn vault_lst_sol_price(
+    vault: &SecondaryVaultState,
+    lst_state: Option<&AccountInfo>,
+) -> Result<LstSolPrice> {
+    read_lst_sol_price(
+        &vault.lst_mint,
+        vault.lst_decimals(),
+        vault.price_source_kind,
+        &vault.price_source_state,
+        lst_state,
+    )
+}
+
+/// true if the price source of the vault needs a state account in remaining_accounts[0]
+pub fn vault_requires_lst_state(vault: &SecondaryVaultState) -> Result<bool> {
+    Ok(price_adapter(vault.price_source_kind.resolve(&vault.lst_mint))?.requires_state_account())
+}
+
+/// validates a price source before storing it in a vault:
+/// the kind is known, the state address is set only if required, and a price can be read from it.
+/// The price epoch is not checked here, it is checked by the vault `PriceEpochPolicy` on every price update
+pub fn check_price_source(
+    lst_mint: &Pubkey,
+    lst_decimals: u8,
+    kind: PriceSourceKind,
+    price_source_state: &Pubkey,
+    lst_state: Option<&AccountInfo>,
+) -> Result<()> {
+    require!(
+        kind != PriceSourceKind::Unset,
+        ErrorCode::InvalidPriceSourceKind
+    );
+    if !price_adapter(kind)?.requires_state_account() {
+        require_keys_eq!(
+            *price_source_state,
+            Pubkey::default(),
+            ErrorCode::InvalidPriceSourceState
+        );
+    }
+    read_lst_sol_price(lst_mint, lst_decimals, kind, price_source_state, lst_state)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_are_outdated_from_the_next_epoch() {
+        let price = LstSolPrice {
+            price_p32: 1,
+            update_epoch: Some(600),
+        };
+        assert!(!price.is_outdated(599));
+        assert!(!price.is_outdated(600));
+        assert!(price.is_outdated(601));
+    }
+
+    #[test]
+    fn default_price_epoch_policies() {
+        for (kind, policy) in [
+            (PriceSourceKind::Wsol, PriceEpochPolicy::Ignore),
+            (PriceSourceKind::Marinade, PriceEpochPolicy::Ignore),
+            (PriceSourceKind::SplStakePool, PriceEpochPolicy::Reject),
+        ] {
+            assert_eq!(
+                price_adapter(kind).unwrap().default_price_epoch_policy(),
+                policy
+            );
+        }
+    }
+}
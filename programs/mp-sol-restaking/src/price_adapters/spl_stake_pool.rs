@@ -0,0 +1,60 @@
+use crate::error::ErrorCode;
+use crate::price_adapters::{LstPriceAdapter, LstSolPrice, PriceSourceKind};
+use crate::state::external::spl_stake_pool_state::{
+    AccountType, SplStakePoolState, SPL_STAKE_POOL_PROGRAM,
+};
+use crate::{SANCTUM_SPL_1, SANCTUM_SPL_2};
+use anchor_lang::prelude::*;
+use shared_lib::backing_lamports_to_lst_sol_price_p32;
+
+use ::borsh::BorshDeserialize;
+
+pub struct SplStakePoolPriceAdapter;
+
+impl LstPriceAdapter for SplStakePoolPriceAdapter {
+    fn kind(&self) -> PriceSourceKind {
+        PriceSourceKind::SplStakePool
+    }
+
+    fn lst_sol_price(
+        &self,
+        lst_mint: &Pubkey,
+        lst_decimals: u8,
+        lst_state: Option<&AccountInfo>,
+    ) -> Result<LstSolPrice> {
+        let lst_state = lst_state.ok_or(error!(ErrorCode::MissingLstStateInRemainingAccounts))?;
+
+        // lst_state.owner must be one of: SPL_STAKE_POOL_PROGRAM, SANCTUM_SPL_1 or SANCTUM_SPL_2
+        let program_id = lst_state.owner.key();
+        require!(
+            program_id == SPL_STAKE_POOL_PROGRAM
+                || program_id == SANCTUM_SPL_1
+                || program_id == SANCTUM_SPL_2,
+            ErrorCode::SplStakePoolStateAccountOwnerIsNotTheSplStakePoolProgram
+        );
+
+        // try deserialize
+        let mut data_slice = &lst_state.data.borrow()[..];
+        let spl_stake_pool_state: SplStakePoolState =
+            SplStakePoolState::deserialize(&mut data_slice)?;
+        // verify mint
+        require_keys_eq!(spl_stake_pool_state.pool_mint, *lst_mint);
+        // verify type
+        require!(
+            spl_stake_pool_state.account_type == AccountType::StakePool,
+            ErrorCode::AccountTypeIsNotStakePool
+        );
+        // compute true price = total_lamports / pool_token_supply
+        // with 32-bit precision, pool_token_supply is measured with the pool mint decimals
+        Ok(LstSolPrice {
+            price_p32: backing_lamports_to_lst_sol_price_p32(
+                spl_stake_pool_state.total_lamports,
+                spl_stake_pool_state.pool_token_supply,
+                lst_decimals,
+            )
+            .ok_or(ErrorCode::AmountConversionOverflow)?,
+            // total_lamports is updated by UpdateStakePoolBalance, once per epoch
+            update_epoch: Some(spl_stake_pool_state.last_update_epoch),
+        })
+    }
+}
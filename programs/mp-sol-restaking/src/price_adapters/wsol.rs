@@ -0,0 +1,35 @@
+use crate::error::ErrorCode;
+use crate::price_adapters::{LstPriceAdapter, LstSolPrice, PriceEpochPolicy, PriceSourceKind};
+use crate::WSOL_MINT;
+use anchor_lang::prelude::*;
+use shared_lib::TWO_POW_32;
+
+pub struct WsolPriceAdapter;
+
+impl LstPriceAdapter for WsolPriceAdapter {
+    fn kind(&self) -> PriceSourceKind {
+        PriceSourceKind::Wsol
+    }
+
+    fn requires_state_account(&self) -> bool {
+        false
+    }
+
+    fn lst_sol_price(
+        &self,
+        lst_mint: &Pubkey,
+        _lst_decimals: u8,
+        _lst_state: Option<&AccountInfo>,
+    ) -> Result<LstSolPrice> {
+        // wSol is simple, always 1
+        require_keys_eq!(*lst_mint, WSOL_MINT, ErrorCode::InvalidPriceSourceLstMint);
+        Ok(LstSolPrice {
+            price_p32: TWO_POW_32,
+            update_epoch: None,
+        })
+    }
+
+    fn default_price_epoch_policy(&self) -> PriceEpochPolicy {
+        PriceEpochPolicy::Ignore
+    }
+}
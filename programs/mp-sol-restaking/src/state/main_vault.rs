@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
 
+use crate::error::ErrorCode;
+use crate::events::ConfigParam;
+
+/// the list of secondary vaults is stored in VaultRegistry PDA(main_state, VAULT_REGISTRY_SEED)
 #[account]
 #[derive(InitSpace)]
 pub struct MainVaultState {
@@ -10,8 +14,14 @@ pub struct MainVaultState {
     pub operator_auth: Pubkey,
     /// authority to move tokens in or out strategies, normally a DAO-authorized bot acting on votes
     pub withdraw_fee_bp: u16,
+    /// global emergency pause: blocks stake, unstake & transfer_lst_to_strat
+    pub paused: bool,
+    /// set together with `paused` if requested: also blocks ticket_claim
+    pub ticket_claims_paused: bool,
+    /// fee for `unstake_instant`, paid in mpSOL to the treasury (kept in the vault if no treasury)
+    pub instant_unstake_fee_bp: u16,
     /// reserved space for extensions
-    pub _reserved_space: [u8; 30],
+    pub _reserved_space: [u8; 26],
 
     pub mpsol_mint: Pubkey,
     pub treasury_mpsol_account: Option<Pubkey>,
@@ -36,5 +46,78 @@ pub struct MainVaultState {
 
     // Config:
     /// normally 48: number of hours for a ticket to be due
+    /// MIN_UNSTAKE_TICKET_WAITING_HOURS..=MAX_UNSTAKE_TICKET_WAITING_HOURS
+    /// applies to new tickets only, issued tickets keep their `ticket_due_timestamp`
     pub unstake_ticket_waiting_hours: u16,
+
+    // --- fields added after v1.0, accounts created before must be extended with `realloc_main_state`
+    /// can pause/unpause (as the admin), for incident response without the DAO admin key
+    /// Pubkey::default() => not set, only the admin can pause
+    pub pause_authority: Pubkey,
+
+    /// two-step handover: proposed by the admin, becomes admin when it signs `accept_admin`
+    /// Pubkey::default() => no pending handover
+    pub pending_admin: Pubkey,
+    /// two-step handover: proposed by the admin, becomes operator_auth when it signs `accept_operator_auth`
+    /// Pubkey::default() => no pending handover
+    pub pending_operator_auth: Pubkey,
+
+    /// timelocked config increases: queued by the admin, applied by the permissionless `apply_pending_config`
+    /// once `effective_timestamp` is reached, so users can see and react to changes. None => no change queued
+    pub pending_withdraw_fee_bp: Option<PendingConfigValue>,
+    pub pending_performance_fee_bp: Option<PendingConfigValue>,
+    pub pending_unstake_ticket_waiting_hours: Option<PendingConfigValue>,
+    pub pending_instant_unstake_fee_bp: Option<PendingConfigValue>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug)]
+pub struct PendingConfigValue {
+    pub value: u16,
+    pub effective_timestamp: u64,
+}
+
+impl MainVaultState {
+    pub fn check_not_paused(&self) -> Result<()> {
+        require!(!self.paused, ErrorCode::ProgramPaused);
+        Ok(())
+    }
+
+    pub fn check_ticket_claims_not_paused(&self) -> Result<()> {
+        require!(!self.ticket_claims_paused, ErrorCode::ProgramPaused);
+        Ok(())
+    }
+
+    pub fn config_value(&self, param: ConfigParam) -> u16 {
+        match param {
+            ConfigParam::WithdrawFeeBp => self.withdraw_fee_bp,
+            ConfigParam::PerformanceFeeBp => self.performance_fee_bp,
+            ConfigParam::UnstakeTicketWaitingHours => self.unstake_ticket_waiting_hours,
+            ConfigParam::InstantUnstakeFeeBp => self.instant_unstake_fee_bp,
+        }
+    }
+
+    pub fn set_config_value(&mut self, param: ConfigParam, value: u16) {
+        match param {
+            ConfigParam::WithdrawFeeBp => self.withdraw_fee_bp = value,
+            ConfigParam::PerformanceFeeBp => self.performance_fee_bp = value,
+            ConfigParam::UnstakeTicketWaitingHours => self.unstake_ticket_waiting_hours = value,
+            ConfigParam::InstantUnstakeFeeBp => self.instant_unstake_fee_bp = value,
+        }
+    }
+
+    pub fn pending_config_mut(&mut self, param: ConfigParam) -> &mut Option<PendingConfigValue> {
+        match param {
+            ConfigParam::WithdrawFeeBp => &mut self.pending_withdraw_fee_bp,
+            ConfigParam::PerformanceFeeBp => &mut self.pending_performance_fee_bp,
+            ConfigParam::UnstakeTicketWaitingHours => {
+                &mut self.pending_unstake_ticket_waiting_hours
+            }
+            ConfigParam::InstantUnstakeFeeBp => &mut self.pending_instant_unstake_fee_bp,
+        }
+    }
+
+    pub fn is_pause_authority(&self, key: &Pubkey) -> bool {
+        *key == self.admin
+            || (self.pause_authority != Pubkey::default() && *key == self.pause_authority)
+    }
 }
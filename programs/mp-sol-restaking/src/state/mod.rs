@@ -2,9 +2,13 @@ pub mod external;
 pub mod main_vault;
 pub mod secondary_vault;
 pub mod unstake_ticket;
+pub mod user_ticket_index;
+pub mod vault_registry;
 pub mod vault_strategy_relation_entry;
 
 pub use main_vault::*;
 pub use secondary_vault::*;
 pub use unstake_ticket::*;
+pub use user_ticket_index::*;
+pub use vault_registry::*;
 pub use vault_strategy_relation_entry::*;
@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
-use shared_lib::{lst_amount_to_sol_value, sol_value_to_lst_amount};
+use shared_lib::{apply_bp, lst_amount_to_sol_value, sol_value_to_lst_amount, SOL_DECIMALS};
 
+use crate::constants::DEFAULT_MAX_PRICE_AGE_SECONDS;
 use crate::error::ErrorCode;
+use crate::price_adapters::{PriceEpochPolicy, PriceSourceKind};
 
 // Secondary-vault State
 #[account]
@@ -13,7 +15,8 @@ pub struct SecondaryVaultState {
     pub lst_mint: Pubkey,
 
     /// LST-token/SOL price with 32-bit precision, cache of last computation of LST-token/SOL price,
-    /// it is computed as `token_sol_price_p32 = LST-backing-lamports * 2^32 / LST-mint-supply`
+    /// it is computed as `token_sol_price_p32 = LST-backing-SOL * 2^32 / LST-mint-supply-in-whole-tokens`,
+    /// i.e. the SOL price of one whole LST token, regardless of the LST mint decimals
     /// it is used to compute vault.sol_value.
     /// To obtain a human-readable price do: human_readable_price = token_sol_price_p32 / 2^32
     /// invariant: token_sol_price_p32 >= 2^32, because the min value for 1 LST is 1 SOL
@@ -45,19 +48,145 @@ pub struct SecondaryVaultState {
     pub deposits_disabled: bool,
     /// 0 means no cap - measured in vault accepted tokens
     pub token_deposit_cap: u64,
+
+    // --- fields added after v1.0, vaults created before must be extended with `realloc_secondary_vault`
+    /// max lst amount paid by `unstake_instant` per epoch, 0 => instant unstake disabled in this vault
+    pub instant_unstake_cap_per_epoch: u64,
+    /// epoch of the last `unstake_instant`, `instant_unstaked_in_epoch` restarts when the epoch changes
+    pub instant_unstake_epoch: u64,
+    /// lst amount paid by `unstake_instant` during `instant_unstake_epoch`
+    pub instant_unstaked_in_epoch: u64,
+    /// LST family of this vault, selects the price adapter. `Unset` for vaults created before v1.1
+    pub price_source_kind: PriceSourceKind,
+    /// state account the price is read from, e.g. the stake-pool state. Default pubkey for wSOL
+    pub price_source_state: Pubkey,
+    /// decimals of `lst_mint`, 0 for vaults created before v1.1 (all of them have 9 decimals).
+    /// Read it with `lst_decimals()`
+    pub lst_mint_decimals: u8,
+    /// what to do with prices read from a source not updated in the current epoch
+    pub price_epoch_policy: PriceEpochPolicy,
+    /// true if the stored price was read from a source not updated in its epoch (`MarkOutdated` policy),
+    /// stake & claims are rejected until a current price is read
+    pub lst_sol_price_outdated: bool,
+    /// circuit breaker, max LST/SOL price increase vs `price_reference_p32`, 0 => no limit
+    pub max_price_increase_bp: u16,
+    /// circuit breaker, max LST/SOL price decrease vs `price_reference_p32`, 0 => no limit
+    pub max_price_decrease_bp: u16,
+    /// stored price at the start of `price_reference_epoch`, price changes are limited vs this price
+    pub price_reference_p32: u64,
+    pub price_reference_epoch: u64,
+    /// set when a price update exceeds the limits: the price is not applied,
+    /// stake & claims are rejected until the admin calls `acknowledge_vault_price`
+    pub price_frozen: bool,
+    /// last price read while frozen, applied by `acknowledge_vault_price`
+    pub pending_lst_sol_price_p32: u64,
+    /// when `pending_lst_sol_price_p32` was read, restored as `lst_sol_price_timestamp` on acknowledge:
+    /// an old read does not become a fresh price
+    pub pending_lst_sol_price_timestamp: u64,
+    /// epoch `pending_lst_sol_price_p32` was read in, restored as `price_reference_epoch` on acknowledge
+    pub pending_lst_sol_price_epoch: u64,
+    /// max seconds since `lst_sol_price_timestamp` for the stored price to be used,
+    /// 0 => DEFAULT_MAX_PRICE_AGE_SECONDS
+    pub max_price_age_seconds: u64,
 }
 
 impl SecondaryVaultState {
-    pub fn vault_total_sol_value(&self) -> u64 {
-        lst_amount_to_sol_value(self.vault_total_lst_amount, self.lst_sol_price_p32)
+    /// decimals of the lst amounts in this vault
+    pub fn lst_decimals(&self) -> u8 {
+        if self.lst_mint_decimals == 0 {
+            SOL_DECIMALS
+        } else {
+            self.lst_mint_decimals
+        }
+    }
+
+    /// sol-value of a lst amount of this vault, at the last stored price
+    pub fn lst_amount_to_sol_value(&self, lst_amount: u64) -> Result<u64> {
+        lst_amount_to_sol_value(lst_amount, self.lst_sol_price_p32, self.lst_decimals())
+            .ok_or(error!(ErrorCode::AmountConversionOverflow))
+    }
+
+    /// lst amount of this vault worth a sol-value, at the last stored price
+    pub fn sol_value_to_lst_amount(&self, sol_value: u64) -> Result<u64> {
+        sol_value_to_lst_amount(sol_value, self.lst_sol_price_p32, self.lst_decimals())
+            .ok_or(error!(ErrorCode::AmountConversionOverflow))
+    }
+
+    pub fn vault_total_sol_value(&self) -> Result<u64> {
+        self.lst_amount_to_sol_value(self.vault_total_lst_amount)
+    }
+
+    /// `locally_stored_amount` not reserved for tickets, the reserve is valued at the stored price
+    pub fn available_for_strategies_amount(&self) -> Result<u64> {
+        self.check_price_not_stale()?;
+        Ok(self
+            .locally_stored_amount
+            .saturating_sub(self.sol_value_to_lst_amount(self.tickets_target_sol_amount)?))
+    }
+
+    /// the stored price can be used to stake & claim
+    pub fn check_price_current(&self) -> Result<()> {
+        require!(!self.price_frozen, ErrorCode::VaultPriceFrozen);
+        self.check_price_not_stale()?;
+        require!(
+            !self.lst_sol_price_outdated,
+            ErrorCode::LstSolPriceIsOutdated
+        );
+        Ok(())
     }
 
-    pub fn available_for_strategies_amount(&self) -> u64 {
-        self.locally_stored_amount
-            .saturating_sub(sol_value_to_lst_amount(
-                self.tickets_target_sol_amount,
-                self.lst_sol_price_p32,
-            ))
+    pub fn max_price_age_seconds(&self) -> u64 {
+        if self.max_price_age_seconds == 0 {
+            DEFAULT_MAX_PRICE_AGE_SECONDS
+        } else {
+            self.max_price_age_seconds
+        }
+    }
+
+    /// the stored price was updated less than `max_price_age_seconds` ago.
+    /// Must be checked before any use of `lst_sol_price_p32`
+    pub fn check_price_not_stale(&self) -> Result<()> {
+        let now_ts = Clock::get()?.unix_timestamp as u64;
+        let elapsed_seconds = now_ts.saturating_sub(self.lst_sol_price_timestamp);
+        require_gte!(
+            self.max_price_age_seconds(),
+            elapsed_seconds,
+            ErrorCode::TokenSolPriceIsStale
+        );
+        Ok(())
+    }
+
+    /// the first price update of each epoch takes the stored price as reference
+    pub fn update_price_reference(&mut self, epoch: u64) {
+        if self.price_reference_epoch != epoch || self.price_reference_p32 == 0 {
+            self.price_reference_epoch = epoch;
+            self.price_reference_p32 = self.lst_sol_price_p32;
+        }
+    }
+
+    /// keeps a price read while the circuit breaker is tripped, with its read time
+    pub fn set_pending_price(&mut self, price_p32: u64, timestamp: u64, epoch: u64) {
+        self.pending_lst_sol_price_p32 = price_p32;
+        self.pending_lst_sol_price_timestamp = timestamp;
+        self.pending_lst_sol_price_epoch = epoch;
+    }
+
+    /// true if `new_price_p32` moved more than the circuit breaker limits allow in this epoch
+    pub fn price_change_exceeds_limits(&self, new_price_p32: u64) -> bool {
+        let reference_price_p32 = self.price_reference_p32;
+        if reference_price_p32 == 0 {
+            // first price of the vault
+            return false;
+        }
+        if new_price_p32 >= reference_price_p32 {
+            self.max_price_increase_bp > 0
+                && new_price_p32 - reference_price_p32
+                    > apply_bp(reference_price_p32, self.max_price_increase_bp)
+        } else {
+            self.max_price_decrease_bp > 0
+                && reference_price_p32 - new_price_p32
+                    > apply_bp(reference_price_p32, self.max_price_decrease_bp)
+        }
     }
 
     pub fn check_cap(&self) -> Result<()> {
@@ -71,6 +200,21 @@ impl SecondaryVaultState {
         Ok(())
     }
 
+    /// records an instant unstake of `lst_amount`, checking the per-epoch cap
+    pub fn add_instant_unstaked(&mut self, lst_amount: u64, epoch: u64) -> Result<()> {
+        if self.instant_unstake_epoch != epoch {
+            self.instant_unstake_epoch = epoch;
+            self.instant_unstaked_in_epoch = 0;
+        }
+        self.instant_unstaked_in_epoch += lst_amount;
+        require_gte!(
+            self.instant_unstake_cap_per_epoch,
+            self.instant_unstaked_in_epoch,
+            ErrorCode::InstantUnstakeExceedsVaultCap
+        );
+        Ok(())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.vault_total_lst_amount == 0
             && self.locally_stored_amount == 0
@@ -78,3 +222,96 @@ impl SecondaryVaultState {
             && self.tickets_target_sol_amount == 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::zeroed_account;
+    use shared_lib::TWO_POW_32;
+
+    /// a vault as stored before the `lst_mint_decimals` field was added: zero bytes
+    fn legacy_vault_state() -> SecondaryVaultState {
+        zeroed_account()
+    }
+
+    #[test]
+    fn legacy_vaults_have_9_decimals() {
+        let mut vault_state = legacy_vault_state();
+        vault_state.lst_sol_price_p32 = TWO_POW_32;
+        assert_eq!(vault_state.lst_decimals(), 9);
+        assert_eq!(
+            vault_state.lst_amount_to_sol_value(1_000_000_000).unwrap(),
+            1_000_000_000
+        );
+        assert_eq!(
+            vault_state.sol_value_to_lst_amount(1_000_000_000).unwrap(),
+            1_000_000_000
+        );
+    }
+
+    #[test]
+    fn lst_amounts_are_scaled_with_the_mint_decimals() {
+        let mut vault_state = legacy_vault_state();
+        vault_state.lst_sol_price_p32 = TWO_POW_32;
+        for (lst_mint_decimals, one_lst) in [(6, 1_000_000), (12, 1_000_000_000_000)] {
+            vault_state.lst_mint_decimals = lst_mint_decimals;
+            assert_eq!(vault_state.lst_decimals(), lst_mint_decimals);
+            assert_eq!(
+                vault_state.lst_amount_to_sol_value(one_lst).unwrap(),
+                1_000_000_000
+            );
+            assert_eq!(
+                vault_state.sol_value_to_lst_amount(1_000_000_000).unwrap(),
+                one_lst
+            );
+        }
+    }
+
+    fn vault_with_price_limits(increase_bp: u16, decrease_bp: u16) -> SecondaryVaultState {
+        let mut vault_state = legacy_vault_state();
+        vault_state.price_reference_p32 = TWO_POW_32;
+        vault_state.max_price_increase_bp = increase_bp;
+        vault_state.max_price_decrease_bp = decrease_bp;
+        vault_state
+    }
+
+    #[test]
+    fn price_increase_is_limited_at_the_bp_boundary() {
+        // 1% = 2^32 / 100 = 42_949_672.96 => 42_949_672
+        let vault_state = vault_with_price_limits(100, 0);
+        let limit = apply_bp(TWO_POW_32, 100);
+        assert_eq!(limit, 42_949_672);
+        assert!(!vault_state.price_change_exceeds_limits(TWO_POW_32));
+        assert!(!vault_state.price_change_exceeds_limits(TWO_POW_32 + limit));
+        assert!(vault_state.price_change_exceeds_limits(TWO_POW_32 + limit + 1));
+        // no decrease limit
+        assert!(!vault_state.price_change_exceeds_limits(TWO_POW_32 / 2));
+    }
+
+    #[test]
+    fn price_decrease_is_limited_at_the_bp_boundary() {
+        let vault_state = vault_with_price_limits(0, 250);
+        let limit = apply_bp(TWO_POW_32, 250);
+        assert!(!vault_state.price_change_exceeds_limits(TWO_POW_32 - limit));
+        assert!(vault_state.price_change_exceeds_limits(TWO_POW_32 - limit - 1));
+        assert!(vault_state.price_change_exceeds_limits(0));
+        // no increase limit
+        assert!(!vault_state.price_change_exceeds_limits(TWO_POW_32 * 2));
+    }
+
+    #[test]
+    fn zero_limits_never_trip() {
+        let vault_state = vault_with_price_limits(0, 0);
+        assert!(!vault_state.price_change_exceeds_limits(0));
+        assert!(!vault_state.price_change_exceeds_limits(1));
+        assert!(!vault_state.price_change_exceeds_limits(u64::MAX));
+    }
+
+    #[test]
+    fn the_first_price_has_no_reference() {
+        let mut vault_state = vault_with_price_limits(1, 1);
+        vault_state.price_reference_p32 = 0;
+        assert!(!vault_state.price_change_exceeds_limits(TWO_POW_32 * 2));
+        assert!(!vault_state.price_change_exceeds_limits(1));
+    }
+}
@@ -1,13 +1,38 @@
+use crate::error::ErrorCode;
 use anchor_lang::prelude::*;
+use shared_lib::mul_div;
 
 #[account]
 #[derive(InitSpace)]
 pub struct UnstakeTicket {
     pub main_state: Pubkey,
     /// auth that can withdraw the LSTs when due
+    /// for tokenized tickets (`tokenize_ticket`) it is the ticket NFT mint, claimed by the NFT holder
     pub beneficiary: Pubkey,
     /// amount (lamports) this ticket is worth (set at unstake) -- can be updated on partial ticket withdraws
     pub ticket_sol_value: u64,
     /// when this ticket is due (unix timestamp)
     pub ticket_due_timestamp: u64,
+
+    // --- fields added after v1.0, tickets created before must be extended with `realloc_unstake_ticket`
+    /// mpSOL burned at unstake for this ticket (net of the withdrawal fee),
+    /// upper bound of the mpSOL `cancel_ticket` can mint back. Reduced pro-rata on partial claims & splits.
+    /// 0 for tickets created before v1.1 (can not be cancelled)
+    pub mpsol_amount: u64,
+}
+
+impl UnstakeTicket {
+    /// removes `sol_value_amount` from the ticket, with its pro-rata part of `mpsol_amount`
+    /// returns the mpSOL amount removed
+    pub fn debit_sol_value(&mut self, sol_value_amount: u64) -> Result<u64> {
+        let mpsol_amount = if sol_value_amount >= self.ticket_sol_value {
+            self.mpsol_amount
+        } else {
+            mul_div(self.mpsol_amount, sol_value_amount, self.ticket_sol_value)
+                .ok_or(ErrorCode::AmountConversionOverflow)?
+        };
+        self.ticket_sol_value -= sol_value_amount;
+        self.mpsol_amount -= mpsol_amount;
+        Ok(mpsol_amount)
+    }
 }
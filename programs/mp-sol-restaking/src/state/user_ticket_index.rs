@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey::Pubkey;
+
+/// per-user counter of the PDA unstake-tickets,
+/// created by `unstake_pda_ticket`, `split_ticket` & `transfer_ticket` (for the new beneficiary)
+/// address is PDA(main_state, USER_TICKET_INDEX_SEED, beneficiary)
+/// ticket `seq` address is PDA(main_state, TICKET_SEED, beneficiary, seq as u64 LE bytes)
+/// so all the tickets of a user are in [0..next_ticket_seq), closed tickets don't exist anymore
+#[account]
+#[derive(InitSpace)]
+pub struct UserTicketIndex {
+    pub main_state: Pubkey,
+    pub beneficiary: Pubkey,
+    /// sequence number of the next ticket, also the count of tickets created
+    pub next_ticket_seq: u64,
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey::Pubkey;
+
+use crate::constants::MAX_WHITELISTED_VAULTS;
+use crate::error::ErrorCode;
+
+/// on-chain list of the secondary vaults of a main_state
+/// vault-registry address is PDA(main_state, VAULT_REGISTRY_SEED)
+/// the account grows (realloc) by one Pubkey each time a vault is registered
+#[account]
+pub struct VaultRegistry {
+    pub main_state: Pubkey,
+    /// secondary-vault-state addresses, the position is the vault index
+    /// vaults are never removed, so an index always refers to the same vault
+    pub vaults: Vec<Pubkey>,
+}
+
+impl VaultRegistry {
+    /// account size (including discriminator) for `vaults_count` vaults
+    pub fn space(vaults_count: usize) -> usize {
+        8 + 32 + 4 + 32 * vaults_count
+    }
+
+    pub fn add_vault(&mut self, vault_state: Pubkey) -> Result<()> {
+        require_gt!(
+            MAX_WHITELISTED_VAULTS as usize,
+            self.vaults.len(),
+            ErrorCode::MaxWhitelistedVaultsReached
+        );
+        require!(
+            !self.vaults.contains(&vault_state),
+            ErrorCode::VaultAlreadyRegistered
+        );
+        self.vaults.push(vault_state);
+        Ok(())
+    }
+
+    /// verify `vault_state` is the vault registered at `index`
+    pub fn check_vault_at_index(&self, index: usize, vault_state: &Pubkey) -> Result<()> {
+        let registered = self.vaults.get(index).ok_or(ErrorCode::InvalidVaultIndex)?;
+        require_keys_eq!(
+            *registered,
+            *vault_state,
+            ErrorCode::VaultIndexHasDifferentVault
+        );
+        Ok(())
+    }
+}
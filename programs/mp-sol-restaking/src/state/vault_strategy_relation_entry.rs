@@ -38,4 +38,10 @@ pub struct VaultStrategyRelationEntry {
     /// Incremented during strategy-amount-update, if the strategy generated yield in the form of more lst tokens
     pub last_read_strat_lst_amount: u64,
     pub last_read_strat_lst_timestamp: u64, // last run of strat-price-update
+
+    // --- fields added after v1.0, entries created before must be extended with `realloc_vault_strategy_relation_entry`
+    /// lst amount lost by the strategy and not recovered yet.
+    /// The performance-fee high-water mark is `last_read_strat_lst_amount + unrecovered_loss_lst_amount`:
+    /// profits recovering a loss are not charged the fee, only profits above the mark
+    pub unrecovered_loss_lst_amount: u64,
 }
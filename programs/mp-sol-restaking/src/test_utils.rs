@@ -0,0 +1,9 @@
+use anchor_lang::{AccountDeserialize, Discriminator, Space};
+
+/// an account as created by the program with all its fields at zero,
+/// also the defaults of the fields added after v1.0 in a reallocated account
+pub fn zeroed_account<T: AccountDeserialize + Discriminator + Space>() -> T {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    data.resize(8 + T::INIT_SPACE, 0);
+    T::try_deserialize(&mut data.as_slice()).unwrap()
+}
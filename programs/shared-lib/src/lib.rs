@@ -2,8 +2,10 @@ pub const TWO_POW_32: u64 = 0x1_0000_0000; // 32-bit price precision, to store a
 
 pub const BASIS_POINTS_100_PERCENT: u16 = 10_000;
 
-pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> u64 {
-    u64::try_from((amount as u128) * (numerator as u128) / (denominator as u128)).unwrap()
+/// amount * numerator / denominator, rounded down.
+/// None if the denominator is zero or the result does not fit in an u64
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    mul_div_u128(amount, numerator as u128, denominator as u128)
 }
 
 /// convert a sol-value into a mpsol-amount,
@@ -15,9 +17,9 @@ pub fn sol_value_to_mpsol_amount(
     sol_value: u64,
     main_vault_backing_sol_value: u64,
     mpsol_current_supply: u64,
-) -> u64 {
+) -> Option<u64> {
     if mpsol_current_supply == 0 {
-        sol_value
+        Some(sol_value)
     } else {
         mul_div(
             sol_value,
@@ -35,24 +37,225 @@ pub fn mpsol_amount_to_sol_value(
     mpsol_amount: u64,
     mpsol_backing_sol_value: u64,
     mpsol_current_supply: u64,
-) -> u64 {
+) -> Option<u64> {
     mul_div(mpsol_amount, mpsol_backing_sol_value, mpsol_current_supply)
 }
 
+/// SOL decimals, sol-values are always lamports
+pub const SOL_DECIMALS: u8 = 9;
+
+/// multiplier and divisor to express a lst-amount with `lst_decimals` decimals
+/// as an amount with the 9 decimals of SOL
+fn lst_to_sol_decimals_scale(lst_decimals: u8) -> (u128, u128) {
+    if lst_decimals <= SOL_DECIMALS {
+        (10u128.pow((SOL_DECIMALS - lst_decimals) as u32), 1)
+    } else {
+        (1, 10u128.pow((lst_decimals - SOL_DECIMALS) as u32))
+    }
+}
+
+fn mul_div_u128(amount: u64, numerator: u128, denominator: u128) -> Option<u64> {
+    let result = (amount as u128)
+        .checked_mul(numerator)?
+        .checked_div(denominator)?;
+    u64::try_from(result).ok()
+}
+
 /// convert a lst-token-amount into a sol-value, using a 32-bit precision price
-/// considering token_sol_price_p32 is LST/SOL in an u64 with 32-bit precision
-/// sol-value = token-amount * token-sol-price
-pub fn lst_amount_to_sol_value(lst_amount: u64, lst_sol_price_p32: u64) -> u64 {
-    mul_div(lst_amount, lst_sol_price_p32, TWO_POW_32)
+/// considering token_sol_price_p32 is the SOL price of one whole LST token in an u64 with 32-bit precision
+/// sol-value = token-amount * token-sol-price, scaled from `lst_decimals` to the 9 decimals of SOL
+/// None if the sol-value does not fit in an u64
+pub fn lst_amount_to_sol_value(
+    lst_amount: u64,
+    lst_sol_price_p32: u64,
+    lst_decimals: u8,
+) -> Option<u64> {
+    let (scale_mul, scale_div) = lst_to_sol_decimals_scale(lst_decimals);
+    mul_div_u128(
+        lst_amount,
+        lst_sol_price_p32 as u128 * scale_mul,
+        TWO_POW_32 as u128 * scale_div,
+    )
 }
 
 /// convert a sol-value in a lst-token-amount, using a 32-bit precision price
-/// considering token_sol_price_p32 is LST/SOL in an u64 with 32-bit precision
-/// token-amount = sol-value / token-sol-price
-pub fn sol_value_to_lst_amount(sol_value: u64, lst_sol_price_p32: u64) -> u64 {
-    mul_div(sol_value, TWO_POW_32, lst_sol_price_p32)
+/// considering token_sol_price_p32 is the SOL price of one whole LST token in an u64 with 32-bit precision
+/// token-amount = sol-value / token-sol-price, scaled from the 9 decimals of SOL to `lst_decimals`
+/// None if the price is zero or the token-amount does not fit in an u64
+pub fn sol_value_to_lst_amount(
+    sol_value: u64,
+    lst_sol_price_p32: u64,
+    lst_decimals: u8,
+) -> Option<u64> {
+    let (scale_mul, scale_div) = lst_to_sol_decimals_scale(lst_decimals);
+    mul_div_u128(
+        sol_value,
+        TWO_POW_32 as u128 * scale_div,
+        lst_sol_price_p32 as u128 * scale_mul,
+    )
+}
+
+/// LST/SOL price with 32-bit precision of one whole LST token,
+/// from the lamports backing the LST and the LST supply (measured with `lst_decimals`)
+/// None if the supply is zero or the price does not fit in an u64
+pub fn backing_lamports_to_lst_sol_price_p32(
+    backing_lamports: u64,
+    lst_supply: u64,
+    lst_decimals: u8,
+) -> Option<u64> {
+    let (scale_mul, scale_div) = lst_to_sol_decimals_scale(lst_decimals);
+    mul_div_u128(
+        backing_lamports,
+        TWO_POW_32 as u128 * scale_div,
+        lst_supply as u128 * scale_mul,
+    )
 }
-// apply basis points to an amount
+
+// apply basis points to an amount, bp above 100% are capped so the result is never above `amount`
 pub fn apply_bp(amount: u64, bp: u16) -> u64 {
-    mul_div(amount, bp as u64, BASIS_POINTS_100_PERCENT as u64)
+    let bp = bp.min(BASIS_POINTS_100_PERCENT);
+    ((amount as u128) * (bp as u128) / (BASIS_POINTS_100_PERCENT as u128)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+    /// 1 LST = 1.25 SOL
+    const PRICE_1_25_P32: u64 = TWO_POW_32 + TWO_POW_32 / 4;
+
+    #[test]
+    fn nine_decimals_lst_is_not_scaled() {
+        assert_eq!(
+            lst_amount_to_sol_value(ONE_SOL, TWO_POW_32, 9),
+            Some(ONE_SOL)
+        );
+        assert_eq!(
+            lst_amount_to_sol_value(4 * ONE_SOL, PRICE_1_25_P32, 9),
+            Some(5 * ONE_SOL)
+        );
+        assert_eq!(
+            sol_value_to_lst_amount(5 * ONE_SOL, PRICE_1_25_P32, 9),
+            Some(4 * ONE_SOL)
+        );
+        assert_eq!(
+            backing_lamports_to_lst_sol_price_p32(5 * ONE_SOL, 4 * ONE_SOL, 9),
+            Some(PRICE_1_25_P32)
+        );
+    }
+
+    #[test]
+    fn six_decimals_lst_is_scaled_up() {
+        let one_lst = 1_000_000;
+        assert_eq!(
+            lst_amount_to_sol_value(one_lst, TWO_POW_32, 6),
+            Some(ONE_SOL)
+        );
+        assert_eq!(
+            lst_amount_to_sol_value(4 * one_lst, PRICE_1_25_P32, 6),
+            Some(5 * ONE_SOL)
+        );
+        assert_eq!(
+            sol_value_to_lst_amount(5 * ONE_SOL, PRICE_1_25_P32, 6),
+            Some(4 * one_lst)
+        );
+        assert_eq!(
+            backing_lamports_to_lst_sol_price_p32(5 * ONE_SOL, 4 * one_lst, 6),
+            Some(PRICE_1_25_P32)
+        );
+    }
+
+    #[test]
+    fn twelve_decimals_lst_is_scaled_down() {
+        let one_lst = 1_000_000_000_000;
+        assert_eq!(
+            lst_amount_to_sol_value(one_lst, TWO_POW_32, 12),
+            Some(ONE_SOL)
+        );
+        assert_eq!(
+            lst_amount_to_sol_value(4 * one_lst, PRICE_1_25_P32, 12),
+            Some(5 * ONE_SOL)
+        );
+        assert_eq!(
+            sol_value_to_lst_amount(5 * ONE_SOL, PRICE_1_25_P32, 12),
+            Some(4 * one_lst)
+        );
+        assert_eq!(
+            backing_lamports_to_lst_sol_price_p32(5 * ONE_SOL, 4 * one_lst, 12),
+            Some(PRICE_1_25_P32)
+        );
+    }
+
+    #[test]
+    fn conversions_round_down() {
+        // 1 lamport of sol-value is less than 1 unit of a 6 decimals lst
+        assert_eq!(sol_value_to_lst_amount(999, TWO_POW_32, 6), Some(0));
+        assert_eq!(sol_value_to_lst_amount(1_999, TWO_POW_32, 6), Some(1));
+        // 1 unit of a 12 decimals lst is less than 1 lamport
+        assert_eq!(lst_amount_to_sol_value(999, TWO_POW_32, 12), Some(0));
+        assert_eq!(lst_amount_to_sol_value(1_999, TWO_POW_32, 12), Some(1));
+        // a round trip never creates value
+        for lst_decimals in [6, 9, 12] {
+            let sol_value = 1_234_567_891;
+            let lst_amount =
+                sol_value_to_lst_amount(sol_value, PRICE_1_25_P32, lst_decimals).unwrap();
+            assert!(
+                lst_amount_to_sol_value(lst_amount, PRICE_1_25_P32, lst_decimals).unwrap()
+                    <= sol_value
+            );
+        }
+    }
+
+    #[test]
+    fn conversions_out_of_range_are_none() {
+        // results above u64::MAX
+        assert_eq!(mul_div(u64::MAX, 2, 1), None);
+        assert_eq!(lst_amount_to_sol_value(u64::MAX, PRICE_1_25_P32, 9), None);
+        assert_eq!(sol_value_to_lst_amount(u64::MAX, TWO_POW_32 / 2, 9), None);
+        assert_eq!(sol_value_to_mpsol_amount(u64::MAX, 1, 2), None);
+        assert_eq!(mpsol_amount_to_sol_value(u64::MAX, 2, 1), None);
+        // zero denominators
+        assert_eq!(mul_div(ONE_SOL, 1, 0), None);
+        assert_eq!(sol_value_to_lst_amount(ONE_SOL, 0, 9), None);
+        assert_eq!(backing_lamports_to_lst_sol_price_p32(ONE_SOL, 0, 9), None);
+        assert_eq!(mpsol_amount_to_sol_value(ONE_SOL, ONE_SOL, 0), None);
+        // an empty mpSOL supply mints 1:1
+        assert_eq!(sol_value_to_mpsol_amount(ONE_SOL, 0, 0), Some(ONE_SOL));
+    }
+
+    #[test]
+    fn decimals_boundaries_do_not_overflow_the_intermediate_product() {
+        // the largest u64 amounts at a 1:1 price, with the min (1) and max (12) lst decimals
+        assert_eq!(
+            lst_amount_to_sol_value(u64::MAX, TWO_POW_32, 12),
+            Some(u64::MAX / 1_000)
+        );
+        assert_eq!(
+            sol_value_to_lst_amount(u64::MAX / 1_000, TWO_POW_32, 12),
+            Some(u64::MAX / 1_000 * 1_000)
+        );
+        assert_eq!(
+            lst_amount_to_sol_value(u64::MAX / 100_000_000, TWO_POW_32, 1),
+            Some(u64::MAX / 100_000_000 * 100_000_000)
+        );
+        assert_eq!(
+            sol_value_to_lst_amount(u64::MAX, TWO_POW_32, 1),
+            Some(u64::MAX / 100_000_000)
+        );
+        // a 12 decimals lst with the whole u64 supply
+        assert_eq!(
+            backing_lamports_to_lst_sol_price_p32(u64::MAX / 1_000, u64::MAX, 12),
+            Some(TWO_POW_32 - 1)
+        );
+    }
+
+    #[test]
+    fn apply_bp_rounds_down() {
+        assert_eq!(apply_bp(ONE_SOL, BASIS_POINTS_100_PERCENT), ONE_SOL);
+        assert_eq!(apply_bp(ONE_SOL, 50), 5_000_000);
+        assert_eq!(apply_bp(199, 50), 0);
+        // never above the amount
+        assert_eq!(apply_bp(u64::MAX, u16::MAX), u64::MAX);
+    }
 }
@@ -0,0 +1,256 @@
+//! instruction builders & CPI helpers for the permissionless mp-sol-restaking instructions
+//! a strategy can call after moving lst to the withdraw account or after acquiring rewards
+use crate::*;
+use anchor_lang::solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+
+// sha256("global:get_lst_from_strat")[..8]
+pub const GET_LST_FROM_STRAT_DISCRIMINATOR: [u8; 8] = [191, 211, 245, 177, 226, 248, 84, 185];
+// sha256("global:update_attached_strat_lst_amount")[..8]
+pub const UPDATE_ATTACHED_STRAT_LST_AMOUNT_DISCRIMINATOR: [u8; 8] =
+    [190, 250, 156, 206, 33, 235, 97, 183];
+
+/// mp-sol-restaking `get_lst_from_strat`
+/// moves lst from the vault-strat withdraw ATA into the vault
+/// `lst_token_program`: Token or Token-2022, owner of the lst mint
+pub fn get_lst_from_strat(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    strategy_state: &Pubkey,
+    lst_token_program: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: MP_SOL_RESTAKING_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*main_state, false),
+            AccountMeta::new_readonly(*lst_mint, false),
+            AccountMeta::new(find_secondary_vault_state(main_state, lst_mint).0, false),
+            AccountMeta::new_readonly(find_vaults_ata_auth(main_state).0, false),
+            AccountMeta::new(
+                vault_lst_account_with_program_id(main_state, lst_mint, lst_token_program),
+                false,
+            ),
+            AccountMeta::new(find_vault_strategy_relation_entry(strategy_state).0, false),
+            AccountMeta::new_readonly(*strategy_state, false),
+            AccountMeta::new_readonly(find_vault_strat_withdraw_auth(strategy_state).0, false),
+            AccountMeta::new(
+                vault_strat_withdraw_account_with_program_id(
+                    strategy_state,
+                    lst_mint,
+                    lst_token_program,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(*lst_token_program, false),
+        ],
+        data: GET_LST_FROM_STRAT_DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// mp-sol-restaking `update_attached_strat_lst_amount`
+/// records strategy profits or losses
+/// `treasury_mpsol_account` must be the one set in main_state, any account if not set
+/// `lst_token_program`: Token or Token-2022, owner of the lst mint
+pub fn update_attached_strat_lst_amount(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    strategy_program: &Pubkey,
+    strategy_state: &Pubkey,
+    mpsol_mint: &Pubkey,
+    treasury_mpsol_account: &Pubkey,
+    lst_token_program: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: MP_SOL_RESTAKING_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*main_state, false),
+            AccountMeta::new_readonly(*lst_mint, false),
+            AccountMeta::new(find_secondary_vault_state(main_state, lst_mint).0, false),
+            AccountMeta::new(find_vault_strategy_relation_entry(strategy_state).0, false),
+            AccountMeta::new_readonly(*strategy_state, false),
+            AccountMeta::new_readonly(
+                find_strategy_authority(strategy_program, strategy_state).0,
+                false,
+            ),
+            AccountMeta::new_readonly(
+                strategy_deposit_account_with_program_id(
+                    strategy_program,
+                    strategy_state,
+                    lst_mint,
+                    lst_token_program,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(find_vault_strat_withdraw_auth(strategy_state).0, false),
+            AccountMeta::new_readonly(
+                vault_strat_withdraw_account_with_program_id(
+                    strategy_state,
+                    lst_mint,
+                    lst_token_program,
+                ),
+                false,
+            ),
+            AccountMeta::new(*mpsol_mint, false),
+            AccountMeta::new_readonly(find_mpsol_mint_authority(main_state).0, false),
+            AccountMeta::new(*treasury_mpsol_account, false),
+            AccountMeta::new_readonly(anchor_spl::token::ID, false),
+            AccountMeta::new_readonly(*lst_token_program, false),
+        ],
+        data: UPDATE_ATTACHED_STRAT_LST_AMOUNT_DISCRIMINATOR.to_vec(),
+    }
+}
+
+/// accounts for the `get_lst_from_strat` CPI, in instruction order
+pub struct GetLstFromStratCpiAccounts<'info> {
+    pub main_state: AccountInfo<'info>,
+    pub lst_mint: AccountInfo<'info>,
+    pub vault_state: AccountInfo<'info>,
+    pub vaults_ata_pda_auth: AccountInfo<'info>,
+    pub vault_lst_account: AccountInfo<'info>,
+    pub vault_strategy_relation_entry: AccountInfo<'info>,
+    pub common_strategy_state: AccountInfo<'info>,
+    pub vault_strat_withdraw_auth: AccountInfo<'info>,
+    pub lst_withdraw_account: AccountInfo<'info>,
+    /// Token or Token-2022, owner of the lst mint
+    pub lst_token_program: AccountInfo<'info>,
+    pub mp_sol_restaking_program: AccountInfo<'info>,
+}
+
+/// CPI into mp-sol-restaking `get_lst_from_strat`, no signer required (permissionless)
+pub fn get_lst_from_strat_cpi(accounts: GetLstFromStratCpiAccounts) -> ProgramResult {
+    let ix = get_lst_from_strat(
+        accounts.main_state.key,
+        accounts.lst_mint.key,
+        accounts.common_strategy_state.key,
+        accounts.lst_token_program.key,
+    );
+    invoke(
+        &ix,
+        &[
+            accounts.main_state,
+            accounts.lst_mint,
+            accounts.vault_state,
+            accounts.vaults_ata_pda_auth,
+            accounts.vault_lst_account,
+            accounts.vault_strategy_relation_entry,
+            accounts.common_strategy_state,
+            accounts.vault_strat_withdraw_auth,
+            accounts.lst_withdraw_account,
+            accounts.lst_token_program,
+            accounts.mp_sol_restaking_program,
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use mp_sol_restaking::{accounts, instruction};
+
+    const LST_TOKEN_PROGRAMS: [Pubkey; 2] = [anchor_spl::token::ID, anchor_spl::token_2022::ID];
+
+    #[test]
+    fn aggregator_seeds_match_the_program() {
+        assert_eq!(VAULTS_ATA_AUTH_SEED, mp_sol_restaking::VAULTS_ATA_AUTH_SEED);
+        assert_eq!(
+            MAIN_VAULT_MINT_AUTH_SEED,
+            mp_sol_restaking::MAIN_VAULT_MINT_AUTH_SEED
+        );
+        assert_eq!(MP_SOL_RESTAKING_PROGRAM_ID, mp_sol_restaking::ID);
+    }
+
+    #[test]
+    fn get_lst_from_strat_matches_the_program_accounts() {
+        let (main_state, lst_mint, strategy_state) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        for lst_token_program in LST_TOKEN_PROGRAMS {
+            let ix =
+                get_lst_from_strat(&main_state, &lst_mint, &strategy_state, &lst_token_program);
+            let expected = accounts::GetLstFromStrat {
+                main_state,
+                lst_mint,
+                vault_state: find_secondary_vault_state(&main_state, &lst_mint).0,
+                vaults_ata_pda_auth: find_vaults_ata_auth(&main_state).0,
+                vault_lst_account: vault_lst_account_with_program_id(
+                    &main_state,
+                    &lst_mint,
+                    &lst_token_program,
+                ),
+                vault_strategy_relation_entry: find_vault_strategy_relation_entry(&strategy_state)
+                    .0,
+                common_strategy_state: strategy_state,
+                vault_strat_withdraw_auth: find_vault_strat_withdraw_auth(&strategy_state).0,
+                lst_withdraw_account: vault_strat_withdraw_account_with_program_id(
+                    &strategy_state,
+                    &lst_mint,
+                    &lst_token_program,
+                ),
+                lst_token_program,
+            }
+            .to_account_metas(None);
+            assert_eq!(ix.program_id, mp_sol_restaking::ID);
+            assert_eq!(ix.accounts, expected);
+            assert_eq!(ix.data, instruction::GetLstFromStrat {}.data());
+        }
+    }
+
+    #[test]
+    fn update_attached_strat_lst_amount_matches_the_program_accounts() {
+        let (main_state, lst_mint, strategy_program, strategy_state) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (mpsol_mint, treasury_mpsol_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for lst_token_program in LST_TOKEN_PROGRAMS {
+            let ix = update_attached_strat_lst_amount(
+                &main_state,
+                &lst_mint,
+                &strategy_program,
+                &strategy_state,
+                &mpsol_mint,
+                &treasury_mpsol_account,
+                &lst_token_program,
+            );
+            let expected = accounts::UpdateAttachedStratLstAmount {
+                main_state,
+                lst_mint,
+                vault_state: find_secondary_vault_state(&main_state, &lst_mint).0,
+                vault_strategy_relation_entry: find_vault_strategy_relation_entry(&strategy_state)
+                    .0,
+                common_strategy_state: strategy_state,
+                strategy_authority: find_strategy_authority(&strategy_program, &strategy_state).0,
+                strategy_deposit_account: strategy_deposit_account_with_program_id(
+                    &strategy_program,
+                    &strategy_state,
+                    &lst_mint,
+                    &lst_token_program,
+                ),
+                vault_strat_withdraw_auth: find_vault_strat_withdraw_auth(&strategy_state).0,
+                lst_withdraw_account: vault_strat_withdraw_account_with_program_id(
+                    &strategy_state,
+                    &lst_mint,
+                    &lst_token_program,
+                ),
+                mpsol_mint,
+                mpsol_mint_authority: find_mpsol_mint_authority(&main_state).0,
+                treasury_mpsol_account,
+                token_program: anchor_spl::token::ID,
+                lst_token_program,
+            }
+            .to_account_metas(None);
+            assert_eq!(ix.program_id, mp_sol_restaking::ID);
+            assert_eq!(ix.accounts, expected);
+            assert_eq!(ix.data, instruction::UpdateAttachedStratLstAmount {}.data());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Contract between the mpSOL restaking aggregator and strategy programs
+//!
+//! A strategy program:
+//! - creates a state account whose first fields are `CommonStrategyState`
+//! - owns an ATA (strategy deposit account) for the LST, with auth PDA(strategy_program, [STRAT_AUTHORITY_SEED, strategy_state])
+//! - moves LSTs requested by the vault (`VaultStrategyRelationEntry.next_withdraw_lst_amount`)
+//!   into the vault-strat withdraw ATA, with auth PDA(mp-sol-restaking, [VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED, strategy_state])
+//!
+//! Both the aggregator and the strategy programs depend on this crate.
+//! Aggregator-only PDAs (secondary vaults, vault registry, mpSOL mint auth...) are in the client crate
+pub mod cpi;
+pub mod pda;
+pub mod state;
+
+pub use pda::*;
+pub use state::*;
+
+use anchor_lang::solana_program::{pubkey, pubkey::Pubkey};
+
+pub const MP_SOL_RESTAKING_PROGRAM_ID: Pubkey =
+    pubkey!("MPSoLoEnfNRFReRZSVH2V8AffSmWSR4dVoBLFm1YpAW");
+
+/// seed for the strategy authority PDA, owner of the strategy deposit ATA
+pub const STRAT_AUTHORITY_SEED: &[u8] = b"authority";
+/// seed for the vault PDA owner of the temp-ATA used to move lst from strat back to the vault
+pub const VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED: &[u8] = b"lst_withdraw_authority";
+/// seed for the VaultStrategyRelationEntry PDA, created when the strategy is attached
+pub const VAULT_STRAT_ENTRY_SEED: &[u8] = b"vault-strat-entry";
+
+// aggregator seeds, only to build the `cpi` accounts, checked against the program by the `cpi` tests
+pub(crate) const VAULTS_ATA_AUTH_SEED: &[u8] = b"vaults-ata-auth";
+pub(crate) const MAIN_VAULT_MINT_AUTH_SEED: &[u8] = b"main-mint";
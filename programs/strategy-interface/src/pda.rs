@@ -0,0 +1,101 @@
+use crate::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token;
+
+/// strategy auth PDA, owner of the strategy deposit ATA
+/// PDA(strategy_program, [STRAT_AUTHORITY_SEED, strategy_state])
+pub fn find_strategy_authority(strategy_program: &Pubkey, strategy_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[STRAT_AUTHORITY_SEED, strategy_state.as_ref()],
+        strategy_program,
+    )
+}
+
+/// strategy ATA, where the vault sends the LSTs
+pub fn strategy_deposit_account(
+    strategy_program: &Pubkey,
+    strategy_state: &Pubkey,
+    lst_mint: &Pubkey,
+) -> Pubkey {
+    strategy_deposit_account_with_program_id(strategy_program, strategy_state, lst_mint, &token::ID)
+}
+
+/// strategy ATA for a lst mint of `lst_token_program` (Token or Token-2022)
+pub fn strategy_deposit_account_with_program_id(
+    strategy_program: &Pubkey,
+    strategy_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+) -> Pubkey {
+    let (authority, _) = find_strategy_authority(strategy_program, strategy_state);
+    get_associated_token_address_with_program_id(&authority, lst_mint, lst_token_program)
+}
+
+/// vault auth PDA, owner of the temp-ATA to move lst from strat back to the vault
+/// PDA(mp-sol-restaking, [VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED, strategy_state])
+pub fn find_vault_strat_withdraw_auth(strategy_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_STRAT_WITHDRAW_ATA_AUTH_SEED, strategy_state.as_ref()],
+        &MP_SOL_RESTAKING_PROGRAM_ID,
+    )
+}
+
+/// temp-ATA to move lst from strat back to the vault
+pub fn vault_strat_withdraw_account(strategy_state: &Pubkey, lst_mint: &Pubkey) -> Pubkey {
+    vault_strat_withdraw_account_with_program_id(strategy_state, lst_mint, &token::ID)
+}
+
+/// temp-ATA to move lst from strat back to the vault, for a lst mint of `lst_token_program`
+pub fn vault_strat_withdraw_account_with_program_id(
+    strategy_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+) -> Pubkey {
+    let (authority, _) = find_vault_strat_withdraw_auth(strategy_state);
+    get_associated_token_address_with_program_id(&authority, lst_mint, lst_token_program)
+}
+
+/// VaultStrategyRelationEntry, exists if the strategy state was attached to a secondary-vault
+/// PDA(mp-sol-restaking, [VAULT_STRAT_ENTRY_SEED, strategy_state])
+pub fn find_vault_strategy_relation_entry(strategy_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_STRAT_ENTRY_SEED, strategy_state.as_ref()],
+        &MP_SOL_RESTAKING_PROGRAM_ID,
+    )
+}
+
+// aggregator PDAs required by the `cpi` instruction builders
+
+/// secondary-vault state, PDA(mp-sol-restaking, [main_state, lst_mint])
+pub(crate) fn find_secondary_vault_state(main_state: &Pubkey, lst_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[main_state.as_ref(), lst_mint.as_ref()],
+        &MP_SOL_RESTAKING_PROGRAM_ID,
+    )
+}
+
+/// vaults ATA auth PDA, PDA(mp-sol-restaking, [main_state, VAULTS_ATA_AUTH_SEED])
+pub(crate) fn find_vaults_ata_auth(main_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[main_state.as_ref(), VAULTS_ATA_AUTH_SEED],
+        &MP_SOL_RESTAKING_PROGRAM_ID,
+    )
+}
+
+/// secondary-vault LST account for a lst mint of `lst_token_program` (Token or Token-2022)
+pub(crate) fn vault_lst_account_with_program_id(
+    main_state: &Pubkey,
+    lst_mint: &Pubkey,
+    lst_token_program: &Pubkey,
+) -> Pubkey {
+    let (authority, _) = find_vaults_ata_auth(main_state);
+    get_associated_token_address_with_program_id(&authority, lst_mint, lst_token_program)
+}
+
+/// mpSOL mint authority PDA, PDA(mp-sol-restaking, [main_state, MAIN_VAULT_MINT_AUTH_SEED])
+pub(crate) fn find_mpsol_mint_authority(main_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[main_state.as_ref(), MAIN_VAULT_MINT_AUTH_SEED],
+        &MP_SOL_RESTAKING_PROGRAM_ID,
+    )
+}
@@ -0,0 +1,282 @@
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::solana_program::{account_info::AccountInfo, program_error::ProgramError};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::cell::Ref;
+
+// EXTERNAL state, belonging to strategy-programs
+// Note for V2: Dual-LST strategies:
+// A dual-token strategy-program must create 2 CommonVaultStrategyStates
+// one for each token, and attach each CommonVaultStrategyState to the specific token vault
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize, PartialEq)]
+pub struct CommonStrategyState {
+    /// the strategy account discriminator, for anchor programs the account discriminator
+    pub discriminator: [u8; 8],
+
+    pub lst_mint: Pubkey,
+
+    // lst amount in this strategy, not counting the strategy deposit ATA & the vault-strat withdraw ATA
+    // the vault adds those two token balances to this amount
+    // incremented when rewards are acquired
+    // decremented when slashed
+    pub strat_total_lst_amount: u64,
+}
+
+impl CommonStrategyState {
+    pub const DISCRIMINATOR_OFFSET: usize = 0;
+    pub const LST_MINT_OFFSET: usize = 8;
+    pub const STRAT_TOTAL_LST_AMOUNT_OFFSET: usize = 8 + 32;
+    /// serialized len of the common part, the strategy state can be larger
+    pub const LEN: usize = 8 + 32 + 8;
+
+    /// deserialize the common first part of a strategy state
+    /// fails if the account is too small or not initialized (zero discriminator)
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        check_common_header(data)?;
+        let mut data_slice = data;
+        Self::deserialize(&mut data_slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// same as `try_from_account_data`, also checks the discriminator
+    pub fn try_from_account_data_checked(
+        data: &[u8],
+        expected_discriminator: &[u8; 8],
+    ) -> Result<Self, ProgramError> {
+        let state = Self::try_from_account_data(data)?;
+        if &state.discriminator != expected_discriminator {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(state)
+    }
+}
+
+/// mp-sol-restaking `VaultStrategyRelationEntry`, created when the strategy state is attached to a secondary-vault.
+/// Read by the strategy programs without depending on mp-sol-restaking,
+/// the caller must check the account address (`find_vault_strategy_relation_entry`) and owner.
+/// v1.0 fields only, the fields added later are not used by the strategies: both layouts can be read
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize, PartialEq)]
+pub struct VaultStrategyRelationEntry {
+    pub main_state: Pubkey,
+    pub lst_mint: Pubkey,
+    pub common_strategy_state: Pubkey,
+    pub strategy_program_code: Pubkey,
+    /// lst amount requested by the vault, the strategy moves it to the vault-strat withdraw ATA
+    pub next_withdraw_lst_amount: u64,
+    pub tickets_target_sol_amount: u64,
+    pub last_read_strat_lst_amount: u64,
+    pub last_read_strat_lst_timestamp: u64,
+}
+
+impl VaultStrategyRelationEntry {
+    // sha256("account:VaultStrategyRelationEntry")[..8]
+    pub const DISCRIMINATOR: [u8; 8] = [161, 40, 119, 237, 160, 148, 124, 128];
+
+    /// deserialize the account data, checks the discriminator
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < 8 || data[..8] != Self::DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut data_slice = &data[8..];
+        Self::deserialize(&mut data_slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+fn check_common_header(data: &[u8]) -> Result<(), ProgramError> {
+    if data.len() < CommonStrategyState::LEN {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    if data[..8] == [0; 8] {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(())
+}
+
+/// zero-copy read-only view of the common first part of a strategy state
+pub struct CommonStrategyStateView<'a> {
+    data: Ref<'a, &'a mut [u8]>,
+}
+
+impl<'a> CommonStrategyStateView<'a> {
+    /// borrow the account data, fails if the account is too small or not initialized
+    pub fn load(account: &'a AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.try_borrow_data()?;
+        check_common_header(&data)?;
+        Ok(Self { data })
+    }
+
+    /// same as `load`, also checks the discriminator
+    pub fn load_checked(
+        account: &'a AccountInfo,
+        expected_discriminator: &[u8; 8],
+    ) -> Result<Self, ProgramError> {
+        let view = Self::load(account)?;
+        if view.discriminator() != expected_discriminator {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(view)
+    }
+
+    pub fn discriminator(&self) -> &[u8; 8] {
+        self.data[..8].try_into().unwrap()
+    }
+
+    pub fn lst_mint(&self) -> Pubkey {
+        Pubkey::try_from(
+            &self.data[CommonStrategyState::LST_MINT_OFFSET
+                ..CommonStrategyState::STRAT_TOTAL_LST_AMOUNT_OFFSET],
+        )
+        .unwrap()
+    }
+
+    pub fn strat_total_lst_amount(&self) -> u64 {
+        u64::from_le_bytes(
+            self.data[CommonStrategyState::STRAT_TOTAL_LST_AMOUNT_OFFSET..CommonStrategyState::LEN]
+                .try_into()
+                .unwrap(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AccountSerialize, Discriminator};
+
+    const DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    /// a strategy state with the common part and some strategy specific data
+    fn strategy_state_data(lst_mint: &Pubkey, strat_total_lst_amount: u64) -> Vec<u8> {
+        let mut data = borsh::to_vec(&CommonStrategyState {
+            discriminator: DISCRIMINATOR,
+            lst_mint: *lst_mint,
+            strat_total_lst_amount,
+        })
+        .unwrap();
+        data.extend_from_slice(&[0xff; 16]);
+        data
+    }
+
+    #[test]
+    fn common_strategy_state_parses_the_common_part() {
+        let lst_mint = Pubkey::new_unique();
+        let data = strategy_state_data(&lst_mint, 123);
+        let expected = CommonStrategyState {
+            discriminator: DISCRIMINATOR,
+            lst_mint,
+            strat_total_lst_amount: 123,
+        };
+        assert_eq!(
+            CommonStrategyState::try_from_account_data(&data).unwrap(),
+            expected
+        );
+        assert_eq!(
+            CommonStrategyState::try_from_account_data_checked(&data, &DISCRIMINATOR).unwrap(),
+            expected
+        );
+        assert_eq!(
+            CommonStrategyState::try_from_account_data_checked(&data, &[9; 8]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn common_strategy_state_rejects_short_or_uninitialized_accounts() {
+        let data = strategy_state_data(&Pubkey::new_unique(), 1);
+        assert_eq!(
+            CommonStrategyState::try_from_account_data(&data[..CommonStrategyState::LEN - 1]),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        assert_eq!(
+            CommonStrategyState::try_from_account_data(&[0; CommonStrategyState::LEN]),
+            Err(ProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn common_strategy_state_view_reads_the_account() {
+        let (key, owner, lst_mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut lamports = 0;
+        let mut data = strategy_state_data(&lst_mint, u64::MAX);
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let view = CommonStrategyStateView::load_checked(&account, &DISCRIMINATOR).unwrap();
+        assert_eq!(view.discriminator(), &DISCRIMINATOR);
+        assert_eq!(view.lst_mint(), lst_mint);
+        assert_eq!(view.strat_total_lst_amount(), u64::MAX);
+        drop(view);
+        assert!(matches!(
+            CommonStrategyStateView::load_checked(&account, &[9; 8]),
+            Err(ProgramError::InvalidAccountData)
+        ));
+
+        let mut lamports = 0;
+        let mut data = vec![0; CommonStrategyState::LEN];
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(matches!(
+            CommonStrategyStateView::load(&account),
+            Err(ProgramError::UninitializedAccount)
+        ));
+    }
+
+    #[test]
+    fn vault_strategy_relation_entry_reads_the_program_account() {
+        let entry = mp_sol_restaking::VaultStrategyRelationEntry {
+            main_state: Pubkey::new_unique(),
+            lst_mint: Pubkey::new_unique(),
+            common_strategy_state: Pubkey::new_unique(),
+            strategy_program_code: Pubkey::new_unique(),
+            next_withdraw_lst_amount: 1,
+            tickets_target_sol_amount: 2,
+            last_read_strat_lst_amount: 3,
+            last_read_strat_lst_timestamp: 4,
+            unrecovered_loss_lst_amount: 5,
+        };
+        let mut data = vec![];
+        entry.try_serialize(&mut data).unwrap();
+
+        assert_eq!(
+            VaultStrategyRelationEntry::DISCRIMINATOR,
+            mp_sol_restaking::VaultStrategyRelationEntry::DISCRIMINATOR
+        );
+        assert_eq!(
+            VaultStrategyRelationEntry::try_from_account_data(&data).unwrap(),
+            VaultStrategyRelationEntry {
+                main_state: entry.main_state,
+                lst_mint: entry.lst_mint,
+                common_strategy_state: entry.common_strategy_state,
+                strategy_program_code: entry.strategy_program_code,
+                next_withdraw_lst_amount: 1,
+                tickets_target_sol_amount: 2,
+                last_read_strat_lst_amount: 3,
+                last_read_strat_lst_timestamp: 4,
+            }
+        );
+
+        // any other account is rejected
+        data[0] ^= 1;
+        assert_eq!(
+            VaultStrategyRelationEntry::try_from_account_data(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
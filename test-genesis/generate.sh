@@ -16,6 +16,10 @@ solana account -u m --output json $MSOL_MINT >$MSOL_MINT.json
 JITOSOL_MINT=J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn
 solana account -u m --output json $JITOSOL_MINT >$JITOSOL_MINT.json
 
+# template for the test LSTs stake-pool states
+JITOSOL_POOL_STATE=Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb
+solana account -u m --output json $JITOSOL_POOL_STATE >$JITOSOL_POOL_STATE.json
+
 cd -
 
 # MARINADE_PROGRAM_DATA=$(npx ts-node test-genesis/get-program-data.ts $MARINADE_PROGRAM)
@@ -24,3 +28,7 @@ cd -
 
 npx ts-node take-test-mint-auth.ts $MSOL_MINT
 npx ts-node take-test-mint-auth.ts $JITOSOL_MINT
+
+# test LSTs with 6 & 8 decimals, priced by a SPL-stake-pool state
+npx ts-node make-test-lst.ts 8qN1yDcBJr4HjXVR1QRjpUbay4DRJGDAFqxpDuDsYCzL 6 8qN1yDcBJr4HjXVR1QRjpUbay4DRMa9JciUbmyiriRVr 8qN1yDcBJr4HjXVR1QRjpUbay4DRLTubPS6BD8d8gci4
+npx ts-node make-test-lst.ts 8qN1yDcBJr4HjZtNq3Gs9ccjNS5Hz1Q2Ebcd2X31UfdA 8 8qN1yDcBJr4HjZtNq3Gs9ccjNS5J3KLAbU8QabXzet8g
@@ -0,0 +1,91 @@
+import { Keypair, PublicKey } from "@solana/web3.js";
+// @ts-ignore: marinade-sdk has @coral-xyz/anchor and an older version of @solana/spl-token -- vscode intellisense gets confused
+import { MintLayout, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+
+// creates a test LST with custom decimals: a mint (auth = local wallet) and a SPL-stake-pool state pricing it,
+// the state is the jitoSOL stake-pool state with pool_mint, total_lamports & pool_token_supply replaced.
+// Optionally creates a 2nd stake-pool state with last_update_epoch = 0, to test outdated prices
+
+const SPL_STAKE_POOL_PROGRAM = "SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy"
+const JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS = "Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb"
+
+// SplStakePoolState field offsets
+const POOL_MINT_OFFSET = 162
+const TOTAL_LAMPORTS_OFFSET = 258
+const POOL_TOKEN_SUPPLY_OFFSET = 266
+const LAST_UPDATE_EPOCH_OFFSET = 274
+
+// 1 test LST = 1.25 SOL
+const TEST_LST_SUPPLY_TOKENS = 1_000_000n
+const TEST_LST_BACKING_LAMPORTS = 1_250_000n * 1_000_000_000n
+
+type AccountJsonDump = {
+    pubkey: string;
+    account: {
+        lamports: number,
+        data: string[]; // ["AQBz...==","base64"]
+        owner: string;
+        executable: boolean;
+        rentEpoch: number;
+        space: number;
+    }
+}
+
+function accountJsonDump(address: string, owner: string, lamports: number, data: Buffer): AccountJsonDump {
+    return {
+        pubkey: address,
+        account: {
+            executable: false,
+            owner,
+            lamports,
+            data: [data.toString("base64"), "base64"],
+            rentEpoch: 9007199254740991, // bug: U64MAX can not be parsed by serde::json
+            space: data.length,
+        }
+    }
+}
+
+function make_test_lst(mintAddress: string, decimals: number, stakePoolStateAddress: string, outdatedStakePoolStateAddress?: string) {
+
+    let localWalletFile = path.join(os.homedir(), ".config", "solana", "id.json")
+    let localWalletPrivatekey: number[] = JSON.parse(fs.readFileSync(localWalletFile).toString())
+    const localWalletKeypair = Keypair.fromSecretKey(Buffer.from(localWalletPrivatekey))
+
+    const supply = TEST_LST_SUPPLY_TOKENS * 10n ** BigInt(decimals)
+
+    // mint
+    const mintData = Buffer.alloc(MintLayout.span)
+    MintLayout.encode({
+        mintAuthorityOption: 1,
+        mintAuthority: localWalletKeypair.publicKey,
+        supply,
+        decimals,
+        isInitialized: true,
+        freezeAuthorityOption: 0,
+        freezeAuthority: PublicKey.default,
+    }, mintData)
+    fs.writeFileSync(path.join("generated-data", mintAddress) + ".json",
+        JSON.stringify(accountJsonDump(mintAddress, TOKEN_PROGRAM_ID.toBase58(), 1461600, mintData)))
+
+    // stake-pool state
+    const jitoDump = JSON.parse(fs.readFileSync(
+        path.join("generated-data", JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS) + ".json").toString()) as AccountJsonDump
+    const stateData = Buffer.from(jitoDump.account.data[0], jitoDump.account.data[1] as BufferEncoding)
+    new PublicKey(mintAddress).toBuffer().copy(stateData, POOL_MINT_OFFSET)
+    stateData.writeBigUInt64LE(TEST_LST_BACKING_LAMPORTS, TOTAL_LAMPORTS_OFFSET)
+    stateData.writeBigUInt64LE(supply, POOL_TOKEN_SUPPLY_OFFSET)
+    fs.writeFileSync(path.join("generated-data", stakePoolStateAddress) + ".json",
+        JSON.stringify(accountJsonDump(stakePoolStateAddress, SPL_STAKE_POOL_PROGRAM, jitoDump.account.lamports, stateData)))
+
+    if (outdatedStakePoolStateAddress) {
+        stateData.writeBigUInt64LE(0n, LAST_UPDATE_EPOCH_OFFSET)
+        fs.writeFileSync(path.join("generated-data", outdatedStakePoolStateAddress) + ".json",
+            JSON.stringify(accountJsonDump(outdatedStakePoolStateAddress, SPL_STAKE_POOL_PROGRAM, jitoDump.account.lamports, stateData)))
+    }
+}
+// argv[0]=node, argv[1]=this-file.ts, argv[2]=mint, argv[3]=decimals, argv[4]=stake-pool state, argv[5]=optional outdated stake-pool state
+make_test_lst(process.argv[2], Number(process.argv[3]), process.argv[4], process.argv[5])
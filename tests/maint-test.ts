@@ -1,7 +1,9 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { MpSolRestaking } from "../target/types/mp_sol_restaking";
-import { Keypair, PublicKey, Transaction } from "@solana/web3.js";
+import { MockStrategy } from "../target/types/mock_strategy";
+import { JustHoldStrategy } from "../target/types/just_hold_strategy";
+import { Authorized, Keypair, PublicKey, StakeProgram, SystemProgram, Transaction, TransactionInstruction } from "@solana/web3.js";
 import * as splStakePool from "@solana/spl-stake-pool";
 // @ts-ignore: marinade-sdk has @coral-xyz/anchor and an older version of @solana/spl-token -- vscode intellisense gets confused
 import { ASSOCIATED_TOKEN_PROGRAM_ID, NATIVE_MINT, TOKEN_PROGRAM_ID, Token, getAssociatedTokenAddressSync, getMint } from "@solana/spl-token";
@@ -15,6 +17,11 @@ import { MethodsBuilder } from "@coral-xyz/anchor/dist/cjs/program/namespace/met
 import { AllInstructions } from "@coral-xyz/anchor/dist/cjs/program/namespace/types";
 import { createSyncNativeInstruction } from "@solana/spl-token";
 import { createAssociatedTokenAccountInstruction } from "@solana/spl-token";
+import {
+  ExtensionType, TOKEN_2022_PROGRAM_ID, createInitializeMintInstruction, createInitializeNonTransferableMintInstruction,
+  createInitializePermanentDelegateInstruction, createInitializeTransferFeeConfigInstruction, getMintLen,
+  createMint, createTransferInstruction
+} from "@solana/spl-token";
 
 const ONE_E9: string = 1e9.toFixed()
 const TWO_POW_32: string = (2 ** 32).toFixed()
@@ -29,9 +36,20 @@ const SPL_STAKE_POOL_PROGRAM = "SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy"
 const JITO_SOL_TOKEN_MINT = "J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn"
 const JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS = "Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb"
 
+// test LSTs created by test-genesis/make-test-lst.ts, 1 LST = 1.25 SOL
+const TEST_LSTS = [
+  { decimals: 6, mint: "8qN1yDcBJr4HjXVR1QRjpUbay4DRJGDAFqxpDuDsYCzL", stakePoolState: "8qN1yDcBJr4HjXVR1QRjpUbay4DRMa9JciUbmyiriRVr" },
+  { decimals: 8, mint: "8qN1yDcBJr4HjZtNq3Gs9ccjNS5Hz1Q2Ebcd2X31UfdA", stakePoolState: "8qN1yDcBJr4HjZtNq3Gs9ccjNS5J3KLAbU8QabXzet8g" },
+]
+const TEST_LST_SOL_PRICE_P32 = (1.25 * 2 ** 32).toFixed()
+// stake-pool state of the 6 decimals test LST, with last_update_epoch = 0
+const TEST_LST_OUTDATED_STAKE_POOL_STATE = "8qN1yDcBJr4HjXVR1QRjpUbay4DRLTubPS6BD8d8gci4"
+
 anchor.setProvider(anchor.AnchorProvider.env());
 
 const program = anchor.workspace.MpSolRestaking as Program<MpSolRestaking>;
+const mockStrategyProgram = anchor.workspace.MockStrategy as Program<MockStrategy>;
+const justHoldStrategyProgram = anchor.workspace.JustHoldStrategy as Program<JustHoldStrategy>;
 const provider = program.provider as anchor.AnchorProvider;
 const wallet = provider.wallet;
 
@@ -76,9 +94,17 @@ async function airdropLamports(provider: Provider, pubkey: PublicKey, amountSol:
   );
 }
 
+function vaultRegistryAddress(mainState: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [mainState.toBuffer(), idlConstant(program.idl, "vaultRegistrySeed")],
+    program.programId
+  )[0]
+}
+
 //-------------------------------
 /// returns vault state address
-async function testCreateSecondaryVault(mainStateKeyPair: Keypair, tokenName: string, lstMint: string): Promise<PublicKey> {
+async function testCreateSecondaryVault(mainStateKeyPair: Keypair, tokenName: string, lstMint: string,
+  priceSourceKind: any = { wsol: {} }, priceSourceState: string | null = null): Promise<PublicKey> {
   // creating a secondary vault
   console.log(`creating ${tokenName} secondary vault, lstMint:${lstMint}`)
   let lstMintPublickey = new PublicKey(lstMint);
@@ -112,14 +138,17 @@ async function testCreateSecondaryVault(mainStateKeyPair: Keypair, tokenName: st
     ASSOCIATED_TOKEN_PROGRAM_ID
   );
 
-  const tx2 = await program.methods.createSecondaryVault()
+  const priceSourceStatePublicKey = priceSourceState ? new PublicKey(priceSourceState) : PublicKey.default
+  const tx2 = await program.methods.createSecondaryVault(priceSourceKind, priceSourceStatePublicKey)
     .accounts({
       admin: wallet.publicKey,
       mainState: mainStateKeyPair.publicKey,
       lstMint: lstMintPublickey,
       secondaryState: vaultSecondaryStateAddress,
-      vaultLstAccount: vaultTokenAccountAddress
+      vaultLstAccount: vaultTokenAccountAddress,
+      lstTokenProgram: TOKEN_PROGRAM_ID
     })
+    .remainingAccounts(priceSourceState ? [{ pubkey: priceSourceStatePublicKey, isSigner: false, isWritable: false }] : [])
     .preInstructions(createAtaIx)
     .rpc();
 
@@ -133,6 +162,16 @@ async function testCreateSecondaryVault(mainStateKeyPair: Keypair, tokenName: st
     expect(secondaryVaultState.lstSolPriceP32.toString()).to.eql("0");
     expect(secondaryVaultState.ticketsTargetSolAmount.toString()).to.eql("0");
     expect(secondaryVaultState.vaultTotalLstAmount.toString()).to.eql("0");
+    expect(secondaryVaultState.priceSourceKind).to.eql(priceSourceKind);
+    expect(secondaryVaultState.priceSourceState).to.eql(priceSourceStatePublicKey);
+    expect(secondaryVaultState.lstMintDecimals).to.eql((await getMint(provider.connection, lstMintPublickey)).decimals);
+  }
+
+  // the new vault is appended to the vault registry
+  {
+    const vaultRegistry = await program.account.vaultRegistry.fetch(vaultRegistryAddress(mainStateKeyPair.publicKey));
+    expect(vaultRegistry.mainState).to.eql(mainStateKeyPair.publicKey);
+    expect(vaultRegistry.vaults[vaultRegistry.vaults.length - 1]).to.eql(vaultSecondaryStateAddress);
   }
 
   return vaultSecondaryStateAddress
@@ -251,6 +290,18 @@ async function createAndTestMainState(shareTokenKeyPair: Keypair): Promise<
     //console.log("Your transaction signature", tx);
   }
 
+  // create the (empty) vault registry, required by createSecondaryVault
+  {
+    await program.methods.createVaultRegistry()
+      .accounts({
+        admin: wallet.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .rpc();
+    const vaultRegistry = await program.account.vaultRegistry.fetch(vaultRegistryAddress(mainStateKeyPair.publicKey));
+    expect(vaultRegistry.vaults.length).to.eql(0);
+  }
+
   // check main state
   const mainState = await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey);
   expect(mainState.admin).to.eql(wallet.publicKey);
@@ -300,7 +351,7 @@ async function createAndTestMainState(shareTokenKeyPair: Keypair): Promise<
   console.log("test wSOL deposit")
   {
     // enable deposits in Wsol vault
-    await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null })
+    await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy: null, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds: null })
       .accounts({
         admin: wallet.publicKey,
         mainState: mainStateKeyPair.publicKey,
@@ -324,6 +375,7 @@ async function createAndTestMainState(shareTokenKeyPair: Keypair): Promise<
         depositorLstAccount: depositorAtaWSol,
         mpsolMint: shareTokenKeyPair.publicKey,
         depositorMpsolAccount: depositorMpSolAta,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
       })
 
     try {
@@ -369,6 +421,98 @@ async function config0hsWaitTime(mainStateKeyPair: Keypair) {
 }
 
 
+//-------------------------
+// a SPL stake-pool with no validators, created in the test-validator so its price can be moved:
+// lamports sent to the reserve are added to total_lamports by UpdateStakePoolBalance
+type TestSplStakePool = {
+  stakePool: PublicKey, withdrawAuthority: PublicKey, validatorList: PublicKey,
+  reserveStake: PublicKey, poolMint: PublicKey, managerPoolAccount: PublicKey,
+}
+
+async function createTestSplStakePool(initialLamports: number): Promise<TestSplStakePool> {
+  const connection = provider.connection
+  const stakePoolProgram = new PublicKey(SPL_STAKE_POOL_PROGRAM)
+  const stakePoolKeyPair = Keypair.generate()
+  const validatorListKeyPair = Keypair.generate()
+  const reserveStakeKeyPair = Keypair.generate()
+  const poolMintKeyPair = Keypair.generate()
+  const [withdrawAuthority] = PublicKey.findProgramAddressSync(
+    [stakePoolKeyPair.publicKey.toBuffer(), Buffer.from("withdraw")], stakePoolProgram)
+
+  // same size as a live stake-pool state, validator list for 1 validator: header + vec len + 1 ValidatorStakeInfo
+  const stakePoolSpace = (await connection.getAccountInfo(new PublicKey(JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS))).data.length
+  const validatorListSpace = 5 + 4 + 73
+  const stakeSpace = StakeProgram.space
+  const createAccountsTx = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: wallet.publicKey, newAccountPubkey: stakePoolKeyPair.publicKey, space: stakePoolSpace,
+      lamports: await connection.getMinimumBalanceForRentExemption(stakePoolSpace), programId: stakePoolProgram,
+    }),
+    SystemProgram.createAccount({
+      fromPubkey: wallet.publicKey, newAccountPubkey: validatorListKeyPair.publicKey, space: validatorListSpace,
+      lamports: await connection.getMinimumBalanceForRentExemption(validatorListSpace), programId: stakePoolProgram,
+    }),
+    StakeProgram.createAccount({
+      fromPubkey: wallet.publicKey, stakePubkey: reserveStakeKeyPair.publicKey,
+      authorized: new Authorized(withdrawAuthority, withdrawAuthority),
+      lamports: await connection.getMinimumBalanceForRentExemption(stakeSpace) + initialLamports,
+    }),
+  )
+  await provider.sendAndConfirm(createAccountsTx, [stakePoolKeyPair, validatorListKeyPair, reserveStakeKeyPair])
+  const poolMint = await createMint(connection, (wallet as any).payer, withdrawAuthority, null, 9, poolMintKeyPair)
+  const managerPoolAccount = await createAta(provider, wallet, poolMint, wallet.publicKey)
+
+  // Initialize: fee, withdrawal_fee & deposit_fee as {denominator, numerator}, referral_fee u8, max_validators u32
+  const data = Buffer.alloc(1 + 3 * 16 + 1 + 4)
+  data.writeUInt8(0, 0)
+  for (let n = 0; n < 3; n++) {
+    data.writeBigUInt64LE(100n, 1 + n * 16)
+    data.writeBigUInt64LE(0n, 1 + n * 16 + 8)
+  }
+  data.writeUInt8(0, 49)
+  data.writeUInt32LE(1, 50)
+  await sendTx(provider, wallet, [new TransactionInstruction({
+    programId: stakePoolProgram,
+    keys: [
+      { pubkey: stakePoolKeyPair.publicKey, isSigner: false, isWritable: true },
+      { pubkey: wallet.publicKey, isSigner: true, isWritable: false }, // manager
+      { pubkey: wallet.publicKey, isSigner: false, isWritable: false }, // staker
+      { pubkey: withdrawAuthority, isSigner: false, isWritable: false },
+      { pubkey: validatorListKeyPair.publicKey, isSigner: false, isWritable: true },
+      { pubkey: reserveStakeKeyPair.publicKey, isSigner: false, isWritable: false },
+      { pubkey: poolMint, isSigner: false, isWritable: true },
+      { pubkey: managerPoolAccount, isSigner: false, isWritable: true },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ],
+    data,
+  })])
+
+  return {
+    stakePool: stakePoolKeyPair.publicKey, withdrawAuthority, validatorList: validatorListKeyPair.publicKey,
+    reserveStake: reserveStakeKeyPair.publicKey, poolMint, managerPoolAccount,
+  }
+}
+
+// adds lamports to the pool reserve and runs UpdateStakePoolBalance: the pool price moves up
+async function raiseTestSplStakePoolPrice(pool: TestSplStakePool, lamports: number) {
+  await sendTx(provider, wallet, [
+    SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: pool.reserveStake, lamports }),
+    new TransactionInstruction({
+      programId: new PublicKey(SPL_STAKE_POOL_PROGRAM),
+      keys: [
+        { pubkey: pool.stakePool, isSigner: false, isWritable: true },
+        { pubkey: pool.withdrawAuthority, isSigner: false, isWritable: false },
+        { pubkey: pool.validatorList, isSigner: false, isWritable: true },
+        { pubkey: pool.reserveStake, isSigner: false, isWritable: false },
+        { pubkey: pool.managerPoolAccount, isSigner: false, isWritable: true },
+        { pubkey: pool.poolMint, isSigner: false, isWritable: true },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from([7]), // UpdateStakePoolBalance
+    }),
+  ])
+}
+
 
 // ------------------------------
 describe("mp-sol-restaking", () => {
@@ -422,8 +566,11 @@ describe("mp-sol-restaking", () => {
     // create Marinade mSol secondary vault
     // ------------------------------
     let marinadeSecondaryVaultStateAddress =
-      await testCreateSecondaryVault(mainStateKeyPair, "mSOL", MARINADE_MSOL_MINT
-      );
+      await testCreateSecondaryVault(mainStateKeyPair, "mSOL", MARINADE_MSOL_MINT,
+        { marinade: {} }, MARINADE_STATE_ADDRESS);
+    // Marinade does not store the epoch of its price, the vault can not check it
+    expect((await program.account.secondaryVaultState.fetch(marinadeSecondaryVaultStateAddress)).priceEpochPolicy)
+      .to.eql({ ignore: {} });
 
     // test mSOL secondary vault update price
     let walletMsolAccount;
@@ -479,6 +626,7 @@ describe("mp-sol-restaking", () => {
           depositorLstAccount: depositorAtaMsol,
           mpsolMint: mpsolTokenMintKeyPair.publicKey,
           depositorMpsolAccount: depositorMpSolAta,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
         })
         .remainingAccounts(
           [{
@@ -502,7 +650,7 @@ describe("mp-sol-restaking", () => {
 
       {
         console.log("config, enable deposits")
-        let configTx = await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null })
+        let configTx = await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy: null, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds: null })
           .accounts({
             admin: wallet.publicKey,
             mainState: mainStateKeyPair.publicKey,
@@ -559,8 +707,8 @@ describe("mp-sol-restaking", () => {
     // create JitoSOL secondary vault
     // ------------------------------
     let jitoSolSecondaryVaultStateAddress =
-      await testCreateSecondaryVault(mainStateKeyPair, "JitoSOL", JITO_SOL_TOKEN_MINT
-      );
+      await testCreateSecondaryVault(mainStateKeyPair, "JitoSOL", JITO_SOL_TOKEN_MINT,
+        { splStakePool: {} }, JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS);
 
     // test SPl-stake-pool update price (using jitoSOL SPL-stake-pool as example)
     {
@@ -599,7 +747,7 @@ describe("mp-sol-restaking", () => {
 
         {
           console.log("config, enable deposits for JITO_SOL_TOKEN_MINT")
-          let configTx = await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null })
+          let configTx = await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy: null, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds: null })
             .accounts({
               admin: wallet.publicKey,
               mainState: mainStateKeyPair.publicKey,
@@ -623,6 +771,7 @@ describe("mp-sol-restaking", () => {
             depositorLstAccount: depositorAtaJitoSol,
             mpsolMint: mpsolTokenMintKeyPair.publicKey,
             depositorMpsolAccount: depositorMpSolAta,
+            lstTokenProgram: TOKEN_PROGRAM_ID,
           })
           .remainingAccounts(
             [{
@@ -700,14 +849,15 @@ describe("mp-sol-restaking", () => {
       console.log("try to claim fake ticket on real vaults", amountSolClaimed.toString())
       const vaultWSolAta = await getAssociatedTokenAddressSync(
         new PublicKey(WSOL_TOKEN_MINT), vaultAtaAuth, true);
-      let claimTx = await program.methods.ticketClaim(amountSolClaimed)
+      let claimTx = await program.methods.ticketClaim(amountSolClaimed, new BN(0))
         .accounts({
           mainState: mainStateKeyPair.publicKey,
           beneficiary: depositorUserKeyPair.publicKey,
           ticketAccount: fakeTicket.publicKey,
           lstMint: new PublicKey(WSOL_TOKEN_MINT),
           beneficiaryLstAccount: depositorAtaWSol,
-          vaultLstAccount: vaultWSolAta
+          vaultLstAccount: vaultWSolAta,
+          lstTokenProgram: TOKEN_PROGRAM_ID
         })
 
       // it should fail 
@@ -746,14 +896,15 @@ describe("mp-sol-restaking", () => {
 
         let amountSolClaimed = amountSolTicket.div(new BN(3))
         console.log("ticket claim 1/3", amountSolClaimed.toString())
-        let claimTx = await program.methods.ticketClaim(amountSolClaimed)
+        let claimTx = await program.methods.ticketClaim(amountSolClaimed, new BN(0))
           .accounts({
             mainState: mainStateKeyPair.publicKey,
             beneficiary: depositorUserKeyPair.publicKey,
             ticketAccount: newTicketAccount2.publicKey,
             lstMint: new PublicKey(MARINADE_MSOL_MINT),
             beneficiaryLstAccount: depositorAtaMsol,
-            vaultLstAccount: vaultMsolAta
+            vaultLstAccount: vaultMsolAta,
+            lstTokenProgram: TOKEN_PROGRAM_ID
           })
           .remainingAccounts([{
             pubkey: new PublicKey(MARINADE_STATE_ADDRESS), isSigner: false, isWritable: false
@@ -819,14 +970,15 @@ describe("mp-sol-restaking", () => {
 
         let amountSolClaimed = amountSolTicket.sub(amountSolTicket.div(new BN(3)))
         console.log("ticket claim rest", amountSolClaimed.toString())
-        let claimTx = await program.methods.ticketClaim(amountSolClaimed)
+        let claimTx = await program.methods.ticketClaim(amountSolClaimed, new BN(0))
           .accounts({
             mainState: mainStateKeyPair.publicKey,
             beneficiary: depositorUserKeyPair.publicKey,
             ticketAccount: newTicketAccount2.publicKey,
             lstMint: new PublicKey(MARINADE_MSOL_MINT),
             beneficiaryLstAccount: depositorAtaMsol,
-            vaultLstAccount: vaultMsolAta
+            vaultLstAccount: vaultMsolAta,
+            lstTokenProgram: TOKEN_PROGRAM_ID
           })
           .remainingAccounts([{
             pubkey: new PublicKey(MARINADE_STATE_ADDRESS), isSigner: false, isWritable: false
@@ -894,6 +1046,1903 @@ describe("mp-sol-restaking", () => {
 
   });
 
-});
+  it("strategy loss is socialized", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const [wSolSecondaryStateAddress] = PublicKey.findProgramAddressSync(
+      [mainStateKeyPair.publicKey.toBuffer(), wSolMint.toBuffer()], program.programId)
+    const vaultWSolAta = getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true);
+
+    // create a mock strategy for wSOL
+    const stratStateKeyPair = Keypair.generate()
+    const [stratAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "authoritySeed"), stratStateKeyPair.publicKey.toBuffer()],
+      mockStrategyProgram.programId)
+    const stratAta = getAssociatedTokenAddressSync(wSolMint, stratAuth, true);
+    await mockStrategyProgram.methods.initialize()
+      .accounts({
+        payer: wallet.publicKey,
+        state: stratStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([stratStateKeyPair])
+      .rpc()
+
+    // attach the strategy to the wSOL vault
+    console.log("attach mock strategy")
+    await program.methods.attachCommonStrategyState()
+      .accounts({
+        admin: wallet.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyProgramCode: mockStrategyProgram.programId,
+      })
+      .rpc()
+    const [vaultStrategyRelationEntry] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "vaultStratEntrySeed"), stratStateKeyPair.publicKey.toBuffer()],
+      program.programId)
+
+    // create the temp-ATA used to move lst from the strat back to the vault
+    const [vaultStratWithdrawAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "vaultStratWithdrawAtaAuthSeed"), stratStateKeyPair.publicKey.toBuffer()],
+      program.programId)
+    const lstWithdrawAccount = await createAta(provider, wallet, wSolMint, vaultStratWithdrawAuth, true)
+
+    // move 5e10 wSOL to the strategy
+    console.log("transfer lst to strat")
+    const amountToStrat = new BN(5e10.toFixed())
+    await program.methods.transferLstToStrat(amountToStrat)
+      .accounts({
+        mainState: mainStateKeyPair.publicKey,
+        operatorAuth: operatorAuthKeyPair.publicKey,
+        lstMint: wSolMint,
+        vaultLstAccount: vaultWSolAta,
+        strategyProgramCode: mockStrategyProgram.programId,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([operatorAuthKeyPair])
+      .rpc()
+
+    // slash 1e10 wSOL from the strategy
+    console.log("slash mock strategy")
+    const amountSlashed = new BN(1e10.toFixed())
+    const slashDestination = await createAta(provider, wallet, wSolMint, Keypair.generate().publicKey)
+    await mockStrategyProgram.methods.slash(amountSlashed)
+      .accounts({
+        state: stratStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        destination: slashDestination,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc()
+
+    // refresh price, update_attached_strat_lst_amount requires a non-stale price
+    await testGetUpdateVaultPriceMethod(mainStateKeyPair, "wSOL", WSOL_TOKEN_MINT).rpc()
+
+    // remember pre data
+    const mainStatePre = await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey);
+    const vaultStatePre = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress);
+    const mpSolSupply = new BN(await getTokenMintSupply(provider, mpsolTokenMintKeyPair.publicKey))
+
+    let lossEvent;
+    const lossEventListenerNumber = program.addEventListener("strategyLossEvent", (event) => { lossEvent = event })
+    console.log("update attached strat lst amount")
+    await program.methods.updateAttachedStratLstAmount()
+      .accounts({
+        mainState: mainStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyDepositAccount: stratAta,
+        lstWithdrawAccount,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+        treasuryMpsolAccount: Keypair.generate().publicKey, // no treasury configured
+      })
+      .rpc()
+
+    // check vault accounting after the loss
+    const vaultStateAfter = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress);
+    expect(vaultStateAfter.vaultTotalLstAmount.toString())
+      .to.eql(vaultStatePre.vaultTotalLstAmount.sub(amountSlashed).toString());
+    expect(vaultStateAfter.inStrategiesAmount.toString())
+      .to.eql(vaultStatePre.inStrategiesAmount.sub(amountSlashed).toString());
+    expect(vaultStateAfter.locallyStoredAmount.toString())
+      .to.eql(vaultStatePre.locallyStoredAmount.toString());
+
+    const relationEntry = await program.account.vaultStrategyRelationEntry.fetch(vaultStrategyRelationEntry);
+    expect(relationEntry.lastReadStratLstAmount.toString()).to.eql(amountToStrat.sub(amountSlashed).toString());
+
+    // wSOL price is 1, so backing_sol_value drops by the slashed amount
+    const mainStateAfter = await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey);
+    expect(mainStateAfter.backingSolValue.toString())
+      .to.eql(mainStatePre.backingSolValue.sub(amountSlashed).toString());
+    // mpSOL supply does not change, so mpSOL price drops by amountSlashed / supply
+    expect(await getTokenMintSupply(provider, mpsolTokenMintKeyPair.publicKey)).to.eql(mpSolSupply.toString());
+    const mpSolPricePre = mainStatePre.backingSolValue.mul(new BN(ONE_E9)).div(mpSolSupply)
+    const mpSolPriceAfter = mainStateAfter.backingSolValue.mul(new BN(ONE_E9)).div(mpSolSupply)
+    // allow for 1 lamport rounding difference
+    expect(mpSolPricePre.sub(mpSolPriceAfter).toNumber())
+      .to.be.closeTo(amountSlashed.mul(new BN(ONE_E9)).div(mpSolSupply).toNumber(), 1);
+
+    // check loss event
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    program.removeEventListener(lossEventListenerNumber)
+    expect(lossEvent.lossLstAmount.toString()).to.eql(amountSlashed.toString());
+    expect(lossEvent.lossSolValue.toString()).to.eql(amountSlashed.toString());
+    expect(lossEvent.unrecoveredLossLstAmount.toString()).to.eql(amountSlashed.toString());
+    expect(relationEntry.unrecoveredLossLstAmount.toString()).to.eql(amountSlashed.toString());
+
+    // the strategy recovers: no performance fee until the loss is recovered (high-water mark)
+    const strategyProfit = async (lamports: number) => {
+      await sendTx(provider, wallet, [
+        SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: stratAta, lamports }),
+        createSyncNativeInstruction(stratAta),
+      ])
+      let profitEvent;
+      const profitEventListenerNumber = program.addEventListener("updateAttachedStratLstAmountEvent", (event) => { profitEvent = event })
+      await program.methods.updateAttachedStratLstAmount()
+        .accounts({
+          mainState: mainStateKeyPair.publicKey,
+          lstMint: wSolMint,
+          commonStrategyState: stratStateKeyPair.publicKey,
+          strategyDepositAccount: stratAta,
+          lstWithdrawAccount,
+          mpsolMint: mpsolTokenMintKeyPair.publicKey,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+          treasuryMpsolAccount: Keypair.generate().publicKey, // no treasury configured
+        })
+        .rpc()
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      program.removeEventListener(profitEventListenerNumber)
+      return profitEvent
+    }
+    {
+      const profitEvent = await strategyProfit(amountSlashed.divn(2).toNumber())
+      expect(profitEvent.recoveredLossLstAmount.toString()).to.eql(amountSlashed.divn(2).toString());
+      expect(profitEvent.performanceFeeMpsolAmount.toString()).to.eql("0");
+      const entry = await program.account.vaultStrategyRelationEntry.fetch(vaultStrategyRelationEntry);
+      expect(entry.unrecoveredLossLstAmount.toString()).to.eql(amountSlashed.divn(2).toString());
+    }
+    {
+      // half recovers the loss, half is charged the fee
+      const profitEvent = await strategyProfit(amountSlashed.toNumber())
+      expect(profitEvent.recoveredLossLstAmount.toString()).to.eql(amountSlashed.divn(2).toString());
+      expect(profitEvent.performanceFeeMpsolAmount.gtn(0)).to.eql(true);
+      const entry = await program.account.vaultStrategyRelationEntry.fetch(vaultStrategyRelationEntry);
+      expect(entry.unrecoveredLossLstAmount.toString()).to.eql("0");
+    }
+  });
+
+  it("just-hold strategy deposit & withdraw", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const [wSolSecondaryStateAddress] = PublicKey.findProgramAddressSync(
+      [mainStateKeyPair.publicKey.toBuffer(), wSolMint.toBuffer()], program.programId)
+    const vaultWSolAta = getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true);
+
+    // create a just-hold strategy for wSOL
+    const stratStateKeyPair = Keypair.generate()
+    await justHoldStrategyProgram.methods.initialize()
+      .accounts({
+        payer: wallet.publicKey,
+        state: stratStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([stratStateKeyPair])
+      .rpc()
+
+    // attach the strategy to the wSOL vault
+    console.log("attach just-hold strategy")
+    await program.methods.attachCommonStrategyState()
+      .accounts({
+        admin: wallet.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyProgramCode: justHoldStrategyProgram.programId,
+      })
+      .rpc()
+    const [vaultStrategyRelationEntry] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "vaultStratEntrySeed"), stratStateKeyPair.publicKey.toBuffer()],
+      program.programId)
+
+    // create the temp-ATA used to move lst from the strat back to the vault
+    const [vaultStratWithdrawAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "vaultStratWithdrawAtaAuthSeed"), stratStateKeyPair.publicKey.toBuffer()],
+      program.programId)
+    const lstWithdrawAccount = await createAta(provider, wallet, wSolMint, vaultStratWithdrawAuth, true)
+
+    // move 5e10 wSOL to the strategy
+    console.log("transfer lst to strat")
+    const amountToStrat = new BN(5e10.toFixed())
+    await program.methods.transferLstToStrat(amountToStrat)
+      .accounts({
+        mainState: mainStateKeyPair.publicKey,
+        operatorAuth: operatorAuthKeyPair.publicKey,
+        lstMint: wSolMint,
+        vaultLstAccount: vaultWSolAta,
+        strategyProgramCode: justHoldStrategyProgram.programId,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([operatorAuthKeyPair])
+      .rpc()
+    {
+      const vaultState = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress);
+      expect(vaultState.inStrategiesAmount.toString()).to.eql(amountToStrat.toString());
+    }
+
+    // signal the strategy we need 2e10 back
+    const amountToWithdraw = new BN(2e10.toFixed())
+    await program.methods.setNextWithdrawAmount(amountToWithdraw)
+      .accounts({
+        mainState: mainStateKeyPair.publicKey,
+        operatorAuth: operatorAuthKeyPair.publicKey,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        lstWithdrawAccount,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([operatorAuthKeyPair])
+      .rpc()
+
+    // permissionless: the strategy moves the lst to the withdraw account
+    console.log("just-hold move to withdraw account")
+    await justHoldStrategyProgram.methods.moveToWithdrawAccount()
+      .accounts({
+        state: stratStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        lstWithdrawAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc()
+    expect(await getTokenAccountBalance(provider, lstWithdrawAccount)).to.eql(amountToWithdraw.toString())
+
+    // permissionless: the vault takes the lst
+    console.log("get lst from strat")
+    const vaultStatePre = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress);
+    await program.methods.getLstFromStrat()
+      .accounts({
+        mainState: mainStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        vaultLstAccount: vaultWSolAta,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        lstWithdrawAccount,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc()
+
+    const vaultStateAfter = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress);
+    expect(vaultStateAfter.inStrategiesAmount.toString())
+      .to.eql(vaultStatePre.inStrategiesAmount.sub(amountToWithdraw).toString());
+    expect(vaultStateAfter.locallyStoredAmount.toString())
+      .to.eql(vaultStatePre.locallyStoredAmount.add(amountToWithdraw).toString());
+    expect(vaultStateAfter.vaultTotalLstAmount.toString())
+      .to.eql(vaultStatePre.vaultTotalLstAmount.toString());
+    const relationEntry = await program.account.vaultStrategyRelationEntry.fetch(vaultStrategyRelationEntry);
+    expect(relationEntry.nextWithdrawLstAmount.toString()).to.eql("0");
+    expect(relationEntry.lastReadStratLstAmount.toString()).to.eql(amountToStrat.sub(amountToWithdraw).toString());
+  });
+
+  it("check invariants", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const [wSolSecondaryStateAddress] = PublicKey.findProgramAddressSync(
+      [mainStateKeyPair.publicKey.toBuffer(), wSolMint.toBuffer()], program.programId)
+    const vaultWSolAta = getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true);
+
+    const checkInvariants = (remainingAccounts) => program.methods.checkInvariants(true)
+      .accounts({
+        caller: wallet.publicKey, // admin
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .remainingAccounts(remainingAccounts)
+
+    // all registered vaults must be sent
+    try {
+      await checkInvariants([]).rpc()
+      expect.fail("should fail with missing vaults")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidVaultIndex")
+    }
+
+    // the lst account must be the vault ATA
+    try {
+      await checkInvariants([
+        { pubkey: wSolSecondaryStateAddress, isSigner: false, isWritable: true },
+        { pubkey: depositorUserKeyPair.publicKey, isSigner: false, isWritable: false },
+      ]).rpc()
+      expect.fail("should fail with invalid vault lst account")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidVaultLstAccount")
+    }
+
+    // anyone can check, but only the operator or a pause authority can pause deposits
+    try {
+      await program.methods.checkInvariants(true)
+        .accounts({
+          caller: depositorUserKeyPair.publicKey,
+          mainState: mainStateKeyPair.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: wSolSecondaryStateAddress, isSigner: false, isWritable: true },
+          { pubkey: vaultWSolAta, isSigner: false, isWritable: false },
+        ])
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with InvalidPauseAuthority")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPauseAuthority")
+    }
+    await program.methods.checkInvariants(false)
+      .accounts({
+        caller: depositorUserKeyPair.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .remainingAccounts([
+        { pubkey: wSolSecondaryStateAddress, isSigner: false, isWritable: false },
+        { pubkey: vaultWSolAta, isSigner: false, isWritable: false },
+      ])
+      .signers([depositorUserKeyPair])
+      .rpc()
+
+    const result = await checkInvariants([
+      { pubkey: wSolSecondaryStateAddress, isSigner: false, isWritable: true },
+      { pubkey: vaultWSolAta, isSigner: false, isWritable: false },
+    ]).simulate()
+    const report = result.events.find(e => e.name == "checkInvariantsEvent").data
+    const mainState = await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey);
+    expect(report.vaultsCount).to.eql(1);
+    expect(report.vaultsTotalSolValue.toString())
+      .to.eql(mainState.backingSolValue.add(mainState.outstandingTicketsSolValue).toString());
+    expect(report.mainInvariantChecked).to.eql(true);
+    expect(report.mainInvariantOk).to.eql(true);
+    expect(report.violatingVaults).to.eql([]);
+    expect(report.staleVaults).to.eql([]);
+    expect(report.depositsPaused).to.eql(false);
+  });
+
+  it("emergency pause", async () => {
 
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+
+    const pauseAuthorityKeyPair = Keypair.generate()
+    await program.methods.configurePauseAuthority(pauseAuthorityKeyPair.publicKey)
+      .accounts({
+        admin: wallet.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .rpc()
+
+    // only pause_authority or admin can pause
+    try {
+      await program.methods.pause(false)
+        .accounts({
+          authority: depositorUserKeyPair.publicKey,
+          mainState: mainStateKeyPair.publicKey,
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with InvalidPauseAuthority")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPauseAuthority")
+    }
+
+    console.log("pause")
+    await program.methods.pause(false)
+      .accounts({
+        authority: pauseAuthorityKeyPair.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .signers([pauseAuthorityKeyPair])
+      .rpc()
+    {
+      const mainState = await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey);
+      expect(mainState.paused).to.eql(true);
+      expect(mainState.ticketClaimsPaused).to.eql(false);
+    }
+
+    // unstake is blocked
+    try {
+      await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+      expect.fail("should fail with ProgramPaused")
+    } catch (ex) {
+      expect(ex.message).to.contain("ProgramPaused")
+    }
+
+    console.log("unpause")
+    await program.methods.unpause()
+      .accounts({
+        authority: pauseAuthorityKeyPair.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .signers([pauseAuthorityKeyPair])
+      .rpc()
+    {
+      const mainState = await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey);
+      expect(mainState.paused).to.eql(false);
+    }
+
+    // unstake works again
+    await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+
+    // clear the pause authority, only the admin can pause
+    await program.methods.configurePauseAuthority(PublicKey.default)
+      .accounts({
+        admin: wallet.publicKey,
+        mainState: mainStateKeyPair.publicKey,
+      })
+      .rpc()
+    expect((await program.account.mainVaultState.fetch(mainStateKeyPair.publicKey)).pauseAuthority.toBase58())
+      .to.eql(PublicKey.default.toBase58());
+    try {
+      await program.methods.pause(false)
+        .accounts({
+          authority: pauseAuthorityKeyPair.publicKey,
+          mainState: mainStateKeyPair.publicKey,
+        })
+        .signers([pauseAuthorityKeyPair])
+        .rpc()
+      expect.fail("should fail with InvalidPauseAuthority")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPauseAuthority")
+    }
+  });
+
+  it("two-step admin & operator handover", async () => {
+
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+
+    // ---- operator_auth
+    const newOperatorKeyPair = Keypair.generate()
+    await program.methods.proposeOperatorAuth(newOperatorKeyPair.publicKey)
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.pendingOperatorAuth).to.eql(newOperatorKeyPair.publicKey);
+      // not changed until accepted
+      expect(state.operatorAuth).to.eql(operatorAuthKeyPair.publicKey);
+    }
+
+    // only the pending key can accept
+    try {
+      await program.methods.acceptOperatorAuth()
+        .accounts({ newOperatorAuth: depositorUserKeyPair.publicKey, mainState })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with NotThePendingAuthority")
+    } catch (ex) {
+      expect(ex.message).to.contain("NotThePendingAuthority")
+    }
+
+    // cancel, then accept fails
+    await program.methods.cancelOperatorAuthProposal()
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    try {
+      await program.methods.acceptOperatorAuth()
+        .accounts({ newOperatorAuth: newOperatorKeyPair.publicKey, mainState })
+        .signers([newOperatorKeyPair])
+        .rpc()
+      expect.fail("should fail with NotThePendingAuthority")
+    } catch (ex) {
+      expect(ex.message).to.contain("NotThePendingAuthority")
+    }
+
+    // propose again & accept
+    await program.methods.proposeOperatorAuth(newOperatorKeyPair.publicKey)
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    await program.methods.acceptOperatorAuth()
+      .accounts({ newOperatorAuth: newOperatorKeyPair.publicKey, mainState })
+      .signers([newOperatorKeyPair])
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.operatorAuth).to.eql(newOperatorKeyPair.publicKey);
+      expect(state.pendingOperatorAuth).to.eql(PublicKey.default);
+    }
+
+    // ---- admin
+    const newAdminKeyPair = Keypair.generate()
+    await program.methods.proposeAdmin(newAdminKeyPair.publicKey)
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    await program.methods.acceptAdmin()
+      .accounts({ newAdmin: newAdminKeyPair.publicKey, mainState })
+      .signers([newAdminKeyPair])
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.admin).to.eql(newAdminKeyPair.publicKey);
+      expect(state.pendingAdmin).to.eql(PublicKey.default);
+    }
+
+    // the previous admin lost its rights
+    try {
+      await program.methods.proposeAdmin(wallet.publicKey)
+        .accounts({ admin: wallet.publicKey, mainState })
+        .rpc()
+      expect.fail("should fail, not the admin")
+    } catch (ex) {
+      expect(ex.message).to.contain("ConstraintHasOne")
+    }
+  });
+
+  it("timelocked config changes", async () => {
+
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const statePre = await program.account.mainVaultState.fetch(mainState);
+
+    // the change is queued, not applied
+    await program.methods.configureWithdrawalFee(50)
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.withdrawFeeBp).to.eql(statePre.withdrawFeeBp);
+      expect(state.pendingWithdrawFeeBp.value).to.eql(50);
+      const delaySeconds = idlConstant(program.idl, "configChangeDelayHours") * 60 * 60
+      const expectedEffective = new Date().getTime() / 1000 + delaySeconds
+      expect(state.pendingWithdrawFeeBp.effectiveTimestamp.toNumber())
+        .to.be.greaterThan(expectedEffective - 10).and.lessThan(expectedEffective + 10);
+    }
+
+    // permissionless apply fails before the effective timestamp
+    try {
+      await program.methods.applyPendingConfig()
+        .accounts({ mainState })
+        .rpc()
+      expect.fail("should fail with PendingConfigNotEffectiveYet")
+    } catch (ex) {
+      expect(ex.message).to.contain("PendingConfigNotEffectiveYet")
+    }
+
+    // admin cancels
+    await program.methods.cancelPendingConfig({ withdrawFeeBp: {} })
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.pendingWithdrawFeeBp).to.eql(null);
+      expect(state.withdrawFeeBp).to.eql(statePre.withdrawFeeBp);
+    }
+    try {
+      await program.methods.cancelPendingConfig({ withdrawFeeBp: {} })
+        .accounts({ admin: wallet.publicKey, mainState })
+        .rpc()
+      expect.fail("should fail with NoPendingConfigChange")
+    } catch (ex) {
+      expect(ex.message).to.contain("NoPendingConfigChange")
+    }
+
+    // max values are checked when queuing
+    try {
+      await program.methods.configurePerformanceFee(idlConstant(program.idl, "maxPerformanceFeeBp") + 1)
+        .accounts({ admin: wallet.publicKey, mainState })
+        .rpc()
+      expect.fail("should fail with PerformanceFeeTooHigh")
+    } catch (ex) {
+      expect(ex.message).to.contain("PerformanceFeeTooHigh")
+    }
+  });
+
+  it("unstake waiting hours bounds, issued tickets keep their due timestamp", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const minHours = idlConstant(program.idl, "minUnstakeTicketWaitingHours")
+    const maxHours = idlConstant(program.idl, "maxUnstakeTicketWaitingHours")
+
+    for (const hours of [minHours - 1, maxHours + 1]) {
+      try {
+        await program.methods.configureUnstakeWaitingHours(hours)
+          .accounts({ admin: wallet.publicKey, mainState })
+          .rpc()
+        expect.fail(`${hours} should fail with UnstakeWaitingHoursOutOfBounds`)
+      } catch (ex) {
+        expect(ex.message).to.contain("UnstakeWaitingHoursOutOfBounds")
+      }
+    }
+
+    // ticket issued with the initial 48 hs
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+    const ticketPre = await program.account.unstakeTicket.fetch(ticketKeyPair.publicKey);
+
+    // decrease: queued too, new tickets still use the current value
+    await program.methods.configureUnstakeWaitingHours(minHours)
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.unstakeTicketWaitingHours).to.eql(48);
+      expect(state.pendingUnstakeTicketWaitingHours.value).to.eql(minHours);
+    }
+    await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+
+    // increase: queued, replaces the queued decrease
+    await program.methods.configureUnstakeWaitingHours(maxHours)
+      .accounts({ admin: wallet.publicKey, mainState })
+      .rpc()
+    {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      expect(state.unstakeTicketWaitingHours).to.eql(48);
+      expect(state.pendingUnstakeTicketWaitingHours.value).to.eql(maxHours);
+    }
+
+    // the ticket issued before keeps its due timestamp
+    const ticketAfter = await program.account.unstakeTicket.fetch(ticketKeyPair.publicKey);
+    expect(ticketAfter.ticketDueTimestamp.toString()).to.eql(ticketPre.ticketDueTimestamp.toString());
+  });
+
+  it("instant unstake from the vault liquidity buffer", async () => {
 
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const depositorAtaWSol = getAssociatedTokenAddressSync(wSolMint, depositorUserKeyPair.publicKey, true);
+
+    const unstakeInstantTx = (mpsolAmount: string, minLstAmount: string) =>
+      program.methods.unstakeInstant(new BN(mpsolAmount), new BN(minLstAmount))
+        .accounts({
+          mainState,
+          unstaker: depositorUserKeyPair.publicKey,
+          unstakerMpsolAccount: depositorMpSolAta,
+          mpsolMint: mpsolTokenMintKeyPair.publicKey,
+          treasuryMpsolAccount: depositorMpSolAta, // treasury not set
+          lstMint: wSolMint,
+          unstakerLstAccount: depositorAtaWSol,
+          vaultLstAccount: getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true),
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([depositorUserKeyPair])
+
+    // disabled until the admin sets a cap
+    try {
+      await unstakeInstantTx(1e9.toFixed(), "0").rpc()
+      expect.fail("should fail with InstantUnstakeDisabledInVault")
+    } catch (ex) {
+      expect(ex.message).to.contain("InstantUnstakeDisabledInVault")
+    }
+
+    const cap = 5e9
+    await program.methods.configureSecondaryVault({ depositsDisabled: null, tokenDepositCap: null, instantUnstakeCapPerEpoch: new BN(cap.toFixed()), priceEpochPolicy: null, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds: null })
+      .accounts({ admin: wallet.publicKey, mainState, lstMint: wSolMint })
+      .rpc()
+
+    const feeBp = (await program.account.mainVaultState.fetch(mainState)).instantUnstakeFeeBp
+    const expectedLst = 1e9 * (10000 - feeBp) / 10000
+
+    // slippage protection
+    try {
+      await unstakeInstantTx(1e9.toFixed(), (expectedLst + 1).toFixed()).rpc()
+      expect.fail("should fail with LstAmountBelowMinimum")
+    } catch (ex) {
+      expect(ex.message).to.contain("LstAmountBelowMinimum")
+    }
+
+    const wSolPre = BigInt(await getTokenAccountBalance(provider, depositorAtaWSol))
+    const mpSolPre = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    await unstakeInstantTx(1e9.toFixed(), expectedLst.toFixed()).rpc()
+    const wSolPost = BigInt(await getTokenAccountBalance(provider, depositorAtaWSol))
+    const mpSolPost = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    expect((mpSolPre - mpSolPost).toString()).to.eql(1e9.toFixed());
+    expect((wSolPost - wSolPre).toString()).to.eql(expectedLst.toFixed());
+    {
+      const vaultState = await program.account.secondaryVaultState.fetch(
+        PublicKey.findProgramAddressSync([mainState.toBuffer(), wSolMint.toBuffer()], program.programId)[0])
+      expect(vaultState.instantUnstakedInEpoch.toString()).to.eql(expectedLst.toFixed());
+    }
+
+    // per-epoch vault cap
+    try {
+      await unstakeInstantTx(cap.toFixed(), "0").rpc()
+      expect.fail("should fail with InstantUnstakeExceedsVaultCap")
+    } catch (ex) {
+      expect(ex.message).to.contain("InstantUnstakeExceedsVaultCap")
+    }
+  });
+
+  it("stake native SOL & claim as native SOL", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const vaultWSolAta = getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true)
+
+    await airdropLamports(provider, depositorUserKeyPair.publicKey);
+
+    const lamports = 2e9
+    const vaultPre = BigInt(await getTokenAccountBalance(provider, vaultWSolAta))
+    const mpSolPre = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    await program.methods.stakeSol(new BN(lamports.toFixed()), 0)
+      .accounts({
+        mainState,
+        vaultLstAccount: vaultWSolAta,
+        depositor: depositorUserKeyPair.publicKey,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        depositorMpsolAccount: depositorMpSolAta,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    const vaultPost = BigInt(await getTokenAccountBalance(provider, vaultWSolAta))
+    const mpSolPost = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    // the lamports are wrapped in the wSOL vault, mpSOL price is 1
+    expect((vaultPost - vaultPre).toString()).to.eql(lamports.toFixed());
+    expect((mpSolPost - mpSolPre).toString()).to.eql(lamports.toFixed());
+
+    // the native claim path goes through the same ticket checks
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+    try {
+      await program.methods.ticketClaimSol(new BN(1e10.toFixed()))
+        .accounts({
+          mainState,
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+          vaultLstAccount: vaultWSolAta,
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with TicketIsNotDueYet")
+    } catch (ex) {
+      expect(ex.message).to.contain("TicketIsNotDueYet")
+    }
+  });
+
+  it("ticket claim across multiple vaults", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const wSolVaultState = PublicKey.findProgramAddressSync([mainState.toBuffer(), wSolMint.toBuffer()], program.programId)[0]
+
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+    const wSolVaultAccounts = [
+      { pubkey: wSolVaultState, isSigner: false, isWritable: true },
+      { pubkey: wSolMint, isSigner: false, isWritable: false },
+      { pubkey: getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true), isSigner: false, isWritable: true },
+      { pubkey: getAssociatedTokenAddressSync(wSolMint, depositorUserKeyPair.publicKey, true), isSigner: false, isWritable: true },
+      // wSOL has no lst state, any account works as placeholder
+      { pubkey: wSolVaultState, isSigner: false, isWritable: false },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ]
+    const claimMultiTx = (solValueAmounts: BN[], vaultTuples: number = 1,
+      minLstAmounts: BN[] = solValueAmounts.map(() => new BN(0))) =>
+      program.methods.ticketClaimMulti(solValueAmounts, minLstAmounts)
+        .accounts({
+          mainState,
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+        })
+        .remainingAccounts(Array(vaultTuples).fill(wSolVaultAccounts).flat())
+        .signers([depositorUserKeyPair])
+
+    // one account tuple per amount
+    try {
+      await claimMultiTx([new BN(5e9.toFixed()), new BN(5e9.toFixed())]).rpc()
+      expect.fail("should fail with InvalidRemainingAccountsCount")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidRemainingAccountsCount")
+    }
+
+    // one min lst amount per amount
+    try {
+      await claimMultiTx([new BN(1e10.toFixed())], 1, []).rpc()
+      expect.fail("should fail with InvalidMinLstAmountsCount")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidMinLstAmountsCount")
+    }
+
+    // the ticket checks apply to the total
+    try {
+      await claimMultiTx([new BN(1e10.toFixed())]).rpc()
+      expect.fail("should fail with TicketIsNotDueYet")
+    } catch (ex) {
+      expect(ex.message).to.contain("TicketIsNotDueYet")
+    }
+
+    // an overflowing total is rejected, not a panic
+    try {
+      await claimMultiTx([new BN("18446744073709551615"), new BN(1)], 2).rpc()
+      expect.fail("should fail with NotEnoughSolValueInTicket")
+    } catch (ex) {
+      expect(ex.message).to.contain("NotEnoughSolValueInTicket")
+    }
+  });
+
+  it("transfer & split unstake tickets", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+    const ticketPre = await program.account.unstakeTicket.fetch(ticketKeyPair.publicKey);
+
+    // split & transfer create the next PDA ticket in the owner index
+    const buyerKeyPair = Keypair.generate()
+    const userTicketIndex = (owner: PublicKey) => PublicKey.findProgramAddressSync(
+      [mainState.toBuffer(), idlConstant(program.idl, "userTicketIndexSeed"), owner.toBuffer()], program.programId)[0]
+    const pdaTicket = (owner: PublicKey, seq: number) => PublicKey.findProgramAddressSync(
+      [mainState.toBuffer(), idlConstant(program.idl, "ticketSeed"), owner.toBuffer(), new BN(seq).toArrayLike(Buffer, "le", 8)],
+      program.programId)[0]
+    for (const beneficiary of [depositorUserKeyPair.publicKey, buyerKeyPair.publicKey]) {
+      await program.methods.createUserTicketIndex()
+        .accounts({ payer: wallet.publicKey, beneficiary, mainState })
+        .rpc()
+    }
+
+    // split: no dust tickets
+    const minMovement = new BN(idlConstant(program.idl, "minMovementLamports"))
+    try {
+      await program.methods.splitTicket(minMovement.subn(1))
+        .accounts({
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+          userTicketIndex: userTicketIndex(depositorUserKeyPair.publicKey),
+          newTicketAccount: pdaTicket(depositorUserKeyPair.publicKey, 0),
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with CantLeaveDustInTicket")
+    } catch (ex) {
+      expect(ex.message).to.contain("CantLeaveDustInTicket")
+    }
+
+    const newTicket = pdaTicket(depositorUserKeyPair.publicKey, 0)
+    await program.methods.splitTicket(new BN(4e9.toFixed()))
+      .accounts({
+        beneficiary: depositorUserKeyPair.publicKey,
+        ticketAccount: ticketKeyPair.publicKey,
+        userTicketIndex: userTicketIndex(depositorUserKeyPair.publicKey),
+        newTicketAccount: newTicket,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    {
+      const ticket = await program.account.unstakeTicket.fetch(ticketKeyPair.publicKey);
+      const splitTicket = await program.account.unstakeTicket.fetch(newTicket);
+      expect(ticket.ticketSolValue.toString()).to.eql(ticketPre.ticketSolValue.subn(4e9).toString());
+      expect(splitTicket.ticketSolValue.toString()).to.eql(4e9.toFixed());
+      expect(splitTicket.ticketDueTimestamp.toString()).to.eql(ticketPre.ticketDueTimestamp.toString());
+      expect(splitTicket.beneficiary).to.eql(depositorUserKeyPair.publicKey);
+      const index = await program.account.userTicketIndex.fetch(userTicketIndex(depositorUserKeyPair.publicKey));
+      expect(index.nextTicketSeq.toNumber()).to.eql(1);
+    }
+
+    // transfer the new ticket, it moves to the buyer index
+    const boughtTicket = pdaTicket(buyerKeyPair.publicKey, 0)
+    await program.methods.transferTicket(buyerKeyPair.publicKey)
+      .accounts({
+        beneficiary: depositorUserKeyPair.publicKey,
+        ticketAccount: newTicket,
+        newBeneficiaryTicketIndex: userTicketIndex(buyerKeyPair.publicKey),
+        newTicketAccount: boughtTicket,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    {
+      const ticket = await program.account.unstakeTicket.fetch(boughtTicket);
+      expect(ticket.beneficiary).to.eql(buyerKeyPair.publicKey);
+      expect(ticket.ticketSolValue.toString()).to.eql(4e9.toFixed());
+      expect(ticket.ticketDueTimestamp.toString()).to.eql(ticketPre.ticketDueTimestamp.toString());
+      expect(await program.account.unstakeTicket.fetchNullable(newTicket)).to.eql(null);
+      const index = await program.account.userTicketIndex.fetch(userTicketIndex(buyerKeyPair.publicKey));
+      expect(index.nextTicketSeq.toNumber()).to.eql(1);
+    }
+
+    // the old beneficiary can't move it anymore
+    try {
+      await program.methods.transferTicket(depositorUserKeyPair.publicKey)
+        .accounts({
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: boughtTicket,
+          newBeneficiaryTicketIndex: userTicketIndex(depositorUserKeyPair.publicKey),
+          newTicketAccount: pdaTicket(depositorUserKeyPair.publicKey, 1),
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with ConstraintHasOne")
+    } catch (ex) {
+      expect(ex.message).to.contain("ConstraintHasOne")
+    }
+  });
+
+  it("tokenized unstake tickets", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+
+    const metadataProgram = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s")
+    const ticketNftMint = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "ticketNftMintSeed"), ticketKeyPair.publicKey.toBuffer()], program.programId)[0]
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), metadataProgram.toBuffer(), ticketNftMint.toBuffer()], metadataProgram)
+    const [masterEdition] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), metadataProgram.toBuffer(), ticketNftMint.toBuffer(), Buffer.from("edition")], metadataProgram)
+    const holderNftAccount = getAssociatedTokenAddressSync(ticketNftMint, depositorUserKeyPair.publicKey)
+
+    await program.methods.tokenizeTicket()
+      .accounts({
+        beneficiary: depositorUserKeyPair.publicKey,
+        ticketAccount: ticketKeyPair.publicKey,
+        metadata,
+        masterEdition,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    {
+      const ticket = await program.account.unstakeTicket.fetch(ticketKeyPair.publicKey);
+      expect(ticket.beneficiary).to.eql(ticketNftMint);
+      expect(await getTokenAccountBalance(provider, holderNftAccount)).to.eql("1");
+    }
+
+    // the ticket now moves with the NFT, the old beneficiary can't use it directly
+    const otherBeneficiary = Keypair.generate().publicKey
+    await program.methods.createUserTicketIndex()
+      .accounts({ payer: wallet.publicKey, beneficiary: otherBeneficiary, mainState })
+      .rpc()
+    try {
+      await program.methods.transferTicket(otherBeneficiary)
+        .accounts({
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+          newBeneficiaryTicketIndex: PublicKey.findProgramAddressSync(
+            [mainState.toBuffer(), idlConstant(program.idl, "userTicketIndexSeed"), otherBeneficiary.toBuffer()],
+            program.programId)[0],
+          newTicketAccount: PublicKey.findProgramAddressSync(
+            [mainState.toBuffer(), idlConstant(program.idl, "ticketSeed"), otherBeneficiary.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+            program.programId)[0],
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with ConstraintHasOne")
+    } catch (ex) {
+      expect(ex.message).to.contain("ConstraintHasOne")
+    }
+
+    // the NFT holder claims, when due
+    try {
+      await program.methods.ticketClaimNft(new BN(1e10.toFixed()), new BN(0))
+        .accounts({
+          mainState,
+          holder: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+          holderNftAccount,
+          metadata,
+          masterEdition,
+          lstMint: wSolMint,
+          holderLstAccount: getAssociatedTokenAddressSync(wSolMint, depositorUserKeyPair.publicKey, true),
+          vaultLstAccount: getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true),
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with TicketIsNotDueYet")
+    } catch (ex) {
+      expect(ex.message).to.contain("TicketIsNotDueYet")
+    }
+
+    // unstake can mint the NFT ticket in the same instruction
+    const nftTicketKeyPair = Keypair.generate()
+    const unstakeNftMint = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "ticketNftMintSeed"), nftTicketKeyPair.publicKey.toBuffer()], program.programId)[0]
+    const unstakeNftAccount = getAssociatedTokenAddressSync(unstakeNftMint, depositorUserKeyPair.publicKey)
+    await program.methods.unstake(new BN(1e10.toFixed()))
+      .accounts({
+        mainState,
+        unstaker: depositorUserKeyPair.publicKey,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        unstakerMpsolAccount: depositorMpSolAta,
+        treasuryMpsolAccount: depositorMpSolAta, // treasury not set
+        newTicketAccount: nftTicketKeyPair.publicKey,
+        ticketNftMint: unstakeNftMint,
+        unstakerNftAccount: unstakeNftAccount,
+        metadata: PublicKey.findProgramAddressSync(
+          [Buffer.from("metadata"), metadataProgram.toBuffer(), unstakeNftMint.toBuffer()], metadataProgram)[0],
+        masterEdition: PublicKey.findProgramAddressSync(
+          [Buffer.from("metadata"), metadataProgram.toBuffer(), unstakeNftMint.toBuffer(), Buffer.from("edition")], metadataProgram)[0],
+        tokenMetadataProgram: metadataProgram,
+      })
+      .signers([depositorUserKeyPair, nftTicketKeyPair])
+      .rpc()
+    {
+      const ticket = await program.account.unstakeTicket.fetch(nftTicketKeyPair.publicKey);
+      expect(ticket.beneficiary).to.eql(unstakeNftMint);
+      expect(await getTokenAccountBalance(provider, unstakeNftAccount)).to.eql("1");
+    }
+
+    // the NFT mint without the rest of the NFT accounts is rejected
+    const partialTicketKeyPair = Keypair.generate()
+    const partialNftMint = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "ticketNftMintSeed"), partialTicketKeyPair.publicKey.toBuffer()], program.programId)[0]
+    try {
+      await program.methods.unstake(new BN(1e10.toFixed()))
+        .accounts({
+          mainState,
+          unstaker: depositorUserKeyPair.publicKey,
+          mpsolMint: mpsolTokenMintKeyPair.publicKey,
+          unstakerMpsolAccount: depositorMpSolAta,
+          treasuryMpsolAccount: depositorMpSolAta, // treasury not set
+          newTicketAccount: partialTicketKeyPair.publicKey,
+          ticketNftMint: partialNftMint,
+          unstakerNftAccount: getAssociatedTokenAddressSync(partialNftMint, depositorUserKeyPair.publicKey),
+          metadata: null,
+          masterEdition: null,
+        })
+        .signers([depositorUserKeyPair, partialTicketKeyPair])
+        .rpc()
+      expect.fail("should fail with MissingTicketNftAccounts")
+    } catch (ex) {
+      expect(ex.message).to.contain("MissingTicketNftAccounts")
+    }
+  });
+
+  it("cancel an unstake ticket and re-stake it", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const mpsolMint = mpsolTokenMintKeyPair.publicKey
+    const mpSolPriceP9 = async () => {
+      const state = await program.account.mainVaultState.fetch(mainState);
+      return state.backingSolValue.mul(new BN(ONE_E9)).div(new BN(await getTokenMintSupply(provider, mpsolMint)))
+    }
+    const cancelTicket = (ticketAccount: PublicKey) => program.methods.cancelTicket()
+      .accounts({
+        mainState,
+        beneficiary: depositorUserKeyPair.publicKey,
+        ticketAccount,
+        mpsolMint,
+        beneficiaryMpsolAccount: depositorMpSolAta,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+    const ticket = await program.account.unstakeTicket.fetch(ticketKeyPair.publicKey);
+    // no treasury configured, no withdraw fee charged
+    expect(ticket.mpsolAmount.toString()).to.eql(1e10.toFixed());
+    const mainStatePre = await program.account.mainVaultState.fetch(mainState);
+    const mpSolPre = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+
+    await cancelTicket(ticketKeyPair.publicKey)
+
+    const mainStatePost = await program.account.mainVaultState.fetch(mainState);
+    const mpSolPost = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    // mpSOL price is 1
+    expect((mpSolPost - mpSolPre).toString()).to.eql(ticket.ticketSolValue.toString());
+    expect(mainStatePost.outstandingTicketsSolValue.toString()).to.eql(
+      mainStatePre.outstandingTicketsSolValue.sub(ticket.ticketSolValue).toString());
+    expect(mainStatePost.backingSolValue.toString()).to.eql(
+      mainStatePre.backingSolValue.add(ticket.ticketSolValue).toString());
+    // ticket closed
+    expect(await program.account.unstakeTicket.fetchNullable(ticketKeyPair.publicKey)).to.eql(null);
+
+    // unstake, then a strategy loss: the ticket keeps its sol-value, the mpSOL price falls
+    const lossTicketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 48)
+    const lossTicket = await program.account.unstakeTicket.fetch(lossTicketKeyPair.publicKey);
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const stratStateKeyPair = Keypair.generate()
+    const [stratAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "authoritySeed"), stratStateKeyPair.publicKey.toBuffer()],
+      mockStrategyProgram.programId)
+    await mockStrategyProgram.methods.initialize()
+      .accounts({ payer: wallet.publicKey, state: stratStateKeyPair.publicKey, lstMint: wSolMint, tokenProgram: TOKEN_PROGRAM_ID })
+      .signers([stratStateKeyPair])
+      .rpc()
+    await program.methods.attachCommonStrategyState()
+      .accounts({
+        admin: wallet.publicKey,
+        mainState,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyProgramCode: mockStrategyProgram.programId,
+      })
+      .rpc()
+    const [vaultStratWithdrawAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "vaultStratWithdrawAtaAuthSeed"), stratStateKeyPair.publicKey.toBuffer()],
+      program.programId)
+    const lstWithdrawAccount = await createAta(provider, wallet, wSolMint, vaultStratWithdrawAuth, true)
+    await program.methods.transferLstToStrat(new BN(5e10.toFixed()))
+      .accounts({
+        mainState,
+        operatorAuth: operatorAuthKeyPair.publicKey,
+        lstMint: wSolMint,
+        vaultLstAccount: getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true),
+        strategyProgramCode: mockStrategyProgram.programId,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([operatorAuthKeyPair])
+      .rpc()
+    await mockStrategyProgram.methods.slash(new BN(1e10.toFixed()))
+      .accounts({
+        state: stratStateKeyPair.publicKey,
+        lstMint: wSolMint,
+        destination: await createAta(provider, wallet, wSolMint, Keypair.generate().publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc()
+    await testGetUpdateVaultPriceMethod(mainStateKeyPair, "wSOL", WSOL_TOKEN_MINT).rpc()
+    await program.methods.updateAttachedStratLstAmount()
+      .accounts({
+        mainState,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyDepositAccount: getAssociatedTokenAddressSync(wSolMint, stratAuth, true),
+        lstWithdrawAccount,
+        mpsolMint,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+        treasuryMpsolAccount: Keypair.generate().publicKey, // no treasury configured
+      })
+      .rpc()
+    const priceAfterLoss = await mpSolPriceP9()
+    expect(priceAfterLoss.lt(new BN(ONE_E9))).to.eql(true);
+
+    // cancelling after the loss mints back only the mpSOL burned, the mpSOL price does not fall
+    const mpSolPreLossCancel = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    await cancelTicket(lossTicketKeyPair.publicKey)
+    const mpSolPostLossCancel = BigInt(await getTokenAccountBalance(provider, depositorMpSolAta))
+    expect((mpSolPostLossCancel - mpSolPreLossCancel).toString()).to.eql(lossTicket.mpsolAmount.toString());
+    expect((await mpSolPriceP9()).gte(priceAfterLoss)).to.eql(true);
+  });
+
+  it("PDA unstake tickets with a per-user index", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const beneficiary = depositorUserKeyPair.publicKey
+    await airdropLamports(provider, beneficiary);
+
+    const userTicketIndex = PublicKey.findProgramAddressSync(
+      [mainState.toBuffer(), idlConstant(program.idl, "userTicketIndexSeed"), beneficiary.toBuffer()], program.programId)[0]
+    const pdaTicket = (seq: number) => PublicKey.findProgramAddressSync(
+      [mainState.toBuffer(), idlConstant(program.idl, "ticketSeed"), beneficiary.toBuffer(), new BN(seq).toArrayLike(Buffer, "le", 8)],
+      program.programId)[0]
+
+    // anyone can pay for the index
+    await program.methods.createUserTicketIndex()
+      .accounts({ payer: wallet.publicKey, beneficiary, mainState })
+      .rpc()
+
+    for (const seq of [0, 1]) {
+      await program.methods.unstakePdaTicket(new BN(1e10.toFixed()))
+        .accounts({
+          mainState,
+          unstaker: beneficiary,
+          unstakerMpsolAccount: depositorMpSolAta,
+          mpsolMint: mpsolTokenMintKeyPair.publicKey,
+          treasuryMpsolAccount: depositorMpSolAta, // treasury not set
+          newTicketAccount: pdaTicket(seq),
+        })
+        .signers([depositorUserKeyPair])
+        .rpc()
+    }
+
+    // enumerate the user tickets from the index
+    const index = await program.account.userTicketIndex.fetch(userTicketIndex);
+    expect(index.nextTicketSeq.toNumber()).to.eql(2);
+    const tickets = await program.account.unstakeTicket.fetchMultiple(
+      [...Array(index.nextTicketSeq.toNumber()).keys()].map(pdaTicket))
+    for (const ticket of tickets) {
+      expect(ticket.beneficiary).to.eql(beneficiary);
+      expect(ticket.ticketSolValue.toString()).to.eql(1e10.toFixed());
+    }
+  });
+
+  it("vault price sources are stored and enforced", async () => {
+
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const jitoSolMint = new PublicKey(JITO_SOL_TOKEN_MINT)
+    const vaultJitoSolAta = getAssociatedTokenAddressSync(jitoSolMint, vaultAtaAuth, true)
+    const jitoSolVaultState = PublicKey.findProgramAddressSync(
+      [mainState.toBuffer(), jitoSolMint.toBuffer()], program.programId)[0]
+    const stateAccount = (address: string) => [{ pubkey: new PublicKey(address), isSigner: false, isWritable: false }]
+
+    // the Marinade adapter does not price jitoSOL
+    try {
+      await program.methods.createSecondaryVault({ marinade: {} }, new PublicKey(MARINADE_STATE_ADDRESS))
+        .accounts({
+          admin: wallet.publicKey,
+          mainState,
+          lstMint: jitoSolMint,
+          secondaryState: jitoSolVaultState,
+          vaultLstAccount: vaultJitoSolAta,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(stateAccount(MARINADE_STATE_ADDRESS))
+        .preInstructions([createAssociatedTokenAccountInstruction(
+          wallet.publicKey, vaultJitoSolAta, vaultAtaAuth, jitoSolMint)])
+        .rpc()
+      expect.fail("should fail with InvalidPriceSourceLstMint")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPriceSourceLstMint")
+    }
+
+    await testCreateSecondaryVault(mainStateKeyPair, "JitoSOL", JITO_SOL_TOKEN_MINT,
+      { splStakePool: {} }, JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS);
+
+    // only the stored state account is accepted
+    try {
+      await program.methods.updateVaultTokenSolPrice()
+        .accounts({ mainState, lstMint: jitoSolMint })
+        .remainingAccounts(stateAccount(MARINADE_STATE_ADDRESS))
+        .rpc()
+      expect.fail("should fail with InvalidPriceSourceState")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPriceSourceState")
+    }
+    await program.methods.updateVaultTokenSolPrice()
+      .accounts({ mainState, lstMint: jitoSolMint })
+      .remainingAccounts(stateAccount(JITO_SOL_SPL_STAKE_POOL_STATE_ADDRESS))
+      .rpc()
+
+    // a stored price source can not be changed
+    try {
+      await program.methods.configureVaultPriceSource({ marinade: {} }, new PublicKey(MARINADE_STATE_ADDRESS))
+        .accounts({ admin: wallet.publicKey, mainState, lstMint: jitoSolMint })
+        .remainingAccounts(stateAccount(MARINADE_STATE_ADDRESS))
+        .rpc()
+      expect.fail("should fail with PriceSourceAlreadySet")
+    } catch (ex) {
+      expect(ex.message).to.contain("PriceSourceAlreadySet")
+    }
+  });
+
+  it("token-2022 lst mints: custody-breaking extensions are rejected", async () => {
+
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+
+    // creates a 9-decimals token-2022 mint with the given extensions
+    const createToken2022Mint = async (extensions: ExtensionType[], initExtensionIxs: (mint: PublicKey) => TransactionInstruction[]) => {
+      const mintKeyPair = Keypair.generate()
+      const mintLen = getMintLen(extensions)
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: mintKeyPair.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        ...initExtensionIxs(mintKeyPair.publicKey),
+        createInitializeMintInstruction(mintKeyPair.publicKey, 9, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID),
+      )
+      await provider.sendAndConfirm(tx, [mintKeyPair])
+      return mintKeyPair.publicKey
+    }
+    const createVaultTx = (lstMint: PublicKey) => {
+      const vaultLstAccount = getAssociatedTokenAddressSync(lstMint, vaultAtaAuth, true, TOKEN_2022_PROGRAM_ID)
+      return program.methods.createSecondaryVault({ wsol: {} }, PublicKey.default)
+        .accounts({
+          admin: wallet.publicKey,
+          mainState,
+          lstMint,
+          secondaryState: PublicKey.findProgramAddressSync([mainState.toBuffer(), lstMint.toBuffer()], program.programId)[0],
+          vaultLstAccount,
+          lstTokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .preInstructions([createAssociatedTokenAccountInstruction(
+          wallet.publicKey, vaultLstAccount, vaultAtaAuth, lstMint, TOKEN_2022_PROGRAM_ID)])
+    }
+
+    const permanentDelegateMint = await createToken2022Mint([ExtensionType.PermanentDelegate],
+      (mint) => [createInitializePermanentDelegateInstruction(mint, wallet.publicKey, TOKEN_2022_PROGRAM_ID)])
+    try {
+      await createVaultTx(permanentDelegateMint).rpc()
+      expect.fail("should fail with LstMintHasPermanentDelegate")
+    } catch (ex) {
+      expect(ex.message).to.contain("LstMintHasPermanentDelegate")
+    }
+
+    const nonTransferableMint = await createToken2022Mint([ExtensionType.NonTransferable],
+      (mint) => [createInitializeNonTransferableMintInstruction(mint, TOKEN_2022_PROGRAM_ID)])
+    try {
+      await createVaultTx(nonTransferableMint).rpc()
+      expect.fail("should fail with LstMintIsNonTransferable")
+    } catch (ex) {
+      expect(ex.message).to.contain("LstMintIsNonTransferable")
+    }
+
+    // a transfer fee keeps the custody intact, the mint passes the extension checks
+    // and fails later only because the wSOL price adapter does not price it
+    const transferFeeMint = await createToken2022Mint([ExtensionType.TransferFeeConfig],
+      (mint) => [createInitializeTransferFeeConfigInstruction(
+        mint, wallet.publicKey, wallet.publicKey, 50, BigInt(1e9), TOKEN_2022_PROGRAM_ID)])
+    try {
+      await createVaultTx(transferFeeMint).rpc()
+      expect.fail("should fail with InvalidPriceSourceLstMint")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPriceSourceLstMint")
+    }
+  });
+
+  for (const testLst of TEST_LSTS) {
+    it(`lst mint with ${testLst.decimals} decimals: stake, strategy & claim`, async () => {
+
+      // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+      const mpsolTokenMintKeyPair = Keypair.generate()
+      const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+      const mainState = mainStateKeyPair.publicKey
+      const lstMint = new PublicKey(testLst.mint)
+      const oneLst = new BN(10).pow(new BN(testLst.decimals))
+      const lstState = [{ pubkey: new PublicKey(testLst.stakePoolState), isSigner: false, isWritable: false }]
+      const vaultLstAta = getAssociatedTokenAddressSync(lstMint, vaultAtaAuth, true)
+
+      const vaultState = await testCreateSecondaryVault(mainStateKeyPair, `test-LST-${testLst.decimals}`, testLst.mint,
+        { splStakePool: {} }, testLst.stakePoolState);
+      await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy: null, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds: null })
+        .accounts({ admin: wallet.publicKey, mainState, lstMint })
+        .rpc()
+
+      // the price is per whole token
+      await program.methods.updateVaultTokenSolPrice()
+        .accounts({ mainState, lstMint })
+        .remainingAccounts(lstState)
+        .rpc()
+      expect((await program.account.secondaryVaultState.fetch(vaultState)).lstSolPriceP32.toString()).to.eql(TEST_LST_SOL_PRICE_P32);
+
+      // stake 100 LST => 125 SOL
+      const depositorLstAta = await mintTokens(provider, wallet, lstMint, depositorUserKeyPair.publicKey, oneLst.muln(1000).toNumber())
+      const amountStaked = oneLst.muln(100)
+      const mainStatePreStake = await program.account.mainVaultState.fetch(mainState);
+      await program.methods.stake(amountStaked, 0)
+        .accounts({
+          mainState,
+          lstMint,
+          vaultLstAccount: vaultLstAta,
+          depositor: depositorUserKeyPair.publicKey,
+          depositorLstAccount: depositorLstAta,
+          mpsolMint: mpsolTokenMintKeyPair.publicKey,
+          depositorMpsolAccount: depositorMpSolAta,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(lstState)
+        .signers([depositorUserKeyPair])
+        .rpc()
+      const mainStateAfterStake = await program.account.mainVaultState.fetch(mainState);
+      expect(mainStateAfterStake.backingSolValue.sub(mainStatePreStake.backingSolValue).toString()).to.eql(125e9.toFixed());
+      {
+        const vault = await program.account.secondaryVaultState.fetch(vaultState);
+        expect(vault.locallyStoredAmount.toString()).to.eql(amountStaked.toString());
+        expect(vault.vaultTotalLstAmount.toString()).to.eql(amountStaked.toString());
+      }
+
+      // strategy: move 40 LST to a mock strategy, slash 10 LST => 12.5 SOL loss
+      const stratStateKeyPair = Keypair.generate()
+      const [stratAuth] = PublicKey.findProgramAddressSync(
+        [idlConstant(program.idl, "authoritySeed"), stratStateKeyPair.publicKey.toBuffer()],
+        mockStrategyProgram.programId)
+      await mockStrategyProgram.methods.initialize()
+        .accounts({ payer: wallet.publicKey, state: stratStateKeyPair.publicKey, lstMint, tokenProgram: TOKEN_PROGRAM_ID })
+        .signers([stratStateKeyPair])
+        .rpc()
+      await program.methods.attachCommonStrategyState()
+        .accounts({
+          admin: wallet.publicKey,
+          mainState,
+          lstMint,
+          commonStrategyState: stratStateKeyPair.publicKey,
+          strategyProgramCode: mockStrategyProgram.programId,
+        })
+        .rpc()
+      const [vaultStratWithdrawAuth] = PublicKey.findProgramAddressSync(
+        [idlConstant(program.idl, "vaultStratWithdrawAtaAuthSeed"), stratStateKeyPair.publicKey.toBuffer()],
+        program.programId)
+      const lstWithdrawAccount = await createAta(provider, wallet, lstMint, vaultStratWithdrawAuth, true)
+      await program.methods.transferLstToStrat(oneLst.muln(40))
+        .accounts({
+          mainState,
+          operatorAuth: operatorAuthKeyPair.publicKey,
+          lstMint,
+          vaultLstAccount: vaultLstAta,
+          strategyProgramCode: mockStrategyProgram.programId,
+          commonStrategyState: stratStateKeyPair.publicKey,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operatorAuthKeyPair])
+        .rpc()
+      await mockStrategyProgram.methods.slash(oneLst.muln(10))
+        .accounts({
+          state: stratStateKeyPair.publicKey,
+          lstMint,
+          destination: await createAta(provider, wallet, lstMint, Keypair.generate().publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc()
+      await program.methods.updateVaultTokenSolPrice()
+        .accounts({ mainState, lstMint })
+        .remainingAccounts(lstState)
+        .rpc()
+      const mainStatePreLoss = await program.account.mainVaultState.fetch(mainState);
+      await program.methods.updateAttachedStratLstAmount()
+        .accounts({
+          mainState,
+          lstMint,
+          commonStrategyState: stratStateKeyPair.publicKey,
+          strategyDepositAccount: getAssociatedTokenAddressSync(lstMint, stratAuth, true),
+          lstWithdrawAccount,
+          mpsolMint: mpsolTokenMintKeyPair.publicKey,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+          treasuryMpsolAccount: Keypair.generate().publicKey, // no treasury configured
+        })
+        .rpc()
+      const mainStateAfterLoss = await program.account.mainVaultState.fetch(mainState);
+      expect(mainStatePreLoss.backingSolValue.sub(mainStateAfterLoss.backingSolValue).toString()).to.eql(12.5e9.toFixed());
+
+      // claim 1 SOL of a ticket => 0.8 LST
+      await config0hsWaitTime(mainStateKeyPair);
+      const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 0)
+      const lstBalancePre = new BN(await getTokenAccountBalance(provider, depositorLstAta))
+      const claimTx = (minLstAmount: BN) => program.methods.ticketClaim(new BN(1e9.toFixed()), minLstAmount)
+        .accounts({
+          mainState,
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+          lstMint,
+          beneficiaryLstAccount: depositorLstAta,
+          vaultLstAccount: vaultLstAta,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(lstState)
+        .signers([depositorUserKeyPair])
+      try {
+        await claimTx(oneLst.muln(8).divn(10).addn(1)).rpc()
+        expect.fail("should fail with LstAmountBelowMinimum")
+      } catch (ex) {
+        expect(ex.message).to.contain("LstAmountBelowMinimum")
+      }
+      await claimTx(oneLst.muln(8).divn(10)).rpc()
+      const lstReceived = new BN(await getTokenAccountBalance(provider, depositorLstAta)).sub(lstBalancePre)
+      expect(lstReceived.toString()).to.eql(oneLst.muln(8).divn(10).toString());
+      {
+        const vault = await program.account.secondaryVaultState.fetch(vaultState);
+        expect(vault.vaultTotalLstAmount.toString()).to.eql(oneLst.muln(100 - 10).sub(lstReceived).toString());
+      }
+    });
+  }
+
+  it("price epoch policy: outdated stake-pool prices", async () => {
+
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const testLst = TEST_LSTS[0]
+    const lstMint = new PublicKey(testLst.mint)
+    const lstState = [{ pubkey: new PublicKey(TEST_LST_OUTDATED_STAKE_POOL_STATE), isSigner: false, isWritable: false }]
+    const configurePriceEpochPolicy = (priceEpochPolicy: any) =>
+      program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds: null })
+        .accounts({ admin: wallet.publicKey, mainState, lstMint })
+        .rpc()
+    const updatePrice = () => program.methods.updateVaultTokenSolPrice()
+      .accounts({ mainState, lstMint })
+      .remainingAccounts(lstState)
+      .rpc()
+
+    // the pool was last updated in epoch 0, wait for the test-validator to leave it
+    while ((await provider.connection.getEpochInfo()).epoch == 0) {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+
+    const vaultState = await testCreateSecondaryVault(mainStateKeyPair, "test-LST-outdated", testLst.mint,
+      { splStakePool: {} }, TEST_LST_OUTDATED_STAKE_POOL_STATE);
+    expect((await program.account.secondaryVaultState.fetch(vaultState)).priceEpochPolicy).to.eql({ reject: {} });
+
+    // default policy: the price is rejected
+    await configurePriceEpochPolicy(null)
+    try {
+      await updatePrice()
+      expect.fail("should fail with LstPriceSourceNotUpdatedThisEpoch")
+    } catch (ex) {
+      expect(ex.message).to.contain("LstPriceSourceNotUpdatedThisEpoch")
+    }
+
+    // mark outdated: the price is stored, but deposits are rejected
+    await configurePriceEpochPolicy({ markOutdated: {} })
+    await updatePrice()
+    {
+      const vault = await program.account.secondaryVaultState.fetch(vaultState)
+      expect(vault.lstSolPriceP32.toString()).to.eql(TEST_LST_SOL_PRICE_P32);
+      expect(vault.lstSolPriceOutdated).to.eql(true);
+    }
+    const depositorLstAta = await mintTokens(provider, wallet, lstMint, depositorUserKeyPair.publicKey, 1e9)
+    const stakeTx = () => program.methods.stake(new BN(1e8.toFixed()), 0)
+      .accounts({
+        mainState,
+        lstMint,
+        vaultLstAccount: getAssociatedTokenAddressSync(lstMint, vaultAtaAuth, true),
+        depositor: depositorUserKeyPair.publicKey,
+        depositorLstAccount: depositorLstAta,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        depositorMpsolAccount: depositorMpSolAta,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(lstState)
+      .signers([depositorUserKeyPair])
+      .rpc()
+    try {
+      await stakeTx()
+      expect.fail("should fail with LstSolPriceIsOutdated")
+    } catch (ex) {
+      expect(ex.message).to.contain("LstSolPriceIsOutdated")
+    }
+
+    // ignore: the pool epoch is not checked
+    await configurePriceEpochPolicy({ ignore: {} })
+    await stakeTx()
+    expect((await program.account.secondaryVaultState.fetch(vaultState)).lstSolPriceOutdated).to.eql(false);
+  });
+
+  it("price circuit breaker: configure limits & acknowledge", async () => {
+
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const testLst = TEST_LSTS[0]
+    const lstMint = new PublicKey(testLst.mint)
+    const lstState = [{ pubkey: new PublicKey(testLst.stakePoolState), isSigner: false, isWritable: false }]
+    const configurePriceLimits = (maxPriceIncreaseBp: number | null, maxPriceDecreaseBp: number | null) =>
+      program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy: null, maxPriceIncreaseBp, maxPriceDecreaseBp, maxPriceAgeSeconds: null })
+        .accounts({ admin: wallet.publicKey, mainState, lstMint })
+        .rpc()
+
+    const vaultState = await testCreateSecondaryVault(mainStateKeyPair, "test-LST-circuit-breaker", testLst.mint,
+      { splStakePool: {} }, testLst.stakePoolState);
+    {
+      const vault = await program.account.secondaryVaultState.fetch(vaultState)
+      expect(vault.maxPriceIncreaseBp).to.eql(0);
+      expect(vault.maxPriceDecreaseBp).to.eql(0);
+      expect(vault.priceFrozen).to.eql(false);
+    }
+
+    // limits over 100% are rejected
+    try {
+      await configurePriceLimits(10001, null)
+      expect.fail("should fail with InvalidPriceChangeLimit")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidPriceChangeLimit")
+    }
+    await configurePriceLimits(100, 50)
+    {
+      const vault = await program.account.secondaryVaultState.fetch(vaultState)
+      expect(vault.maxPriceIncreaseBp).to.eql(100);
+      expect(vault.maxPriceDecreaseBp).to.eql(50);
+    }
+
+    // the pool price does not move: the update is applied & sets the epoch reference
+    await program.methods.updateVaultTokenSolPrice()
+      .accounts({ mainState, lstMint })
+      .remainingAccounts(lstState)
+      .rpc()
+    {
+      const vault = await program.account.secondaryVaultState.fetch(vaultState)
+      expect(vault.priceFrozen).to.eql(false);
+      expect(vault.lstSolPriceP32.toString()).to.eql(TEST_LST_SOL_PRICE_P32);
+      expect(vault.priceReferenceP32.toString()).to.eql(TEST_LST_SOL_PRICE_P32);
+    }
+
+    // nothing to acknowledge
+    try {
+      await program.methods.acknowledgeVaultPrice()
+        .accounts({ admin: wallet.publicKey, mainState, lstMint })
+        .rpc()
+      expect.fail("should fail with VaultPriceNotFrozen")
+    } catch (ex) {
+      expect(ex.message).to.contain("VaultPriceNotFrozen")
+    }
+  });
+
+  it("price circuit breaker: a pool price jump freezes the vault until acknowledged", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+
+    // a stake-pool priced at 1 SOL, the manager holds the 1e9 initial pool tokens
+    const pool = await createTestSplStakePool(1e9)
+    const lstMint = pool.poolMint
+    const lstState = [{ pubkey: pool.stakePool, isSigner: false, isWritable: false }]
+    const vaultLstAta = getAssociatedTokenAddressSync(lstMint, vaultAtaAuth, true)
+    const vaultState = await testCreateSecondaryVault(mainStateKeyPair, "test-pool-circuit-breaker", lstMint.toBase58(),
+      { splStakePool: {} }, pool.stakePool.toBase58());
+    await program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch: null, priceEpochPolicy: null, maxPriceIncreaseBp: 100, maxPriceDecreaseBp: 50, maxPriceAgeSeconds: null })
+      .accounts({ admin: wallet.publicKey, mainState, lstMint })
+      .rpc()
+    const updatePrice = () => program.methods.updateVaultTokenSolPrice()
+      .accounts({ mainState, lstMint })
+      .remainingAccounts(lstState)
+      .rpc()
+    await updatePrice()
+    const vaultPre = await program.account.secondaryVaultState.fetch(vaultState)
+    expect(vaultPre.lstSolPriceP32.toString()).to.eql(TWO_POW_32);
+    expect(vaultPre.priceReferenceP32.toString()).to.eql(TWO_POW_32);
+
+    // stake 0.5 pool tokens, so the vault has liquidity for claims
+    const depositorLstAta = await createAta(provider, wallet, lstMint, depositorUserKeyPair.publicKey)
+    await sendTx(provider, wallet, [createTransferInstruction(pool.managerPoolAccount, depositorLstAta, wallet.publicKey, 5e8)])
+    const stakeTx = () => program.methods.stake(new BN(1e8.toFixed()), 0)
+      .accounts({
+        mainState,
+        lstMint,
+        vaultLstAccount: vaultLstAta,
+        depositor: depositorUserKeyPair.publicKey,
+        depositorLstAccount: depositorLstAta,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        depositorMpsolAccount: depositorMpSolAta,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(lstState)
+      .signers([depositorUserKeyPair])
+      .rpc()
+    await stakeTx()
+
+    // +5% pool price, over the 1% limit
+    await raiseTestSplStakePoolPrice(pool, 5e7)
+    let breakerEvent;
+    const breakerEventListenerNumber = program.addEventListener("vaultPriceCircuitBreakerEvent", (event) => { breakerEvent = event })
+    await updatePrice()
+    const vaultFrozen = await program.account.secondaryVaultState.fetch(vaultState)
+    expect(vaultFrozen.priceFrozen).to.eql(true);
+    // the stored price & the backing do not move
+    expect(vaultFrozen.lstSolPriceP32.toString()).to.eql(TWO_POW_32);
+    const pendingPriceP32 = vaultFrozen.pendingLstSolPriceP32
+    expect(pendingPriceP32.gt(new BN(TWO_POW_32).muln(101).divn(100))).to.eql(true);
+    expect(vaultFrozen.pendingLstSolPriceTimestamp.toNumber()).to.be.greaterThan(0);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    program.removeEventListener(breakerEventListenerNumber)
+    expect(breakerEvent.referencePriceP32.toString()).to.eql(TWO_POW_32);
+    expect(breakerEvent.storedPriceP32.toString()).to.eql(TWO_POW_32);
+    expect(breakerEvent.newPriceP32.toString()).to.eql(pendingPriceP32.toString());
+    expect(breakerEvent.maxPriceIncreaseBp).to.eql(100);
+
+    // stake & claims are blocked while frozen
+    try {
+      await stakeTx()
+      expect.fail("should fail with VaultPriceFrozen")
+    } catch (ex) {
+      expect(ex.message).to.contain("VaultPriceFrozen")
+    }
+    await config0hsWaitTime(mainStateKeyPair)
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 0)
+    try {
+      await program.methods.ticketClaim(new BN(1e7.toFixed()), new BN(0))
+        .accounts({
+          mainState,
+          beneficiary: depositorUserKeyPair.publicKey,
+          ticketAccount: ticketKeyPair.publicKey,
+          lstMint,
+          beneficiaryLstAccount: depositorLstAta,
+          vaultLstAccount: vaultLstAta,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(lstState)
+        .signers([depositorUserKeyPair])
+        .rpc()
+      expect.fail("should fail with VaultPriceFrozen")
+    } catch (ex) {
+      expect(ex.message).to.contain("VaultPriceFrozen")
+    }
+
+    // the admin accepts the new price: applied with its read timestamp, the backing moves
+    const mainStatePreAcknowledge = await program.account.mainVaultState.fetch(mainState)
+    await program.methods.acknowledgeVaultPrice()
+      .accounts({ admin: wallet.publicKey, mainState, lstMint })
+      .rpc()
+    const vaultAcknowledged = await program.account.secondaryVaultState.fetch(vaultState)
+    expect(vaultAcknowledged.priceFrozen).to.eql(false);
+    expect(vaultAcknowledged.lstSolPriceP32.toString()).to.eql(pendingPriceP32.toString());
+    expect(vaultAcknowledged.lstSolPriceTimestamp.toString()).to.eql(vaultFrozen.pendingLstSolPriceTimestamp.toString());
+    expect(vaultAcknowledged.priceReferenceP32.toString()).to.eql(pendingPriceP32.toString());
+    expect(vaultAcknowledged.pendingLstSolPriceP32.toString()).to.eql("0");
+    const mainStateAcknowledged = await program.account.mainVaultState.fetch(mainState)
+    const vaultSolValueGain = vaultFrozen.vaultTotalLstAmount.mul(pendingPriceP32).div(new BN(TWO_POW_32))
+      .sub(vaultFrozen.vaultTotalLstAmount)
+    expect(mainStateAcknowledged.backingSolValue.sub(mainStatePreAcknowledge.backingSolValue).toString())
+      .to.eql(vaultSolValueGain.toString());
+    await stakeTx()
+  });
+
+  it("max price age: expired prices are refreshed or rejected", async () => {
+
+    // create main state, with 1e11 wSOL deposited by depositorUserKeyPair
+    const mpsolTokenMintKeyPair = Keypair.generate()
+    const { mainStateKeyPair, vaultAtaAuth, depositorMpSolAta } = await createAndTestMainState(mpsolTokenMintKeyPair);
+    const mainState = mainStateKeyPair.publicKey
+    const wSolMint = new PublicKey(WSOL_TOKEN_MINT)
+    const [wSolSecondaryStateAddress] = PublicKey.findProgramAddressSync(
+      [mainState.toBuffer(), wSolMint.toBuffer()], program.programId)
+    const vaultWSolAta = getAssociatedTokenAddressSync(wSolMint, vaultAtaAuth, true);
+    const depositorAtaWSol = getAssociatedTokenAddressSync(wSolMint, depositorUserKeyPair.publicKey, true);
+    const configureMaxPriceAge = (maxPriceAgeSeconds: BN | null, instantUnstakeCapPerEpoch: BN | null = null) =>
+      program.methods.configureSecondaryVault({ depositsDisabled: false, tokenDepositCap: null, instantUnstakeCapPerEpoch, priceEpochPolicy: null, maxPriceIncreaseBp: null, maxPriceDecreaseBp: null, maxPriceAgeSeconds })
+        .accounts({ admin: wallet.publicKey, mainState, lstMint: wSolMint })
+        .rpc()
+    const waitForPriceToExpire = async () => {
+      const vault = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress)
+      while (await provider.connection.getBlockTime(await provider.connection.getSlot()) <= vault.lstSolPriceTimestamp.toNumber() + 1) {
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+      }
+    }
+    const expectPriceRefreshed = async () => {
+      const vault = await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress)
+      const now = await provider.connection.getBlockTime(await provider.connection.getSlot())
+      expect(vault.lstSolPriceTimestamp.toNumber()).to.be.greaterThanOrEqual(now - 1);
+    }
+
+    // 0 (default) => 1 day, max 7 days
+    expect((await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress)).maxPriceAgeSeconds.toNumber()).to.eql(0);
+    try {
+      await configureMaxPriceAge(new BN(7 * 24 * 60 * 60 + 1))
+      expect.fail("should fail with InvalidMaxPriceAge")
+    } catch (ex) {
+      expect(ex.message).to.contain("InvalidMaxPriceAge")
+    }
+    await configureMaxPriceAge(new BN(1), new BN(5e9.toFixed()))
+    expect((await program.account.secondaryVaultState.fetch(wSolSecondaryStateAddress)).maxPriceAgeSeconds.toNumber()).to.eql(1);
+
+    // strategy profit accounting uses the stored price: rejected when expired
+    const stratStateKeyPair = Keypair.generate()
+    const [stratAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "authoritySeed"), stratStateKeyPair.publicKey.toBuffer()],
+      mockStrategyProgram.programId)
+    await mockStrategyProgram.methods.initialize()
+      .accounts({ payer: wallet.publicKey, state: stratStateKeyPair.publicKey, lstMint: wSolMint, tokenProgram: TOKEN_PROGRAM_ID })
+      .signers([stratStateKeyPair])
+      .rpc()
+    await program.methods.attachCommonStrategyState()
+      .accounts({
+        admin: wallet.publicKey,
+        mainState,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyProgramCode: mockStrategyProgram.programId,
+      })
+      .rpc()
+    const [vaultStratWithdrawAuth] = PublicKey.findProgramAddressSync(
+      [idlConstant(program.idl, "vaultStratWithdrawAtaAuthSeed"), stratStateKeyPair.publicKey.toBuffer()],
+      program.programId)
+    const lstWithdrawAccount = await createAta(provider, wallet, wSolMint, vaultStratWithdrawAuth, true)
+    const updateAttachedTx = () => program.methods.updateAttachedStratLstAmount()
+      .accounts({
+        mainState,
+        lstMint: wSolMint,
+        commonStrategyState: stratStateKeyPair.publicKey,
+        strategyDepositAccount: getAssociatedTokenAddressSync(wSolMint, stratAuth, true),
+        lstWithdrawAccount,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+        treasuryMpsolAccount: Keypair.generate().publicKey, // no treasury configured
+      })
+      .rpc()
+
+    await testGetUpdateVaultPriceMethod(mainStateKeyPair, "wSOL", WSOL_TOKEN_MINT).rpc()
+    await waitForPriceToExpire()
+    try {
+      await updateAttachedTx()
+      expect.fail("should fail with TokenSolPriceIsStale")
+    } catch (ex) {
+      expect(ex.message).to.contain("TokenSolPriceIsStale")
+    }
+    await testGetUpdateVaultPriceMethod(mainStateKeyPair, "wSOL", WSOL_TOKEN_MINT).rpc()
+    await updateAttachedTx()
+
+    // the liquidity reserved for tickets is valued at the stored price: transfers to strategies are rejected when expired
+    await waitForPriceToExpire()
+    try {
+      await program.methods.transferLstToStrat(new BN(1e9.toFixed()))
+        .accounts({
+          mainState,
+          operatorAuth: operatorAuthKeyPair.publicKey,
+          lstMint: wSolMint,
+          vaultLstAccount: vaultWSolAta,
+          strategyProgramCode: mockStrategyProgram.programId,
+          commonStrategyState: stratStateKeyPair.publicKey,
+          lstTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([operatorAuthKeyPair])
+        .rpc()
+      expect.fail("should fail with TokenSolPriceIsStale")
+    } catch (ex) {
+      expect(ex.message).to.contain("TokenSolPriceIsStale")
+    }
+
+    // stake refreshes the expired price before using it
+    await waitForPriceToExpire()
+    await sendTx(provider, wallet, [
+      anchor.web3.SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: depositorAtaWSol, lamports: 1e9 }),
+      createSyncNativeInstruction(depositorAtaWSol)
+    ])
+    await program.methods.stake(new BN(1e9.toFixed()), 0)
+      .accounts({
+        mainState,
+        lstMint: wSolMint,
+        vaultLstAccount: vaultWSolAta,
+        depositor: depositorUserKeyPair.publicKey,
+        depositorLstAccount: depositorAtaWSol,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        depositorMpsolAccount: depositorMpSolAta,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    await expectPriceRefreshed()
+
+    // ticket claim refreshes the expired price before using it
+    await config0hsWaitTime(mainStateKeyPair)
+    const ticketKeyPair = await testCreate1e10UnstakeTicket(mainStateKeyPair, depositorMpSolAta, 0)
+    await waitForPriceToExpire()
+    await program.methods.ticketClaim(new BN(1e9.toFixed()), new BN(0))
+      .accounts({
+        mainState,
+        beneficiary: depositorUserKeyPair.publicKey,
+        ticketAccount: ticketKeyPair.publicKey,
+        lstMint: wSolMint,
+        beneficiaryLstAccount: depositorAtaWSol,
+        vaultLstAccount: vaultWSolAta,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    await expectPriceRefreshed()
+
+    // instant unstake refreshes the expired price before using it
+    await waitForPriceToExpire()
+    await program.methods.unstakeInstant(new BN(1e9.toFixed()), new BN(0))
+      .accounts({
+        mainState,
+        unstaker: depositorUserKeyPair.publicKey,
+        unstakerMpsolAccount: depositorMpSolAta,
+        mpsolMint: mpsolTokenMintKeyPair.publicKey,
+        treasuryMpsolAccount: depositorMpSolAta, // treasury not set
+        lstMint: wSolMint,
+        unstakerLstAccount: depositorAtaWSol,
+        vaultLstAccount: vaultWSolAta,
+        lstTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([depositorUserKeyPair])
+      .rpc()
+    await expectPriceRefreshed()
+  });
+
+});
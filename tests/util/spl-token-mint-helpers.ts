@@ -24,7 +24,7 @@ import { createAssociatedTokenAccountInstruction } from "@solana/spl-token";
 import { createAssociatedTokenAccountIdempotentInstruction } from "@solana/spl-token";
 
 // Function to mint tokens
-export async function createAta(provider: Provider, wallet: any, mint: PublicKey, recipient: PublicKey)
+export async function createAta(provider: Provider, wallet: any, mint: PublicKey, recipient: PublicKey, allowOwnerOffCurve: boolean = false)
     : Promise<PublicKey> {
 
     const connection: Connection = provider.connection
@@ -32,7 +32,7 @@ export async function createAta(provider: Provider, wallet: any, mint: PublicKey
     const instructions: TransactionInstruction[] = [];
 
     // 1. Get the associated token account address for the recipient
-    const associatedTokenAddress = await getAssociatedTokenAddressSync(mint, recipient);
+    const associatedTokenAddress = await getAssociatedTokenAddressSync(mint, recipient, allowOwnerOffCurve);
     console.log("associatedTokenAddress", associatedTokenAddress.toBase58())
 
     instructions.push(createAssociatedTokenAccountInstruction(